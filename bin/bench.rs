@@ -12,7 +12,7 @@ use gnuplot::{AxesCommon, Caption, Figure};
 use other_octree::Octree as OtherOctree;
 
 use octree::point::Point3D;
-use octree::Octree;
+use octree::{Locatable, Octree};
 
 // To satisfy the other crate's requirement.
 struct Point3DIterator {
@@ -56,7 +56,7 @@ fn read_points(file: File) -> Vec<Point3D> {
     points
 }
 
-fn bench_test(points: &Vec<Point3D>) -> BTreeMap<usize, f64> {
+fn bench_test(points: &[Point3D]) -> BTreeMap<usize, f64> {
     let mut results = BTreeMap::new();
     for i in (0..=points.len()).step_by(1000) {
         results.insert(
@@ -69,13 +69,26 @@ fn bench_test(points: &Vec<Point3D>) -> BTreeMap<usize, f64> {
     results
 }
 
-fn bench_test_baseline(points: &Vec<Point3D>) -> BTreeMap<usize, f64> {
+fn bench_test_parallel(points: &[Point3D]) -> BTreeMap<usize, f64> {
+    let mut results = BTreeMap::new();
+    for i in (0..=points.len()).step_by(1000) {
+        results.insert(
+            i,
+            timeit_loops!(10, {
+                Octree::new_parallel(points.iter().take(i).collect());
+            }),
+        );
+    }
+    results
+}
+
+fn bench_test_baseline(points: &[Point3D]) -> BTreeMap<usize, f64> {
     let mut results = BTreeMap::new();
     for i in (1..points.len()).step_by(1000) {
         results.insert(
             i,
             timeit_loops!(10, {
-                let points_used: Vec<Point3D> = points.clone().into_iter().take(i).collect();
+                let points_used: Vec<Point3D> = points.iter().take(i).cloned().collect();
                 let mut tree = OtherOctree::new(Wapper(points_used));
                 tree.build(8);
             }),
@@ -84,6 +97,22 @@ fn bench_test_baseline(points: &Vec<Point3D>) -> BTreeMap<usize, f64> {
     results
 }
 
+// Sequential k-nearest lookups against the rayon batch, for every point as query location.
+fn bench_k_nearest(points: &[Point3D], k: usize) -> (f64, f64) {
+    let octree = Octree::new(points.iter().collect());
+    let locations: Vec<[f32; 3]> = points.iter().map(|p| p.get_location()).collect();
+    let sequential = timeit_loops!(10, {
+        let _: Vec<Vec<&Point3D>> = locations
+            .iter()
+            .map(|location| octree.k_nearest(location, k))
+            .collect();
+    });
+    let batch = timeit_loops!(10, {
+        octree.k_nearest_batch_parallel(&locations, k);
+    });
+    (sequential, batch)
+}
+
 #[cfg_attr(tarpaulin, skip)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(debug_assertions)]
@@ -107,7 +136,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         sender1_cloned.send(results).unwrap();
     }));
 
-    let points_cloned = points;
+    let points_cloned = points.clone();
     let sender2_cloned = sender2.clone();
     handles.push(spawn(move || {
         let results = bench_test(&points_cloned);
@@ -120,6 +149,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let baseline_results = receiver1.recv().unwrap();
     let results = receiver2.recv().unwrap();
+    // Uses all cores itself, so it runs after the single thread benchmarks.
+    let parallel_results = bench_test_parallel(&points);
 
     drop(sender1);
     drop(sender2);
@@ -128,6 +159,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let num_points: Vec<usize> = results.keys().cloned().collect();
     let durations: Vec<f64> = results.values().cloned().collect();
     let durations_baseline: Vec<f64> = baseline_results.values().cloned().collect();
+    let durations_parallel: Vec<f64> = parallel_results.values().cloned().collect();
 
     figure
         .axes2d()
@@ -136,6 +168,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             &durations,
             &[Caption("My Octree, Single Thread")],
         )
+        .lines_points(
+            &num_points,
+            &durations_parallel,
+            &[Caption("My Octree, Rayon")],
+        )
         .lines_points(&num_points, &durations_baseline, &[Caption("Baseline")])
         .set_title("Octree Building Benchmark", &[])
         .set_x_label("Number of Points", &[])
@@ -146,5 +183,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("Failed to save figure");
 
     println!("Benchmark result is saved to {}", output_path);
+
+    let (sequential, batch) = bench_k_nearest(&points, 8);
+    println!(
+        "8-nearest for {} locations: {:.6} sec sequential, {:.6} sec k_nearest_batch_parallel",
+        points.len(),
+        sequential,
+        batch
+    );
     Ok(())
 }
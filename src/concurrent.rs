@@ -0,0 +1,233 @@
+//! Optional thread safe sharing of an [Octree]. Many threads may query one [ConcurrentOctree] at once, while a writer
+//! updating it waits for them, and they wait for the writer.
+
+use crate::{BoundingBox, Locatable, Octree, Scalar};
+use core::hash::Hash;
+use std::collections::HashSet;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// An [Octree] behind a [RwLock], to be shared between threads, e.g. in an [Arc](std::sync::Arc) or with
+/// [std::thread::scope]. [query](ConcurrentOctree::query) takes the lock for reading,
+/// [insert](ConcurrentOctree::insert) and [delete](ConcurrentOctree::delete) take it for writing, all through `&self`.
+///
+/// The [Octree] only borrows its points, so they must outlive the [ConcurrentOctree] as usual: the threads share the
+/// `&'point L` references, and the [ConcurrentOctree] is [Send] and [Sync] as long as `L` is [Sync]. The results of a
+/// query are these references too, so they stay valid after the lock is released, while the tree may already have
+/// changed. Use [read](ConcurrentOctree::read) or [write](ConcurrentOctree::write) to run several operations under
+/// one lock, or any other method of [Octree].
+///
+/// Requires the `sync` feature.
+///
+/// # Panics
+/// Every method panics if the lock is poisoned, i.e. a thread panicked while updating the [Octree], which may have
+/// been left half updated.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::{BoundingBox, ConcurrentOctree, Octree};
+///
+/// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+/// let point = Point3D::new(5.0, 5.0, 5.0);
+/// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| octree.insert(&point));
+///     scope.spawn(|| octree.query(&BoundingBox::new(points.iter().collect())));
+/// });
+/// assert_eq!(octree.read().len(), 3);
+/// ```
+pub struct ConcurrentOctree<'point, L, T = f32> {
+    octree: RwLock<Octree<'point, L, T>>,
+}
+
+impl<'point, L, T> ConcurrentOctree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    /// Share an [Octree] between threads.
+    pub fn new(octree: Octree<'point, L, T>) -> Self {
+        Self {
+            octree: RwLock::new(octree),
+        }
+    }
+
+    /// Take the [Octree] back, once it is no longer shared.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    ///
+    /// assert_eq!(octree.into_inner(), Octree::new(points.iter().collect()));
+    /// ```
+    pub fn into_inner(self) -> Octree<'point, L, T> {
+        self.octree.into_inner().unwrap()
+    }
+
+    /// Lock the [Octree] for reading, blocking until no thread writes it. Other threads may read it meanwhile.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    ///
+    /// assert_eq!(octree.read().nearest([1.0, 1.0, 1.0]), Some(&points[0]));
+    /// ```
+    pub fn read(&self) -> RwLockReadGuard<'_, Octree<'point, L, T>> {
+        self.octree.read().unwrap()
+    }
+
+    /// Lock the [Octree] for writing, blocking until no other thread reads or writes it.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let point = Point3D::new(20.0, 20.0, 20.0);
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    ///
+    /// assert!(octree.write().insert_growing(&point));
+    /// assert!(octree.read().contains(&point));
+    /// ```
+    pub fn write(&self) -> RwLockWriteGuard<'_, Octree<'point, L, T>> {
+        self.octree.write().unwrap()
+    }
+
+    /// Find the points covered by a [BoundingBox] like [Octree::query], under a read lock.
+    /// # Example
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    ///
+    /// assert_eq!(octree.query(&bounding_box), HashSet::from([&points[0]]));
+    /// ```
+    pub fn query(&self, bounding_box: &BoundingBox<T>) -> HashSet<&'point L> {
+        self.read().query(bounding_box)
+    }
+
+    /// Insert a point like [Octree::insert], under a write lock.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let point1 = Point3D::new(5.0, 5.0, 5.0);
+    /// let point2 = Point3D::new(20.0, 20.0, 20.0);
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    ///
+    /// assert!(octree.insert(&point1));
+    /// assert!(!octree.insert(&point2));
+    /// ```
+    pub fn insert(&self, point: &'point L) -> bool {
+        self.write().insert(point)
+    }
+
+    /// Delete a point like [Octree::delete], under a write lock.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ConcurrentOctree, Octree};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let octree = ConcurrentOctree::new(Octree::new(points.iter().collect()));
+    ///
+    /// assert!(octree.delete(&points[0]));
+    /// assert!(!octree.delete(&points[0]));
+    /// ```
+    pub fn delete(&self, point: &'point L) -> bool {
+        self.write().delete(point)
+    }
+}
+
+impl<'point, L, T> From<Octree<'point, L, T>> for ConcurrentOctree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn from(octree: Octree<'point, L, T>) -> Self {
+        Self::new(octree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point3D;
+    use crate::OctreeBuilder;
+
+    /// Compile only if `S` can be shared between threads.
+    fn assert_send_sync<S: Send + Sync>() {}
+
+    #[test]
+    /// Should be shareable between threads whenever the points are.
+    fn test_send_sync() {
+        assert_send_sync::<ConcurrentOctree<Point3D>>();
+        assert_send_sync::<ConcurrentOctree<crate::point::Point3D64, f64>>();
+    }
+
+    #[test]
+    /// Should keep a valid tree, and never let a reader see a point half inserted, while one writer churns points
+    /// and several readers query concurrently.
+    fn test_concurrent_stress() {
+        let fixed: Vec<Point3D> = (0..64)
+            .map(|i| Point3D::new((i % 4) as f32, (i / 4 % 4) as f32, (i / 16) as f32))
+            .collect();
+        let moving: Vec<Point3D> = (0..256)
+            .map(|i| {
+                Point3D::new(
+                    (i * 7 % 30) as f32 / 10.0 + 0.05,
+                    (i * 11 % 29) as f32 / 10.0 + 0.05,
+                    (i * 13 % 23) as f32 / 10.0 + 0.05,
+                )
+            })
+            .collect();
+        let octree = ConcurrentOctree::new(
+            OctreeBuilder::new()
+                .capacity(4)
+                .build(fixed.iter().collect()),
+        );
+        let everything = octree.read().bounds().clone();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..20 {
+                    for point in &moving {
+                        assert!(octree.insert(point));
+                    }
+                    for point in &moving {
+                        assert!(octree.delete(point));
+                    }
+                }
+            });
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..200 {
+                        let found = octree.query(&everything);
+                        // The writer only holds the lock between whole operations.
+                        assert!(fixed.iter().all(|point| found.contains(point)));
+                        assert!(found.len() <= fixed.len() + moving.len());
+                        let octree = octree.read();
+                        assert_eq!(octree.len(), octree.iter().count());
+                    }
+                });
+            }
+        });
+
+        let octree = octree.into_inner();
+        assert_eq!(octree.len(), fixed.len());
+        assert!(octree.check_invariants().is_ok());
+    }
+}
@@ -1,44 +1,501 @@
 //! A highly optimized [octree](https://en.wikipedia.org/wiki/Octree) implementation, with threading enabled for improved efficiency. This octree implementation is also capable of tracking highly dynamic environment.
+//!
+//! The crate is `no_std` compatible. The default `std` feature stores points in `std::collections::HashSet`; disable
+//! default features and enable `alloc` to use [hashbrown](https://docs.rs/hashbrown)'s `HashSet` instead, which only
+//! needs an allocator, with float functions from [libm](https://docs.rs/libm).
+//!
+//! The points stored in the tree are hashed with SipHash, the standard library's default, which resists HashDoS but is
+//! slow for the small keys of spatial data. The `fast-hash` feature swaps in [foldhash](https://docs.rs/foldhash)
+//! instead, which is much faster but can be attacked by crafted input. It only changes the sets inside the tree: the
+//! [HashSet]s returned by queries such as [Octree::query] keep the default hasher, and [Octree::query_vec] avoids
+//! hashing the results altogether. Without `std`, hashbrown already uses foldhash.
+//!
+//! Coordinates are [f32] by default. Every type taking coordinates has a [Scalar] parameter which defaults to [f32],
+//! so data which needs more precision, e.g. geodetic coordinates, can use [f64] instead, such as
+//! `Octree<Point3D64, f64>` with [Point3D64](point::Point3D64).
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("either the `std` or the `alloc` feature must be enabled");
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BinaryHeap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+use core::fmt::{self, Debug, Formatter, Write};
+use core::hash::Hash;
+#[cfg(not(feature = "std"))]
+use hashbrown::HashSet;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
 use std::collections::HashSet;
-use std::hash::Hash;
 
+/// The set of points held by a [TreeNode], hashed with foldhash if the `fast-hash` feature is enabled.
+#[cfg(feature = "fast-hash")]
+type PointSet<'point, L> = HashSet<&'point L, foldhash::fast::RandomState>;
+#[cfg(not(feature = "fast-hash"))]
+type PointSet<'point, L> = HashSet<&'point L>;
+
+#[cfg(feature = "sync")]
+mod concurrent;
 pub mod point;
+mod scalar;
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg(feature = "sync")]
+pub use concurrent::ConcurrentOctree;
+pub use scalar::Scalar;
+#[cfg(feature = "serde")]
+pub use serialization::OctreeData;
+
 /// Calculates the location of your object in a 3d space. Your data structure must implement this trait so [Octree] can
-/// can get location of each data point. A sample implementation is provided in [Point3D](point::Point3D).
-pub trait Locatable {
-    fn get_location(&self) -> [f32; 3];
+/// can get location of each data point. A sample implementation is provided in [Point3D](point::Point3D). The
+/// coordinates are of type `T`, [f32] unless another [Scalar] is chosen.
+///
+/// [get_location](Locatable::get_location) is called for every point a node looks at, so it should be cheap, e.g.
+/// return coordinates stored in the point. The location is returned by value on purpose: three coordinates fit into
+/// registers, so returning a reference would not save a copy, and a reference could not be returned at all for
+/// locations which are computed, like the snapped one of [ApproxPoint](point::ApproxPoint).
+///
+/// Raw coordinates, `[T; 3]` and `(T, T, T)`, are [Locatable] too, e.g. to construct a [BoundingBox] straight from
+/// them. An [Octree] can not hold them though, as floats are neither [Eq] nor [Hash]; convert them into a
+/// [Point](point::Point) instead, which compares and hashes the bits of its coordinates.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::{BoundingBox, Octree};
+///
+/// let coordinates = vec![[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]];
+/// let bounding_box = BoundingBox::new(coordinates.iter().collect());
+/// assert_eq!(bounding_box.get_max(), &[1.0, 2.0, 3.0]);
+///
+/// let points: Vec<Point3D> = coordinates.into_iter().map(Point3D::from).collect();
+/// let octree = Octree::new(points.iter().collect());
+/// assert!(octree.contains(&Point3D::from((1.0, 2.0, 3.0))));
+/// ```
+pub trait Locatable<T = f32> {
+    fn get_location(&self) -> [T; 3];
+}
+
+impl<T> Locatable<T> for [T; 3]
+where
+    T: Copy,
+{
+    fn get_location(&self) -> [T; 3] {
+        *self
+    }
+}
+
+impl<T> Locatable<T> for (T, T, T)
+where
+    T: Copy,
+{
+    fn get_location(&self) -> [T; 3] {
+        [self.0, self.1, self.2]
+    }
 }
 
 /// The [Octree](https://en.wikipedia.org/wiki/Octree) data structure.
+///
+/// Only leaves hold points. A leaf which gets more points than its capacity is splitted and pushes all of them down
+/// into its children, unless they all share one location, and a node whose sub tree is left with no more than its
+/// capacity merges its children back. So the structure is a function of the points stored and the settings, not of
+/// the order they came in: [Octree]s over the same extent holding the same points compare equal with `==`. Only a
+/// root grown by [Octree::insert_growing] or [Octree::merge] depends on the history, and a loose node keeps the points
+/// in its margin which none of its children covers.
+///
+/// Points are told apart by [Eq], not by location. Distinct points at one location are all stored, together in one
+/// leaf, which then holds more points than its capacity if there are that many, as no split could separate them.
+/// Every query, e.g. [Octree::query] or [Octree::k_nearest], returns all of them, while points which are equal are only
+/// stored once.
+pub struct Octree<'point, L, T = f32> {
+    root: TreeNode<'point, L, T>,
+    on_change: Option<ChangeCallback<'point, L>>,
+    changes: Option<Changes<'point, L>>,
+    generation: u64,
+    // The settings shared by every node, passed down to them by reference.
+    config: OctreeConfig,
+    // Whether the root BoundingBox was given rather than derived from the points, so rebuilding keeps it.
+    explicit_bounds: bool,
+}
+
+/// Callback registered by [Octree::on_change].
+type ChangeCallback<'point, L> = Box<dyn FnMut(ChangeEvent<&L>) + Send + Sync + 'point>;
+
+/// A change applied to an [Octree], reported to the callback registered by [Octree::on_change].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeEvent<T> {
+    /// A point was inserted into the [Octree].
+    Inserted(T),
+    /// A point was removed from the [Octree].
+    Removed(T),
+}
+
+impl<T> ChangeEvent<T> {
+    /// Map the point carried by this [ChangeEvent], keeping the kind of change.
+    /// # Example
+    /// ```
+    /// use octree::ChangeEvent;
+    ///
+    /// assert_eq!(ChangeEvent::Inserted(&1).map(|x| x + 1), ChangeEvent::Inserted(2));
+    /// ```
+    pub fn map<U, F>(self, f: F) -> ChangeEvent<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            ChangeEvent::Inserted(point) => ChangeEvent::Inserted(f(point)),
+            ChangeEvent::Removed(point) => ChangeEvent::Removed(f(point)),
+        }
+    }
+}
+
+/// The points inserted into and deleted from an [Octree] since tracking began or the changes were last taken, returned
+/// by [Octree::take_changes]. Applying the deletions and then the insertions to the points recorded back then gives the
+/// points recorded now, e.g. to send a delta over the network. A point inserted and deleted in between is in neither
+/// set, while a point deleted and inserted back, e.g. moved by [Octree::update], is in both, so a point told apart from
+/// the old one by more than [Eq] is not lost.
 #[derive(Debug)]
-pub struct Octree<'point, L> {
-    root: TreeNode<'point, L>,
+pub struct Changes<'point, L> {
+    inserted: HashSet<&'point L>,
+    deleted: HashSet<&'point L>,
+}
+
+impl<'point, L> Clone for Changes<'point, L> {
+    fn clone(&self) -> Self {
+        Self {
+            inserted: self.inserted.clone(),
+            deleted: self.deleted.clone(),
+        }
+    }
+}
+
+impl<'point, L> PartialEq for Changes<'point, L>
+where
+    L: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inserted == other.inserted && self.deleted == other.deleted
+    }
+}
+
+impl<'point, L> Eq for Changes<'point, L> where L: Eq + Hash {}
+
+impl<'point, L> Changes<'point, L>
+where
+    L: Eq + Hash,
+{
+    /// Construct an empty record of changes.
+    fn new() -> Self {
+        Self {
+            inserted: HashSet::new(),
+            deleted: HashSet::new(),
+        }
+    }
+
+    /// Record a change.
+    fn record(&mut self, event: ChangeEvent<&'point L>) {
+        match event {
+            ChangeEvent::Inserted(point) => {
+                self.inserted.insert(point);
+            }
+            ChangeEvent::Removed(point) => {
+                if !self.inserted.remove(point) {
+                    self.deleted.insert(point);
+                }
+            }
+        }
+    }
+
+    /// Getter for the points inserted, to be applied after the [deleted](Changes::get_deleted) ones.
+    pub fn get_inserted(&self) -> &HashSet<&'point L> {
+        &self.inserted
+    }
+
+    /// Getter for the points deleted, to be applied before the [inserted](Changes::get_inserted) ones.
+    pub fn get_deleted(&self) -> &HashSet<&'point L> {
+        &self.deleted
+    }
+
+    /// Check if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// What [Octree::try_insert] did with a point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The point was recorded.
+    Inserted,
+    /// An equal point was already recorded, so nothing changed.
+    AlreadyPresent,
+    /// The point is not covered by the [Octree], so nothing changed. Use [Octree::insert_growing] to grow the
+    /// [Octree] instead.
+    OutOfBounds,
+}
+
+/// Why [Octree::try_insert] could not even try to insert a point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OctreeError<T = f32> {
+    /// The point has an infinite or NaN coordinate, at the location given, which no [Octree] can cover.
+    NonFinite([T; 3]),
+}
+
+impl<T> fmt::Display for OctreeError<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OctreeError::NonFinite(location) => {
+                write!(
+                    f,
+                    "the point {location:?} has a coordinate which is not finite"
+                )
+            }
+        }
+    }
 }
 
-/// Recursive data structure for tree node. Its children is None if not splitted. If splitted, then its children is an
-/// array of length 8 of (points of) sub tree nodes.
+#[cfg(feature = "std")]
+impl<T> std::error::Error for OctreeError<T> where T: Debug {}
+
+/// Recursive data structure for tree node. Its children is None if not splitted. If splitted, then its children are
+/// the sub tree nodes of the octants it uses, 2, 4 or 8 of them depending on the axes it is splitted along, in the
+/// order of their octants.
 #[derive(Debug)]
-struct TreeNode<'point, L> {
-    children: Option<[Box<TreeNode<'point, L>>; 8]>,
-    bounding_box: BoundingBox,
-    points: HashSet<&'point L>,
-    capacity: usize,
+struct TreeNode<'point, L, T = f32> {
+    children: Option<Vec<TreeNode<'point, L, T>>>,
+    bounding_box: BoundingBox<T>,
+    points: PointSet<'point, L>,
     splitted: bool,
+    // Bit i is set if the node is splitted along axis i.
+    split_axes: u8,
+    // Bit i is set if the node also covers its max surface along axis i, the one it shares with an inclusive root.
+    closed_axes: u8,
+    // The number of points in this sub tree, updated by every change through recount.
+    subtree_count: usize,
 }
 
 /// Bounding box defines a 3D space.
 #[derive(Clone, Debug, PartialEq)]
-pub struct BoundingBox {
-    min: [f32; 3],
-    max: [f32; 3],
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox<T = f32> {
+    min: [T; 3],
+    max: [T; 3],
+}
+
+/// Builder for [Octree]s with non default settings, which sets the fields of an [OctreeConfig] one by one.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::OctreeBuilder;
+///
+/// let point1 = Point3D::new(0.0, 0.0, 0.0);
+/// let point2 = Point3D::new(10.0, 10.0, 10.0);
+/// let points = vec![point1, point2];
+///
+/// let octree = OctreeBuilder::new()
+///     .loose_factor(0.25)
+///     .build(points.iter().collect());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OctreeBuilder {
+    config: OctreeConfig,
+}
+
+/// The settings of an [Octree], shared by all of its nodes, for [Octree::with_config] and returned by
+/// [Octree::config]. The [default](OctreeConfig::default) is the behaviour of [Octree::new]. [OctreeBuilder] sets
+/// them one by one, and documents each of them.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::{Octree, OctreeConfig};
+///
+/// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+/// let config = OctreeConfig {
+///     capacity: 4,
+///     ..OctreeConfig::default()
+/// };
+///
+/// let octree = Octree::with_config(points.iter().collect(), config);
+/// assert_eq!(octree.config().capacity, 4);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OctreeConfig {
+    /// The number of points a node holds before it is splitted. See [OctreeBuilder::capacity].
+    pub capacity: usize,
+    /// How far every node is grown on every side to take points, relative to its size. See
+    /// [OctreeBuilder::loose_factor].
+    pub loose_factor: f32,
+    /// How [Octree::insert_growing] grows the root. See [OctreeBuilder::growth_policy].
+    pub growth_policy: GrowthPolicy,
+    /// The axis collapsed to get a quadtree, if any. See [OctreeBuilder::project_axis].
+    pub project_axis: Option<usize>,
+    /// The ratio to the largest extent of a node below which an axis is not splitted. See
+    /// [OctreeBuilder::split_ratio].
+    pub split_ratio: f32,
+    /// The depth at which nodes are no longer splitted. See [OctreeBuilder::max_depth].
+    pub max_depth: usize,
+    /// Whether [Octree::insert] grows the root to cover the point like [Octree::insert_growing]. See
+    /// [OctreeBuilder::grow_on_insert].
+    pub grow_on_insert: bool,
+    /// Whether the root also covers its max surface. See [OctreeBuilder::boundary_inclusive].
+    pub boundary_inclusive: bool,
+}
+
+/// Statistics of the structure of an [Octree], returned by [Octree::stats], e.g. to tune
+/// [capacity](OctreeBuilder::capacity) or to spot degenerate trees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OctreeStats {
+    /// The largest depth of any node, 0 if the root has never been splitted. See [Octree::depth].
+    pub depth: usize,
+    /// The number of nodes, including the root. See [Octree::node_count].
+    pub node_count: usize,
+    /// The number of recorded points. See [Octree::len].
+    pub point_count: usize,
+    /// The most points stored by any single node, whether a leaf or not.
+    pub max_points_per_node: usize,
+}
+
+/// How [Octree::insert_growing] grows the root of an [Octree] to cover a point outside of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrowthPolicy {
+    /// Double the size of the root towards the point, until the point is covered. Every time the old root becomes one
+    /// of the children of the new root, so the tree is kept as it is, but the root may become much larger than
+    /// needed.
+    #[default]
+    Double,
+    /// Grow the root just enough to cover the point, and rebuild the tree. The root stays tight, but every growth
+    /// costs as much as constructing a new [Octree].
+    Fit,
+}
+
+/// One of the eight octants a [BoundingBox] is splitted into, named after its side on each axis: down or up along z,
+/// front or back along y, left or right along x. Its [index](Octant::index) is the position of its [BoundingBox] in
+/// [BoundingBox::split], and of the child in [NodeCursor::child].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Octant {
+    /// Down, front, left: the lower half on every axis.
+    Dfl = 0,
+    /// Down, front, right.
+    Dfr = 1,
+    /// Down, back, left.
+    Dbl = 2,
+    /// Down, back, right.
+    Dbr = 3,
+    /// Up, front, left.
+    Ufl = 4,
+    /// Up, front, right.
+    Ufr = 5,
+    /// Up, back, left.
+    Ubl = 6,
+    /// Up, back, right: the upper half on every axis.
+    Ubr = 7,
+}
+
+/// Read only handle on a node of an [Octree], returned by [Octree::root_cursor]. It can be used to walk the tree with
+/// custom algorithms, while the tree itself stays private.
+pub struct NodeCursor<'node, 'point, L, T = f32> {
+    node: &'node TreeNode<'point, L, T>,
+    depth: usize,
+}
+
+/// A grid cell holding the points snapped to it by [Octree::new_quantized]. Cells are located at the snapped location,
+/// and two cells are equal if they are at the same location.
+pub struct Quantized<'point, L, T = f32> {
+    location: [T; 3],
+    points: Vec<&'point L>,
+}
+
+/// Linear motion of a point between two frames, used by [Octree::swept_pairs_within]. It is located at the centre of
+/// the motion, and identified by its index.
+struct Sweep<'a, L, T> {
+    index: usize,
+    point: &'a L,
+    start: [T; 3],
+    end: [T; 3],
+}
+
+/// An item with its squared distance to a query location, or its distance by the metric of [Octree::nearest_by],
+/// ordered by the distance only, used by [Octree::k_nearest] and [Octree::nearest_iter].
+struct Nearest<T, I> {
+    distance_sq: T,
+    item: I,
+}
+
+/// Queue of the nodes still to visit in a best first search, closest first.
+type NodeQueue<'node, 'point, L, T> =
+    BinaryHeap<Reverse<Nearest<T, &'node TreeNode<'point, L, T>>>>;
+
+/// Iterator over the points of a consumed [Octree], returned by [Octree::into_iter]. It yields the stored references
+/// node by node, taking each node apart once its points are yielded.
+pub struct IntoIter<'point, L, T = f32> {
+    nodes: Vec<TreeNode<'point, L, T>>,
+    points: Vec<&'point L>,
+    remaining: usize,
+}
+
+/// Lazy best first search returned by [Octree::nearest_iter]. Nodes and points share one queue, closest first, so a
+/// node is only opened once nothing closer is left to yield.
+struct NearestIter<'node, 'point, L, T> {
+    root: &'node TreeNode<'point, L, T>,
+    config: &'node OctreeConfig,
+    location: [T; 3],
+    queue: BinaryHeap<Reverse<Nearest<T, Candidate<'node, 'point, L, T>>>>,
 }
 
-impl<'point, L> Octree<'point, L>
+/// An entry of the queue of [NearestIter].
+enum Candidate<'node, 'point, L, T> {
+    Node(&'node TreeNode<'point, L, T>),
+    Point(&'point L),
+}
+
+/// The fewest points [Octree::new_parallel] hands to a node as a separate task, and the most points of a sub tree
+/// which [Octree::query_parallel] searches sequentially, as tasks would cost more than they save for fewer points.
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 1024;
+
+/// The default depth below which nodes are not splitted, see [OctreeBuilder::max_depth]. Halving the root 32 times
+/// already exhausts the precision of [f32].
+const MAX_DEPTH: usize = 32;
+
+/// Which side of a plane a [BoundingBox] lies on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlaneSide {
+    Front,
+    Back,
+    Crossing,
+}
+
+/// Total order on locations, by x, then y, then z.
+fn compare_locations<T>(a: &[T; 3], b: &[T; 3]) -> Ordering
+where
+    T: Scalar,
+{
+    a[0].total_cmp(&b[0])
+        .then_with(|| a[1].total_cmp(&b[1]))
+        .then_with(|| a[2].total_cmp(&b[2]))
+}
+
+impl<'point, L, T> Octree<'point, L, T>
 where
-    L: Locatable + Eq + Hash,
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
 {
-    /// Construct an [Octree] that covers all given points.
+    /// Construct an [Octree] that covers all given points. As a [BoundingBox] does not cover its max surface, the max
+    /// corner of the root is moved up by the smallest possible step along every axis, so the points on it, or all
+    /// points along an axis on which they have the same coordinate, are covered and stored too.
+    /// Points with an infinite or NaN coordinate can not be covered and are left out, see [BoundingBox::new].
     /// # Example
     /// ```
     /// use octree::point::Point3D;
@@ -54,11 +511,114 @@ where
     /// ```
     pub fn new(points: Vec<&'point L>) -> Self {
         Self {
-            root: TreeNode::new(points),
+            root: TreeNode::new(&OctreeConfig::default(), points),
+            ..Default::default()
+        }
+    }
+
+    /// Construct an [Octree] that covers all given points, like [Octree::new], but build the sub trees of the root, and
+    /// of their large enough descendants, as separate tasks on the [rayon] thread pool. The result is equal to the one
+    /// of [Octree::new], so the choice only matters for speed: large point clouds scale across cores, while for small
+    /// ones [Octree::new] is faster. The points are shared between threads, thus `L` must be [Sync]. Requires the
+    /// `rayon` feature.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..1000)
+    ///     .map(|i| Point3D::new((i % 10) as f32, (i / 10 % 10) as f32, (i / 100) as f32))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     Octree::new_parallel(points.iter().collect()),
+    ///     Octree::new(points.iter().collect())
+    /// );
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn new_parallel(points: Vec<&'point L>) -> Self
+    where
+        L: Sync,
+    {
+        let mut root = TreeNode {
+            // So the created bounding box contains every point in points.
+            bounding_box: BoundingBox::covering(points.clone()),
+            ..Default::default()
+        };
+        root.extend_parallel(
+            &OctreeConfig::default(),
+            points.into_iter().enumerate().collect(),
+            &mut Vec::new(),
+            0,
+        );
+        Self {
+            root,
+            ..Default::default()
         }
     }
 
-    /// Insert a new point. If [Octree] does not cover the new point then nothing will change.
+    /// Construct an empty [Octree] over a fixed extent, e.g. a known world volume, instead of deriving it from the
+    /// points. The nodes are then splitted along the same planes whatever points are inserted, and in whatever order,
+    /// which keeps the octant layout stable from frame to frame. Points which are not covered by the [BoundingBox] are
+    /// dropped by [Octree::insert], see [OctreeBuilder::build_with_bounds]. This is the way to start empty and insert,
+    /// as the [default](Octree::default) [Octree] covers nothing until it grows.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(100.0, 100.0, 100.0)].iter().collect());
+    /// let point1 = Point3D::new(10.0, 20.0, 30.0);
+    /// let point2 = Point3D::new(200.0, 20.0, 30.0);
+    ///
+    /// let mut octree = Octree::with_bounds(bounding_box.clone());
+    /// assert!(octree.is_empty());
+    /// assert!(octree.insert(&point1));
+    /// assert!(!octree.insert(&point2));
+    /// assert_eq!(octree.bounds(), &bounding_box);
+    /// ```
+    pub fn with_bounds(bounding_box: BoundingBox<T>) -> Self {
+        OctreeBuilder::new().build_with_bounds(bounding_box, Vec::new())
+    }
+
+    /// Construct an [Octree] that covers all given points, with the settings of an [OctreeConfig]. They are checked as
+    /// by the setters of [OctreeBuilder], e.g. a capacity of 0 is treated as 1.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Octree, OctreeConfig};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(20.0, 20.0, 20.0);
+    /// let points = vec![point1, point2];
+    /// let config = OctreeConfig {
+    ///     capacity: 1,
+    ///     grow_on_insert: true,
+    ///     ..OctreeConfig::default()
+    /// };
+    ///
+    /// let mut octree = Octree::with_config(points.iter().collect(), config);
+    /// assert!(octree.insert(&point3));
+    /// assert_eq!(octree.len(), 3);
+    /// assert_eq!(Octree::with_config(points.iter().collect(), OctreeConfig::default()), Octree::new(points.iter().collect()));
+    /// ```
+    pub fn with_config(points: Vec<&'point L>, config: OctreeConfig) -> Self {
+        OctreeBuilder::new()
+            .capacity(config.capacity)
+            .loose_factor(config.loose_factor)
+            .growth_policy(config.growth_policy)
+            .project_axis(config.project_axis)
+            .split_ratio(config.split_ratio)
+            .max_depth(config.max_depth)
+            .grow_on_insert(config.grow_on_insert)
+            .boundary_inclusive(config.boundary_inclusive)
+            .build(points)
+    }
+
+    /// Insert a new point, returning whether it was recorded. If [Octree] does not cover the new point, or an equal
+    /// point is already recorded, then nothing will change. Use [Octree::insert_growing], or build the [Octree] with
+    /// [OctreeBuilder::grow_on_insert], to grow it instead, or [Octree::try_insert] to tell the cases apart.
     /// # Example
     /// ```
     /// use octree::point::Point3D;
@@ -77,10 +637,47 @@ where
     /// assert!(!octree.insert(&point4));
     /// ```
     pub fn insert(&mut self, point: &'point L) -> bool {
-        self.root.insert(point)
+        matches!(self.try_insert(point), Ok(InsertOutcome::Inserted))
+    }
+
+    /// Insert a new point like [Octree::insert], but report what happened to it: whether it was recorded, was already
+    /// recorded, or is out of bounds. A point with an infinite or NaN coordinate, which no [Octree] can cover, is
+    /// reported as an error instead. The callback registered by [Octree::on_change] is only told about a point which
+    /// is [Inserted](InsertOutcome::Inserted).
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{InsertOutcome, Octree, OctreeError};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let point1 = Point3D::new(5.0, 5.0, 5.0);
+    /// let point2 = Point3D::new(20.0, 5.0, 5.0);
+    /// let point3 = Point3D::new(f32::NAN, 5.0, 5.0);
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.try_insert(&point1), Ok(InsertOutcome::Inserted));
+    /// assert_eq!(octree.try_insert(&point1), Ok(InsertOutcome::AlreadyPresent));
+    /// assert_eq!(octree.try_insert(&point2), Ok(InsertOutcome::OutOfBounds));
+    /// assert!(matches!(octree.try_insert(&point3), Err(OctreeError::NonFinite(_))));
+    /// ```
+    pub fn try_insert(&mut self, point: &'point L) -> Result<InsertOutcome, OctreeError<T>> {
+        let location = point.get_location();
+        if !location.iter().all(|coordinate| coordinate.is_finite()) {
+            return Err(OctreeError::NonFinite(location));
+        }
+        if self.config.grow_on_insert {
+            self.grow_to(&location);
+        }
+        let outcome = self.root.insert(&self.config, point, 0);
+        if outcome == InsertOutcome::Inserted {
+            self.changed(ChangeEvent::Inserted(point));
+        }
+        Ok(outcome)
     }
 
-    /// Delete a point from current [Octree], if the point is not in the tree, then nothing will change.
+    /// Delete a point from current [Octree], if the point is not in the tree, then nothing will change. Afterwards, a
+    /// node whose sub tree holds no more points than its capacity merges its children back into itself, so churn does
+    /// not leave a deep skeleton of empty nodes.
     /// # Example
     /// ```
     /// use octree::point::Point3D;
@@ -97,7 +694,112 @@ where
     /// assert!(!octree.delete(&point3));
     /// ```
     pub fn delete(&mut self, point: &'point L) -> bool {
-        self.root.delete(point)
+        let deleted = self.root.delete(&self.config, point);
+        if deleted {
+            self.changed(ChangeEvent::Removed(point));
+        }
+        deleted
+    }
+
+    /// Move a point: remove `old` and insert `new` in its place, e.g. the same object at its location in the next
+    /// frame. Returns whether the update succeeded. Nothing changes if `old` is not recorded, if `new` is not covered
+    /// by the [Octree], or if a point equal to `new` is already recorded, so the tree is never left with neither of
+    /// them. Only when the update succeeds is the callback registered by [Octree::on_change] told about the removal and
+    /// the insertion.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    /// let points = vec![point1.clone(), point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(!octree.update(&point1, &point4));
+    /// assert!(octree.contains(&point1));
+    ///
+    /// assert!(octree.update(&point1, &point3));
+    /// assert!(!octree.contains(&point1));
+    /// assert!(octree.contains(&point3));
+    /// ```
+    pub fn update(&mut self, old: &'point L, new: &'point L) -> bool {
+        let location = new.get_location();
+        if self.config.grow_on_insert
+            && location.iter().all(|coordinate| coordinate.is_finite())
+            && self.root.contains(&self.config, old)
+        {
+            self.grow_to(&location);
+        }
+        if !self.root.covers(&self.config, new) || !self.root.delete(&self.config, old) {
+            return false;
+        }
+        if self.root.insert(&self.config, new, 0) == InsertOutcome::Inserted {
+            self.changed(ChangeEvent::Removed(old));
+            self.changed(ChangeEvent::Inserted(new));
+            true
+        } else {
+            // Put `old` back where it was, as if nothing happened.
+            self.root.insert(&self.config, old, 0);
+            false
+        }
+    }
+
+    /// Keep only the points for which `f` returns `true`, removing the others, e.g. expired particles, in one walk over
+    /// the tree. `f` is called once for every recorded point. Like [Octree::delete], a node whose sub tree then holds
+    /// no more points than its capacity merges its children back into itself. The callback registered by
+    /// [Octree::on_change] is told about every removed point.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.retain(|point| point.x < 5.0);
+    /// assert_eq!(octree.len(), 5);
+    /// assert_eq!(octree.leaf_count(), 1);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&L) -> bool,
+    {
+        let mut removed = Vec::new();
+        self.root.retain(&self.config, &mut f, &mut removed);
+        for point in removed {
+            self.changed(ChangeEvent::Removed(point));
+        }
+    }
+
+    /// Remove all points covered by a specified [BoundingBox], the ones a [query](Octree::query) would return, in one
+    /// walk over the tree, returning how many were removed. Sub trees which do not overlap the [BoundingBox] are
+    /// skipped, and like [Octree::delete], a node whose sub tree then holds no more points than its capacity merges its
+    /// children back into itself. The callback registered by [Octree::on_change] is told about every removed point.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// // The max surface is not covered, so the point at 5 is kept.
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(2.0, 2.0, 2.0), Point3D::new(5.0, 5.0, 5.0)].iter().collect());
+    /// assert_eq!(octree.remove_within(&bounding_box), 3);
+    /// assert_eq!(octree.len(), 7);
+    /// ```
+    pub fn remove_within(&mut self, bounding_box: &BoundingBox<T>) -> usize {
+        let mut removed = Vec::new();
+        let bounding_box = self.root.project_bounding_box(&self.config, bounding_box);
+        self.root
+            .remove_within(&self.config, &bounding_box, &mut removed);
+        for point in &removed {
+            self.changed(ChangeEvent::Removed(*point));
+        }
+        removed.len()
     }
 
     /// Find all points covered by a specified [BoundingBox].
@@ -112,9 +814,9 @@ where
     /// let point1 = Point3D::new(0.0, 0.0, 0.0);
     /// let point2 = Point3D::new(10.0, 10.0, 10.0);
     /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let point4 = Point3D::new(5.0, 10.0, 5.0);
     /// let points = vec![point1.clone(), point2];
     /// let mut octree = Octree::new(points.iter().collect());
-    /// let point4 = Point3D::new(5.0, 10.0, 5.0);
     /// octree.insert(&point3);
     /// octree.insert(&point4);
     ///
@@ -127,55 +829,277 @@ where
     ///     HashSet::from([&point1, &point3])
     /// );
     /// ```
-    pub fn query(&self, bounding_box: &BoundingBox) -> HashSet<&L> {
-        self.root.query(bounding_box)
+    pub fn query(&self, bounding_box: &BoundingBox<T>) -> HashSet<&'point L> {
+        self.root.query(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+        )
     }
 
-    /// Check if a point is already recorded.
+    /// Find all points covered by a specified [BoundingBox], like [query](Octree::query), but collect them into a [Vec]
+    /// as the tree is walked, without hashing them. Every point is stored in exactly one node, so none is repeated.
+    /// The order follows the tree, use [Octree::query_ordered] to sort the points by location.
     /// # Example
     /// ```
     /// use octree::point::Point3D;
-    /// use octree::Octree;
+    /// use octree::{BoundingBox, Octree};
     ///
     /// let point1 = Point3D::new(0.0, 0.0, 0.0);
     /// let point2 = Point3D::new(10.0, 10.0, 10.0);
-    /// let point3 = Point3D::new(20.0, 20.0, 20.0);
-    ///
-    /// let points = vec![point1.clone(), point2];
-    ///
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
     /// let octree = Octree::new(points.iter().collect());
     ///
-    /// assert!(octree.contains(&point1));
-    /// assert!(!octree.contains(&point3));
+    /// let bounding_box = BoundingBox::new(vec![point1.clone(), Point3D::new(5.0, 5.0, 5.0)].iter().collect());
+    /// let mut found = octree.query_vec(&bounding_box);
+    /// found.sort();
+    /// assert_eq!(found, vec![&point1, &point3]);
     /// ```
-    pub fn contains(&self, point: &L) -> bool {
-        self.root.contains(point)
+    pub fn query_vec(&self, bounding_box: &BoundingBox<T>) -> Vec<&'point L> {
+        let mut ret = Vec::new();
+        self.for_each_in_box(bounding_box, |point| ret.push(point));
+        ret
     }
 
-    /// Check if a point can be covered by the current [Octree].
+    /// Call `f` on every point covered by a specified [BoundingBox], the ones [query](Octree::query) returns, without
+    /// collecting them into a [HashSet].
+    ///
+    /// This is also the way to change the points found. The [Octree] only borrows the points, shared for all of
+    /// `'point`, so it can never hand out `&mut L`: other references to the same points may be alive, in the tree or
+    /// outside of it. State which changes must therefore use interior mutability, e.g. [Cell](core::cell::Cell),
+    /// [RefCell](core::cell::RefCell) or atomics. Such state must not take part in [Locatable], [Eq] or [Hash], or
+    /// the point is filed in the wrong node or under the wrong hash; use [Octree::update] to move a point instead.
     /// # Example
     /// ```
-    /// use octree::point::Point3D;
-    /// use octree::Octree;
+    /// use std::cell::Cell;
+    /// use std::hash::{Hash, Hasher};
     ///
-    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
-    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
-    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
-    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    /// use octree::{BoundingBox, Locatable, Octree};
     ///
-    /// // Note points takes ownership of the above two points.
-    /// let points = vec![point1, point2];
+    /// struct Particle {
+    ///     location: [f32; 3],
+    ///     colour: Cell<u32>,
+    /// }
+    ///
+    /// impl PartialEq for Particle {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.location == other.location
+    ///     }
+    /// }
+    ///
+    /// impl Eq for Particle {}
+    ///
+    /// impl Hash for Particle {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.location.map(f32::to_bits).hash(state);
+    ///     }
+    /// }
+    ///
+    /// impl Locatable for Particle {
+    ///     fn get_location(&self) -> [f32; 3] {
+    ///         self.location
+    ///     }
+    /// }
+    ///
+    /// let particles: Vec<Particle> = (0..10)
+    ///     .map(|i| Particle { location: [i as f32; 3], colour: Cell::new(0) })
+    ///     .collect();
+    /// let octree = Octree::new(particles.iter().collect());
+    ///
+    /// let bounding_box = BoundingBox::new(vec![&particles[2], &particles[5]]);
+    /// octree.for_each_in_box(&bounding_box, |particle| particle.colour.set(1));
+    /// let colours: Vec<u32> = particles.iter().map(|particle| particle.colour.get()).collect();
+    /// assert_eq!(colours, vec![0, 0, 1, 1, 1, 0, 0, 0, 0, 0]);
+    /// ```
+    pub fn for_each_in_box<F>(&self, bounding_box: &BoundingBox<T>, mut f: F)
+    where
+        F: FnMut(&'point L),
+    {
+        self.root.query_each(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+            &mut f,
+        );
+    }
+
+    /// Find all points covered by a specified [BoundingBox], like [query](Octree::query), but search the children of a
+    /// split node on the [rayon] thread pool, merging their results. A sub tree holding no more than 1024 points is
+    /// searched sequentially, as a task would cost more than it saves, so only large trees and regions benefit. The
+    /// points are shared between threads, thus `L` must be [Sync]. Requires the `rayon` feature.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
     ///
+    /// let points: Vec<Point3D> = (0..10000)
+    ///     .map(|i| Point3D::new((i % 100) as f32, (i / 100) as f32, (i % 7) as f32))
+    ///     .collect();
     /// let octree = Octree::new(points.iter().collect());
     ///
-    /// assert!(octree.covers(&point3));
-    /// assert!(!octree.covers(&point4));
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(10.0, 10.0, 0.0), Point3D::new(60.0, 90.0, 5.0)].iter().collect());
+    /// assert_eq!(octree.query_parallel(&bounding_box), octree.query(&bounding_box));
     /// ```
-    pub fn covers(&self, point: &L) -> bool {
-        self.root.covers(point)
+    #[cfg(feature = "rayon")]
+    pub fn query_parallel(&self, bounding_box: &BoundingBox<T>) -> HashSet<&L>
+    where
+        L: Sync,
+    {
+        self.root.query_parallel(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+        )
     }
 
-    /// Check if the space occupied by current [Octree] overlap with a given [BoundingBox].
+    /// Count the points covered by a specified [BoundingBox], the same number as `query(bounding_box).len()`, but with
+    /// neither hashing nor allocation, e.g. to compute density grids.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let bounding_box = BoundingBox::new(vec![point1, Point3D::new(5.0, 5.0, 5.0)].iter().collect());
+    /// assert_eq!(octree.count_in_box(&bounding_box), 2);
+    /// ```
+    pub fn count_in_box(&self, bounding_box: &BoundingBox<T>) -> usize {
+        self.root.count_in_box(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+        )
+    }
+
+    /// Count the points in each cell of a regular grid over the [bounds](Octree::bounds), e.g. as a coarse density map
+    /// for adaptive sampling. The bounds are divided into `divisions[i]` equal slices along axis `i`, and each cell is
+    /// counted with [count_in_box](Octree::count_in_box), so subtrees outside a cell are skipped and subtrees inside it
+    /// are counted without visiting their points.
+    ///
+    /// The counts are row-major over x, y and z: the cell with indices `[i, j, k]` is at
+    /// `(i * divisions[1] + j) * divisions[2] + k`, so z varies fastest. The cells cover their lower boundaries but not
+    /// their upper ones, like [BoundingBox::covers], so no point is counted twice. A zero division gives no cells. As
+    /// in every other query, points are projected onto the min surface along the
+    /// [project_axis](OctreeBuilder::project_axis), so along it they are all counted in the first slice.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(1.0, 9.0, 1.0);
+    /// let point3 = Point3D::new(2.0, 8.0, 6.0);
+    /// let point4 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2, point3, point4];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // [x lower, y lower, z lower], [x lower, y lower, z upper], [x lower, y upper, z lower],
+    /// // [x lower, y upper, z upper], [x upper, y lower, z lower], ..., [x upper, y upper, z upper].
+    /// assert_eq!(octree.density_grid([2, 2, 2]), vec![1, 0, 1, 1, 0, 0, 0, 1]);
+    /// assert_eq!(octree.density_grid([1, 1, 1]), vec![4]);
+    /// ```
+    pub fn density_grid(&self, divisions: [usize; 3]) -> Vec<usize> {
+        let bounds = self.bounds();
+        let boundary = |axis: usize, i: usize| {
+            if i == divisions[axis] {
+                bounds.max[axis]
+            } else {
+                let ratio = T::from_f64(i as f64 / divisions[axis] as f64);
+                bounds.min[axis] + (bounds.max[axis] - bounds.min[axis]) * ratio
+            }
+        };
+
+        let mut ret = Vec::with_capacity(divisions.iter().product());
+        for i in 0..divisions[0] {
+            for j in 0..divisions[1] {
+                for k in 0..divisions[2] {
+                    if self
+                        .config
+                        .project_axis
+                        .is_some_and(|axis| [i, j, k][axis] > 0)
+                    {
+                        ret.push(0);
+                        continue;
+                    }
+                    let cell = BoundingBox {
+                        min: [boundary(0, i), boundary(1, j), boundary(2, k)],
+                        max: [boundary(0, i + 1), boundary(1, j + 1), boundary(2, k + 1)],
+                    };
+                    ret.push(self.count_in_box(&cell));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Check if a point is already recorded.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(20.0, 20.0, 20.0);
+    ///
+    /// let points = vec![point1.clone(), point2];
+    ///
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(octree.contains(&point1));
+    /// assert!(!octree.contains(&point3));
+    /// ```
+    pub fn contains(&self, point: &L) -> bool {
+        self.root.contains(&self.config, point)
+    }
+
+    /// Check if any point is recorded at exactly a location, e.g. when only its coordinates are known. Unlike
+    /// [contains](Octree::contains) this does not need the point itself, and finds any point located there, equal to
+    /// each other or not. Unlike [covers](Octree::covers) it is about the points recorded, not the space covered.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2];
+    ///
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(octree.contains_location([10.0, 10.0, 10.0]));
+    /// assert!(!octree.contains_location([5.0, 5.0, 5.0]));
+    /// ```
+    pub fn contains_location(&self, location: [T; 3]) -> bool {
+        self.root.contains_location(&self.config, location)
+    }
+
+    /// Check if a point can be covered by the current [Octree].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
+    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    ///
+    /// // Note points takes ownership of the above two points.
+    /// let points = vec![point1, point2];
+    ///
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(octree.covers(&point3));
+    /// assert!(!octree.covers(&point4));
+    /// ```
+    pub fn covers(&self, point: &L) -> bool {
+        self.root.covers(&self.config, point)
+    }
+
+    /// Check if the space occupied by current [Octree] overlap with a given [BoundingBox].
     /// # Example
     /// ```
     /// use octree::point::Point3D;
@@ -202,627 +1126,8772 @@ where
     /// assert!(octree.overlaps(&bounding_box2));
     /// assert!(!octree.overlaps(&bounding_box3));
     /// ```
-    pub fn overlaps(&self, bounding_box: &BoundingBox) -> bool {
-        self.root.overlaps(bounding_box)
+    pub fn overlaps(&self, bounding_box: &BoundingBox<T>) -> bool {
+        self.root.overlaps(&self.config, bounding_box)
     }
-}
 
-impl<'point, L> Default for Octree<'point, L>
-where
-    L: Locatable + Eq + Hash,
-{
-    fn default() -> Self {
-        Self {
-            root: TreeNode::default(),
+    /// Reserve space for at least `additional` more points in the root node, so a following batch of inserts
+    /// reallocates less. This is only a hint: the root never holds more than its capacity, so the reservation is capped
+    /// at that, and the stored points are the same whether or not it is called.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
+    ///
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.reserve(1_000);
+    /// assert!(octree.insert(&point3));
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.root.reserve(&self.config, additional);
+    }
+
+    /// Find the [BoundingBox]es of all leaf nodes crossed by a plane. The plane is given as `[a, b, c, d]`, i.e. all
+    /// points satisfying `a * x + b * y + c * z + d = 0`. A leaf is crossed if its corners are not all on the same side
+    /// of the plane, a leaf touching the plane is considered crossed as well.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // The plane x = 5.
+    /// assert_eq!(octree.cells_crossed_by_plane(&[1.0, 0.0, 0.0, -5.0]).len(), 1);
+    /// // The plane x = 20.
+    /// assert!(octree.cells_crossed_by_plane(&[1.0, 0.0, 0.0, -20.0]).is_empty());
+    /// ```
+    pub fn cells_crossed_by_plane(&self, plane: &[T; 4]) -> Vec<BoundingBox<T>> {
+        let mut ret = Vec::new();
+        self.root.cells_crossed_by_plane(plane, &mut ret);
+        ret
+    }
+
+    /// Register a callback which is called after every successful [insert](Octree::insert) and
+    /// [delete](Octree::delete), so a secondary index can be kept in sync. Registering a new callback replaces the
+    /// previous one.
+    ///
+    /// The callback is called once per change, after the change is applied to the tree, and in the same order as the
+    /// changes are made. Calls that change nothing, e.g. inserting an uncovered point, do not call it.
+    ///
+    /// Storing the callback costs one allocation, and every change then costs one extra dynamic function call. The
+    /// callback may borrow local state for as long as the points are borrowed. It must be [Send] and [Sync], so the
+    /// [Octree] still is, e.g. to be queried in parallel or shared through a `ConcurrentOctree`, whichever features
+    /// are enabled. Use a `Mutex` rather than a `RefCell` for state shared with the callback.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{ChangeEvent, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
+    /// let points = vec![point1, point2];
+    /// let mut events = Vec::new();
+    ///
+    /// let mut octree = Octree::new(points.iter().collect());
+    /// octree.on_change(|event| events.push(event.map(Point3D::clone)));
+    /// octree.insert(&point3);
+    /// octree.delete(&point3);
+    /// drop(octree);
+    ///
+    /// assert_eq!(
+    ///     events,
+    ///     vec![ChangeEvent::Inserted(point3.clone()), ChangeEvent::Removed(point3)]
+    /// );
+    /// ```
+    pub fn on_change<F>(&mut self, f: F)
+    where
+        F: FnMut(ChangeEvent<&L>) + Send + Sync + 'point,
+    {
+        self.on_change = Some(Box::new(f));
+    }
+
+    /// Counter of changes made to this [Octree], increased by every successful [insert](Octree::insert) and
+    /// [delete](Octree::delete), and whenever the tree is rebuilt, e.g. by [compact](Octree::compact), or
+    /// [cleared](Octree::clear). Caches of data derived from the tree can compare it to know whether they are stale,
+    /// without comparing the tree itself. It wraps around to 0 on overflow, which takes far longer than any real
+    /// program runs.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
+    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    ///
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    /// let generation = octree.generation();
+    ///
+    /// octree.insert(&point4);
+    /// assert_eq!(octree.generation(), generation);
+    /// octree.insert(&point3);
+    /// assert!(octree.generation() > generation);
+    /// ```
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Record a change made to this [Octree].
+    fn changed(&mut self, event: ChangeEvent<&'point L>) {
+        self.touch();
+        self.report(event);
+    }
+
+    /// Report a change to the tracked changes and the callback registered by [Octree::on_change], without increasing
+    /// the generation, for a batch of changes counted as one.
+    fn report(&mut self, event: ChangeEvent<&'point L>) {
+        if let Some(changes) = self.changes.as_mut() {
+            changes.record(event);
+        }
+        if let Some(on_change) = self.on_change.as_mut() {
+            on_change(event);
+        }
+    }
+
+    /// Start recording the points inserted and deleted, to be taken by [take_changes](Octree::take_changes), e.g. once
+    /// per frame. Everything the callback registered by [Octree::on_change] is told about is recorded, from
+    /// [insert](Octree::insert), [delete](Octree::delete), [update](Octree::update) and the other changes. If tracking
+    /// already began, the changes recorded so far are discarded.
+    ///
+    /// Tracking keeps one reference in a [HashSet] for every point changed since the changes were last taken, so its
+    /// memory grows with the number of changes until they are taken, not with the size of the tree. Changes which
+    /// cancel out, a point inserted and deleted again, take no memory.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let point = Point3D::new(5.0, 5.0, 5.0);
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.begin_tracking();
+    /// octree.insert(&point);
+    /// octree.delete(&points[0]);
+    /// let changes = octree.take_changes();
+    /// assert!(changes.get_inserted().contains(&point));
+    /// assert!(changes.get_deleted().contains(&points[0]));
+    /// ```
+    pub fn begin_tracking(&mut self) {
+        self.changes = Some(Changes::new());
+    }
+
+    /// Take the points inserted and deleted since [begin_tracking](Octree::begin_tracking) or the last call, and keep
+    /// tracking from here. Nothing is reported if tracking has not begun.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let point = Point3D::new(5.0, 5.0, 5.0);
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.insert(&point);
+    /// assert!(octree.take_changes().is_empty());
+    ///
+    /// octree.begin_tracking();
+    /// octree.delete(&point);
+    /// assert_eq!(octree.take_changes().get_deleted().len(), 1);
+    /// assert!(octree.take_changes().is_empty());
+    /// ```
+    pub fn take_changes(&mut self) -> Changes<'point, L> {
+        match self.changes.as_mut() {
+            Some(changes) => core::mem::replace(changes, Changes::new()),
+            None => Changes::new(),
+        }
+    }
+
+    /// Find all points covered by a specified [BoundingBox], like [query](Octree::query), but return them sorted by
+    /// their location: by x, then y, then z, each compared with [total_cmp](Scalar::total_cmp). So the order is stable
+    /// across runs as long as no two points share a location.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 1.0, 4.0);
+    /// let point4 = Point3D::new(4.0, 0.0, 5.0);
+    /// let points = vec![point1.clone(), point2, point3.clone(), point4.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let bounding_box = BoundingBox::new(vec![point1.clone(), Point3D::new(5.0, 5.0, 6.0)].iter().collect());
+    ///
+    /// assert_eq!(octree.query_ordered(&bounding_box), vec![&point1, &point4, &point3]);
+    /// ```
+    pub fn query_ordered(&self, bounding_box: &BoundingBox<T>) -> Vec<&L> {
+        let mut ret = self.query_vec(bounding_box);
+        ret.sort_by(|a, b| compare_locations(&a.get_location(), &b.get_location()));
+        ret
+    }
+
+    /// The [BoundingBox] of the root node, i.e. the space this [Octree] covers, e.g. to query everything or to split it
+    /// into sub regions. It is set when the [Octree] is constructed, and changed only by [Octree::insert_growing] and
+    /// [Octree::merge], which grow it, and by [Octree::compact] and [compact_with_ratio](Octree::compact_with_ratio),
+    /// which fit it to the recorded points. [Octree::rebuild] fits it too, unless it was given explicitly, e.g. by
+    /// [Octree::with_bounds] or [OctreeBuilder::build_with_bounds], and has not been grown or compacted since. A loose
+    /// root, see [OctreeBuilder::loose_factor], also takes the points in a margin around it, which a query over these
+    /// bounds misses.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(20.0, 0.0, 0.0);
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.bounds().get_min(), &[0.0, 0.0, 0.0]);
+    /// assert_eq!(octree.query(octree.bounds()).len(), 2);
+    ///
+    /// octree.insert_growing(&point3);
+    /// assert!(octree.bounds().get_max()[0] > 20.0);
+    /// ```
+    pub fn bounds(&self) -> &BoundingBox<T> {
+        &self.root.bounding_box
+    }
+
+    /// The settings this [Octree] was built with, as an [OctreeConfig], e.g. to inspect its capacity or depth limit,
+    /// or to build another [Octree] which behaves the same with [Octree::with_config]. An [Octree] constructed without
+    /// an [OctreeBuilder] or an [OctreeConfig] has the default settings.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Octree, OctreeBuilder, OctreeConfig};
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+    /// let builder = OctreeBuilder::new().capacity(4).max_depth(10);
+    /// let octree = builder.clone().build(points.iter().collect());
+    ///
+    /// assert_eq!(octree.config(), builder.get_config());
+    /// assert_eq!(octree.config().capacity, 4);
+    /// assert_eq!(Octree::new(points.iter().collect()).config(), OctreeConfig::default());
+    /// ```
+    pub fn config(&self) -> OctreeConfig {
+        self.config
+    }
+
+    /// Get a read only [NodeCursor] on the root node, to walk the tree with custom algorithms.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Locatable, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let root = octree.root_cursor();
+    /// assert!(root.is_leaf());
+    /// assert!(root.child(0).is_none());
+    /// assert_eq!(root.bounds().get_min(), &[0.0, 0.0, 0.0]);
+    /// assert_eq!(root.points().count(), 2);
+    /// assert!(root.points().any(|point| point.get_location() == [10.0, 10.0, 10.0]));
+    /// ```
+    pub fn root_cursor(&self) -> NodeCursor<'_, 'point, L, T> {
+        NodeCursor {
+            node: &self.root,
+            depth: 0,
+        }
+    }
+
+    /// Verify the internal invariants of this [Octree], which is useful to catch bugs after a batch of changes, e.g.
+    /// in debug builds. The following are checked for every node:
+    ///  - every point is covered by the node's [BoundingBox],
+    ///  - a leaf holds no more points than its capacity, unless they all share one location or it is at the
+    ///    [max depth](OctreeBuilder::max_depth),
+    ///  - a splitted node holds no point covered by one of its children, so only leaves and the margins of loose nodes
+    ///    hold points,
+    ///  - a splitted node has children, and a node which is not splitted has none,
+    ///  - the node's count of the points in its sub tree, which [Octree::len] returns for the root, is right,
+    ///  - the children's [BoundingBox]es are the [split](BoundingBox::split) of the node's [BoundingBox], along the
+    ///    axes the node is splitted along.
+    ///
+    /// The error message describes the first violated invariant, and where the node is by the octants leading to it
+    /// from the root.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.root.check_invariants(&self.config, &mut Vec::new())
+    }
+
+    /// Increase the generation without reporting a change event, for changes of the tree structure only.
+    fn touch(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Shrink the root [BoundingBox] to the extent of the recorded points, if it has become much larger than needed,
+    /// e.g. after deleting all points in one half of the space. This is
+    /// [compact_with_ratio](Octree::compact_with_ratio) with a ratio of 0.5.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(1.0, 1.0, 1.0);
+    /// let point3 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2, point3.clone()];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(!octree.compact());
+    /// assert!(octree.covers(&Point3D::new(5.0, 5.0, 5.0)));
+    ///
+    /// octree.delete(&point3);
+    /// assert!(octree.compact());
+    /// assert!(!octree.covers(&Point3D::new(5.0, 5.0, 5.0)));
+    /// ```
+    pub fn compact(&mut self) -> bool {
+        self.compact_with_ratio(0.5)
+    }
+
+    /// Rebuild the [Octree] over the tight extent of the recorded points, but only if the volume of that extent is at
+    /// most `ratio` times the volume of the current root [BoundingBox]. Returns whether the tree was rebuilt. Nothing
+    /// changes for an empty tree.
+    ///
+    /// A smaller `ratio` rebuilds less often, only once the root is much too large, while a `ratio` of 1.0 or more
+    /// always rebuilds. Rebuilding costs as much as constructing a new [Octree] from all recorded points, which pays
+    /// off when many queries follow, as a tight root prunes sub trees earlier.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(8.0, 8.0, 8.0);
+    /// let point3 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1, point2, point3.clone()];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// // The remaining points take about half of the volume of the root.
+    /// octree.delete(&point3);
+    /// assert!(!octree.compact_with_ratio(0.1));
+    /// assert!(octree.compact_with_ratio(0.9));
+    /// ```
+    pub fn compact_with_ratio(&mut self, ratio: f32) -> bool {
+        let mut points = Vec::new();
+        self.root.for_each(&mut |point| points.push(point));
+        if points.is_empty() {
+            return false;
+        }
+
+        let bounding_box = BoundingBox::covering(points.clone());
+        if bounding_box.volume() > self.root.bounding_box.volume() * T::from_f32(ratio) {
+            return false;
+        }
+
+        self.rebuild_within(bounding_box, points);
+        self.explicit_bounds = false;
+        true
+    }
+
+    /// Reconstruct the [Octree] from scratch over the tight extent of the recorded points, with the same settings,
+    /// e.g. after heavy churn or after [Octree::insert_growing] doubled the root far beyond the points. The structure
+    /// of an [Octree] only depends on its points and its root [BoundingBox], see [Octree], so it is the root fitted to
+    /// the points which makes the tree shallower; the depth never grows. This is
+    /// [compact_with_ratio](Octree::compact_with_ratio) without a ratio: it always rebuilds, except that an empty tree
+    /// is kept as it is. The callback registered by [Octree::on_change] is not told, as no point changes.
+    ///
+    /// A root [BoundingBox] given explicitly, e.g. by [Octree::with_bounds], is kept rather than fitted, so points
+    /// outside of the recorded ones can still be inserted afterwards.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..20).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let far = Point3D::new(1000.0, 1000.0, 1000.0);
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.insert_growing(&far);
+    /// octree.delete(&far);
+    /// let depth = octree.depth();
+    ///
+    /// octree.rebuild();
+    /// assert!(octree.depth() < depth);
+    /// assert_eq!(octree, Octree::new(points.iter().collect()));
+    /// ```
+    pub fn rebuild(&mut self) {
+        let mut points = Vec::new();
+        self.root.for_each(&mut |point| points.push(point));
+        if points.is_empty() {
+            return;
+        }
+
+        let bounding_box = if self.explicit_bounds {
+            self.root.bounding_box.clone()
+        } else {
+            BoundingBox::covering(points.clone())
+        };
+        self.rebuild_within(bounding_box, points);
+    }
+
+    /// Move the points whose location changed without the [Octree] being told, e.g. through interior mutability, to the
+    /// nodes covering their current location. Only the points which left the node holding them are removed and inserted
+    /// again, so this is cheaper than [rebuild](Octree::rebuild) when most points stay put. Points which left the
+    /// [bounds](Octree::bounds) are removed and returned, e.g. to be put back with [Octree::insert_growing], and the
+    /// callback registered by [Octree::on_change] is told about them.
+    ///
+    /// The [Hash] and [Eq] of the points must not depend on their location, or the [HashSet]s holding them would lose
+    /// track of them as they move.
+    /// # Example
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// use octree::{BoundingBox, Locatable, Octree};
+    ///
+    /// #[derive(Debug)]
+    /// struct Particle {
+    ///     id: usize,
+    ///     location: Cell<[f32; 3]>,
+    /// }
+    ///
+    /// impl PartialEq for Particle {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.id == other.id
+    ///     }
+    /// }
+    ///
+    /// impl Eq for Particle {}
+    ///
+    /// impl Hash for Particle {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.id.hash(state);
+    ///     }
+    /// }
+    ///
+    /// impl Locatable for Particle {
+    ///     fn get_location(&self) -> [f32; 3] {
+    ///         self.location.get()
+    ///     }
+    /// }
+    ///
+    /// let particles: Vec<Particle> = (0..10)
+    ///     .map(|id| Particle { id, location: Cell::new([id as f32; 3]) })
+    ///     .collect();
+    /// let mut octree = Octree::new(particles.iter().collect());
+    /// let bounding_box = BoundingBox::new(vec![[0.0; 3], [1.0; 3]].iter().collect());
+    ///
+    /// particles[8].location.set([0.5; 3]);
+    /// particles[9].location.set([20.0; 3]);
+    /// assert_eq!(octree.reindex(), vec![&particles[9]]);
+    /// assert_eq!(octree.query(&bounding_box).len(), 2);
+    /// ```
+    pub fn reindex(&mut self) -> Vec<&'point L> {
+        let mut drifted = Vec::new();
+        self.root.remove_drifted(&self.config, &mut drifted);
+        if drifted.is_empty() {
+            return drifted;
+        }
+
+        let mut lost = Vec::new();
+        for point in drifted {
+            if self.root.insert(&self.config, point, 0) == InsertOutcome::OutOfBounds {
+                lost.push(point);
+            }
+        }
+        self.touch();
+        for point in &lost {
+            self.report(ChangeEvent::Removed(*point));
+        }
+        lost
+    }
+
+    /// Replace the tree by a new one over a [BoundingBox] with the same settings, holding the given points.
+    fn rebuild_within(&mut self, bounding_box: BoundingBox<T>, points: Vec<&'point L>) {
+        let mut root = self.root.empty_like(bounding_box);
+        for point in points {
+            root.insert(&self.config, point, 0);
+        }
+        self.root = root;
+        self.touch();
+    }
+
+    /// Find all pairs of points which came within `distance` of each other at any moment between the previous frame,
+    /// given as the [Octree] `prev`, and this frame. Points are matched between the two frames by equality, and are
+    /// assumed to move along a straight line at constant speed in between, so two fast points which pass through each
+    /// other are found even if they are far apart in both frames. Points which are not recorded by `prev` are treated
+    /// as not moving. Every pair is reported once, in no particular order.
+    ///
+    /// Only pairs whose swept boxes are close enough on every axis are compared, the candidates are found by querying
+    /// an [Octree] of the motions, so not all pairs are checked.
+    /// # Example
+    /// ```
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// use octree::{Locatable, Octree};
+    ///
+    /// // Particles are identified by their id, so the same particle can be found in both frames.
+    /// struct Particle {
+    ///     id: u32,
+    ///     location: [f32; 3],
+    /// }
+    ///
+    /// impl PartialEq for Particle {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.id == other.id
+    ///     }
+    /// }
+    ///
+    /// impl Eq for Particle {}
+    ///
+    /// impl Hash for Particle {
+    ///     fn hash<H: Hasher>(&self, state: &mut H) {
+    ///         self.id.hash(state);
+    ///     }
+    /// }
+    ///
+    /// impl Locatable for Particle {
+    ///     fn get_location(&self) -> [f32; 3] {
+    ///         self.location
+    ///     }
+    /// }
+    ///
+    /// // The first two particles swap places, the last one only marks the extent of the world.
+    /// let prev_particles = vec![
+    ///     Particle { id: 0, location: [0.0, 0.0, 0.0] },
+    ///     Particle { id: 1, location: [10.0, 1.0, 0.0] },
+    ///     Particle { id: 2, location: [100.0, 100.0, 100.0] },
+    /// ];
+    /// let particles = vec![
+    ///     Particle { id: 0, location: [10.0, 0.0, 0.0] },
+    ///     Particle { id: 1, location: [0.0, 1.0, 0.0] },
+    ///     Particle { id: 2, location: [100.0, 100.0, 100.0] },
+    /// ];
+    /// let prev = Octree::new(prev_particles.iter().collect());
+    /// let octree = Octree::new(particles.iter().collect());
+    ///
+    /// // They are 1 apart when passing each other half way.
+    /// let pairs = octree.swept_pairs_within(&prev, 1.0);
+    /// assert_eq!(pairs.len(), 1);
+    /// assert!(octree.swept_pairs_within(&prev, 0.5).is_empty());
+    /// ```
+    pub fn swept_pairs_within(&self, prev: &Octree<L, T>, distance: T) -> Vec<(&L, &L)> {
+        let mut ret = Vec::new();
+        if distance.is_nan() || distance < T::ZERO {
+            return ret;
+        }
+
+        let mut prev_points = HashSet::new();
+        prev.root.for_each(&mut |point| {
+            prev_points.insert(point);
+        });
+
+        let mut sweeps = Vec::new();
+        self.root.for_each(&mut |point| {
+            let end = point.get_location();
+            let start = prev_points
+                .get(point)
+                .map_or(end, |prev_point| prev_point.get_location());
+            sweeps.push(Sweep {
+                index: sweeps.len(),
+                point,
+                start,
+                end,
+            });
+        });
+
+        // The largest half extent of all motions on every axis, two motions can only come close if their centres are
+        // within distance plus both half extents.
+        let mut max_half_extent = [T::ZERO; 3];
+        for sweep in &sweeps {
+            let half_extent = sweep.half_extent();
+            for i in 0..3 {
+                max_half_extent[i] = max_half_extent[i].max(half_extent[i]);
+            }
+        }
+
+        // The sweeps are indexed with the default settings, whatever the settings of this Octree.
+        let config = OctreeConfig::default();
+        let mut root = TreeNode {
+            bounding_box: BoundingBox::covering(sweeps.iter().collect()),
+            ..Default::default()
+        };
+        for sweep in &sweeps {
+            root.insert(&config, sweep, 0);
+        }
+
+        for sweep in &sweeps {
+            let centre = sweep.get_location();
+            let half_extent = sweep.half_extent();
+            let mut bounding_box = BoundingBox::default();
+            for i in 0..3 {
+                let reach = distance + half_extent[i] + max_half_extent[i];
+                bounding_box.min[i] = centre[i] - reach;
+                bounding_box.max[i] = (centre[i] + reach).next_up();
+            }
+
+            root.query_each(&config, &bounding_box, &mut |other| {
+                if other.index > sweep.index
+                    && closest_approach_sq(sweep, other) <= distance * distance
+                {
+                    ret.push((sweep.point, other.point));
+                }
+            });
+        }
+        ret
+    }
+
+    /// Find all points covered by a specified [BoundingBox], like [query](Octree::query), together with the
+    /// [BoundingBox] of the node each point is stored in. Useful to understand why a point is or is not returned by a
+    /// query, and how the space has been subdivided.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2.clone(), point3];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let bounding_box = BoundingBox::new(vec![point1.clone(), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    ///
+    /// assert_eq!(
+    ///     octree.query_with_cells(&bounding_box),
+    ///     vec![(&point1, octree.root_cursor().bounds().clone())]
+    /// );
+    /// ```
+    pub fn query_with_cells(&self, bounding_box: &BoundingBox<T>) -> Vec<(&L, BoundingBox<T>)> {
+        let mut ret = Vec::new();
+        self.root.query_with_cells(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+            &mut ret,
+        );
+        ret
+    }
+
+    /// Insert a new point, growing the [Octree] if it does not cover the point yet, as chosen by the [GrowthPolicy] of
+    /// [OctreeBuilder::growth_policy]. While the root is not splitted it is always grown just enough, as there is no
+    /// structure worth keeping. Points with infinite or NaN coordinates can not be covered and are not inserted.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(20.0, 20.0, 20.0);
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(octree.insert_growing(&point3));
+    /// assert!(octree.contains(&point3));
+    /// ```
+    pub fn insert_growing(&mut self, point: &'point L) -> bool {
+        let location = point.get_location();
+        if !location.iter().all(|coordinate| coordinate.is_finite()) {
+            return false;
+        }
+
+        self.grow_to(&location);
+        self.insert(point)
+    }
+
+    /// Grow the root until it covers a finite location, as chosen by the [GrowthPolicy].
+    fn grow_to(&mut self, location: &[T; 3]) {
+        while !self.root.covers_location(&self.config, *location) {
+            if self.config.growth_policy == GrowthPolicy::Double && self.root.splitted {
+                self.double_towards(location);
+            } else {
+                self.fit_to(location);
+            }
+            self.explicit_bounds = false;
+            self.touch();
+        }
+    }
+
+    /// Replace the root by one twice as large towards a location, with the old root as one of its children.
+    fn double_towards(&mut self, location: &[T; 3]) {
+        let old_root = core::mem::take(&mut self.root);
+        let mut bounding_box = old_root.bounding_box.clone();
+        let mut octant = 0;
+        for (i, coordinate) in location.iter().enumerate() {
+            if self.config.project_axis == Some(i) {
+                continue;
+            }
+            let size = bounding_box.max[i] - bounding_box.min[i];
+            if *coordinate < bounding_box.min[i] {
+                // The old root becomes the upper half on this axis.
+                bounding_box.min[i] -= size;
+                octant |= 1 << i;
+            } else {
+                bounding_box.max[i] += size;
+            }
+        }
+
+        let mut root = old_root.empty_like(bounding_box);
+        root.split(&self.config, 0);
+        let index = root.index_of_octant(octant);
+        let child = &mut root.children.as_mut().unwrap()[index];
+        if child.bounding_box == old_root.bounding_box && child.closed_axes == old_root.closed_axes
+        {
+            *child = old_root;
+        } else {
+            // Rounding moved the centre of the new root off the old boundary, or the new root is not splitted along
+            // every axis the old one grew along, so the old root does not fit exactly. An inclusive old root which is
+            // now in the lower half no longer covers its max surface either.
+            old_root.for_each(&mut |point| {
+                root.insert(&self.config, point, 0);
+            });
+        }
+        root.recount();
+        self.root = root;
+    }
+
+    /// Rebuild the tree with the root grown just enough to cover a location.
+    fn fit_to(&mut self, location: &[T; 3]) {
+        let mut bounding_box = self.root.bounding_box.clone();
+        for (i, coordinate) in location.iter().enumerate() {
+            if self.config.project_axis == Some(i) {
+                continue;
+            }
+            bounding_box.min[i] = bounding_box.min[i].min(*coordinate);
+            // The max surface is not covered.
+            bounding_box.max[i] = bounding_box.max[i].max(coordinate.next_up());
+        }
+
+        let mut points = Vec::new();
+        self.root.for_each(&mut |point| points.push(point));
+        let mut root = self.root.empty_like(bounding_box);
+        for point in points {
+            root.insert(&self.config, point, 0);
+        }
+        self.root = root;
+    }
+
+    /// Find the `k` points closest to a location, nearest first. Fewer points are returned if the [Octree] holds less
+    /// than `k`. Points at the same distance are returned in an unspecified order.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.k_nearest(&[3.0, 3.0, 3.0], 2), vec![&point3, &point1]);
+    /// ```
+    pub fn k_nearest(&self, location: &[T; 3], k: usize) -> Vec<&L> {
+        self.k_nearest_with(location, k, &mut BinaryHeap::new())
+    }
+
+    /// Find the `k` points closest to a location like [Octree::k_nearest], searching with a queue of nodes which is
+    /// reused between queries.
+    fn k_nearest_with<'node>(
+        &'node self,
+        location: &[T; 3],
+        k: usize,
+        nodes: &mut NodeQueue<'node, 'point, L, T>,
+    ) -> Vec<&'point L> {
+        self.nearest_candidates_by(
+            location,
+            k,
+            |_| true,
+            distance_sq,
+            BoundingBox::min_distance_sq,
+            nodes,
+        )
+        .into_sorted_vec()
+        .into_iter()
+        .map(|nearest| nearest.item)
+        .collect()
+    }
+
+    /// Find the distance from a location to the nearest point, or `None` if the [Octree] is empty.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1, point2, point3];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.nearest_distance(&[-3.0, 0.0, -4.0]), Some(5.0));
+    /// ```
+    pub fn nearest_distance(&self, location: &[T; 3]) -> Option<T> {
+        self.nearest_candidates(location, 1, |_| true)
+            .peek()
+            .map(|nearest| nearest.distance_sq.sqrt())
+    }
+
+    /// Iterate over all points by increasing distance to a location, each with its distance. Unlike
+    /// [Octree::k_nearest], the number of points need not be known in advance: the search is lazy, opening a node
+    /// only when the next point could lie in it, so stopping early, or dropping the iterator, skips the remaining
+    /// work. Points at the same distance are returned in an unspecified order.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, 0.0, 0.0)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // Pull points until one is farther than 2 from the location.
+    /// let near: Vec<(f32, f32)> = octree
+    ///     .nearest_iter([3.2, 0.0, 0.0])
+    ///     .take_while(|(_, distance)| *distance <= 2.0)
+    ///     .map(|(point, distance)| (point.x, distance))
+    ///     .collect();
+    /// assert_eq!(near.iter().map(|(x, _)| *x).collect::<Vec<_>>(), vec![3.0, 4.0, 2.0, 5.0]);
+    /// ```
+    pub fn nearest_iter(&self, location: [T; 3]) -> impl Iterator<Item = (&'point L, T)> + '_ {
+        let location = self.root.project(&self.config, location);
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse(Nearest {
+            distance_sq: self
+                .root
+                .loose_bounding_box(&self.config)
+                .min_distance_sq(&location),
+            item: Candidate::Node(&self.root),
+        }));
+        NearestIter {
+            root: &self.root,
+            config: &self.config,
+            location,
+            queue,
+        }
+    }
+
+    /// Best first search for the `k` accepted points closest to a location, returning them farthest first. Nodes are
+    /// visited by the distance to their loose [BoundingBox], until they can not be closer than the points found.
+    fn nearest_candidates<F>(
+        &self,
+        location: &[T; 3],
+        k: usize,
+        accept: F,
+    ) -> BinaryHeap<Nearest<T, &'point L>>
+    where
+        F: Fn(&L) -> bool,
+    {
+        self.nearest_candidates_by(
+            location,
+            k,
+            accept,
+            distance_sq,
+            BoundingBox::min_distance_sq,
+            &mut BinaryHeap::new(),
+        )
+    }
+
+    /// Best first search like [nearest_candidates](Octree::nearest_candidates), but by any metric. `lower_bound` of a
+    /// [BoundingBox] must not exceed the metric from the location to any point within it, or closer points are missed.
+    /// `nodes` is the queue of nodes to visit, cleared first, so its allocation can be reused between searches.
+    fn nearest_candidates_by<'node, F, M, B>(
+        &'node self,
+        location: &[T; 3],
+        k: usize,
+        accept: F,
+        metric: M,
+        lower_bound: B,
+        nodes: &mut NodeQueue<'node, 'point, L, T>,
+    ) -> BinaryHeap<Nearest<T, &'point L>>
+    where
+        F: Fn(&L) -> bool,
+        M: Fn(&[T; 3], &[T; 3]) -> T,
+        B: Fn(&BoundingBox<T>, &[T; 3]) -> T,
+    {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k == 0 {
+            return best;
+        }
+
+        let location = &self.root.project(&self.config, *location);
+        // Nodes to visit, closest first.
+        nodes.clear();
+        nodes.push(Reverse(Nearest {
+            distance_sq: lower_bound(&self.root.loose_bounding_box(&self.config), location),
+            item: &self.root,
+        }));
+
+        while let Some(Reverse(node)) = nodes.pop() {
+            if best.len() == k
+                && best.peek().is_some_and(|farthest: &Nearest<T, &L>| {
+                    node.distance_sq >= farthest.distance_sq
+                })
+            {
+                break;
+            }
+            for point in &node.item.points {
+                if !accept(point) {
+                    continue;
+                }
+                best.push(Nearest {
+                    distance_sq: metric(
+                        location,
+                        &self.root.project(&self.config, point.get_location()),
+                    ),
+                    item: *point,
+                });
+                if best.len() > k {
+                    best.pop();
+                }
+            }
+            if let Some(children) = &node.item.children {
+                for child in children.iter() {
+                    nodes.push(Reverse(Nearest {
+                        distance_sq: lower_bound(&child.loose_bounding_box(&self.config), location),
+                        item: child,
+                    }));
+                }
+            }
+        }
+        best
+    }
+
+    /// Run [Octree::k_nearest] for every location, returning the results in input order. The queue of nodes to visit
+    /// is allocated once and reused for all of them, so this is cheaper than calling [Octree::k_nearest] in a loop.
+    /// See [Octree::k_nearest_batch_parallel] to spread the locations over threads.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(
+    ///     octree.k_nearest_batch(&[[1.0, 1.0, 1.0], [5.0, 5.0, 5.0]], 1),
+    ///     vec![vec![&point1], vec![&point3]]
+    /// );
+    /// ```
+    pub fn k_nearest_batch(&self, locations: &[[T; 3]], k: usize) -> Vec<Vec<&L>> {
+        let mut nodes = BinaryHeap::new();
+        locations
+            .iter()
+            .map(|location| self.k_nearest_with(location, k, &mut nodes))
+            .collect()
+    }
+
+    /// Run [Octree::k_nearest] for every location on the [rayon] thread pool, returning the results in input order.
+    /// The [Octree] is only read, so queries need no locking, but the points are shared between threads, thus `L`
+    /// must be [Sync]. Like in [Octree::k_nearest_batch], each thread reuses its queue of nodes to visit. Requires the
+    /// `rayon` feature.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(
+    ///     octree.k_nearest_batch_parallel(&[[1.0, 1.0, 1.0], [5.0, 5.0, 5.0]], 1),
+    ///     vec![vec![&point1], vec![&point3]]
+    /// );
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn k_nearest_batch_parallel(&self, locations: &[[T; 3]], k: usize) -> Vec<Vec<&L>>
+    where
+        L: Sync,
+    {
+        locations
+            .par_iter()
+            .map_init(BinaryHeap::new, |nodes, location| {
+                self.k_nearest_with(location, k, nodes)
+            })
+            .collect()
+    }
+
+    /// Iterate over a [NodeCursor] for every node, in depth first pre-order: a node comes before its children, which
+    /// are visited in the order of [BoundingBox::split], each with all of its descendants before the next one. Use
+    /// [NodeCursor::depth] to tell the levels apart, or [Octree::root_cursor] to descend selectively instead.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let depths: Vec<usize> = octree.nodes().map(|node| node.depth()).collect();
+    /// assert_eq!(depths, vec![0, 1, 1, 1, 1, 1, 1, 1, 1]);
+    /// ```
+    pub fn nodes(&self) -> impl Iterator<Item = NodeCursor<'_, 'point, L, T>> {
+        let mut stack = Vec::from([self.root_cursor()]);
+        core::iter::from_fn(move || {
+            let cursor = stack.pop()?;
+            // Reversed, so the first octant is popped first.
+            stack.extend((0..8).rev().filter_map(|octant| cursor.child(octant)));
+            Some(cursor)
+        })
+    }
+
+    /// Walk the [Octree] with a custom pruning predicate, which is the skeleton of region queries such as
+    /// [Octree::query]. `descend` is called with the [BoundingBox] of each node, grown by the loose factor of
+    /// [OctreeBuilder::loose_factor] if there is one, and the node with its whole sub tree is skipped if it returns
+    /// `false`. Otherwise every point stored in the node is passed to `visit` before the children are walked.
+    ///
+    /// Points of visited nodes are not filtered, so `visit` has to test them against the region itself.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Locatable, Octree};
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // Points below the plane x + y + z = 6.
+    /// let mut below = Vec::new();
+    /// octree.prune_walk(
+    ///     |bounding_box| bounding_box.get_min().iter().sum::<f32>() < 6.0,
+    ///     |point| {
+    ///         if point.get_location().iter().sum::<f32>() < 6.0 {
+    ///             below.push(point);
+    ///         }
+    ///     },
+    /// );
+    /// below.sort_by(|a, b| a.x.total_cmp(&b.x));
+    /// assert_eq!(below, vec![&points[0], &points[1]]);
+    /// ```
+    pub fn prune_walk<F, G>(&self, descend: F, mut visit: G)
+    where
+        F: Fn(&BoundingBox<T>) -> bool,
+        G: FnMut(&'point L),
+    {
+        self.root
+            .prune_walk(&self.config, &descend, &mut |point, _| visit(point));
+    }
+
+    /// Compute the tight [BoundingBox] of the points matched by a [query](Octree::query), in the same traversal, or
+    /// `None` if nothing matches. Like [BoundingBox::new], the max surface of the result touches the points on it.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 2.0, 3.0);
+    /// let point4 = Point3D::new(1.0, 3.0, 2.0);
+    /// let points = vec![point1.clone(), point2, point3.clone(), point4.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(1.0, 1.0, 1.0), Point3D::new(5.0, 5.0, 5.0)].iter().collect());
+    /// assert_eq!(
+    ///     octree.bounds_of_query(&bounding_box),
+    ///     Some(BoundingBox::new(vec![point3, point4].iter().collect()))
+    /// );
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(6.0, 6.0, 6.0), Point3D::new(7.0, 7.0, 7.0)].iter().collect());
+    /// assert_eq!(octree.bounds_of_query(&bounding_box), None);
+    /// ```
+    pub fn bounds_of_query(&self, bounding_box: &BoundingBox<T>) -> Option<BoundingBox<T>> {
+        let mut ret: Option<BoundingBox<T>> = None;
+        self.root.query_each(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, bounding_box),
+            &mut |point| {
+                let location = point.get_location();
+                match &mut ret {
+                    Some(bounds) => {
+                        for (i, coordinate) in location.iter().enumerate() {
+                            bounds.min[i] = bounds.min[i].min(*coordinate);
+                            bounds.max[i] = bounds.max[i].max(*coordinate);
+                        }
+                    }
+                    None => {
+                        ret = Some(BoundingBox {
+                            min: location,
+                            max: location,
+                        })
+                    }
+                }
+            },
+        );
+        ret
+    }
+
+    /// Count the leaves, the nodes which have not been splitted. An [Octree] which has never been splitted has one.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points[..2].iter().collect());
+    /// assert_eq!(octree.leaf_count(), 1);
+    ///
+    /// let octree = Octree::new(points.iter().collect());
+    /// assert_eq!(octree.leaf_count(), 8);
+    /// ```
+    pub fn leaf_count(&self) -> usize {
+        self.nodes().filter(|node| node.is_leaf()).count()
+    }
+
+    /// Find the most points held by any single leaf, 0 if all leaves are empty.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points[..3].iter().collect());
+    /// assert_eq!(octree.max_leaf_occupancy(), 3);
+    ///
+    /// // The root pushes its 10 points down, 5 into each of two leaves.
+    /// let octree = Octree::new(points.iter().collect());
+    /// assert_eq!(octree.max_leaf_occupancy(), 5);
+    /// ```
+    pub fn max_leaf_occupancy(&self) -> usize {
+        self.nodes()
+            .filter(|node| node.is_leaf())
+            .map(|node| node.points().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Find the largest depth of any node, i.e. the length of the longest path from the root to a leaf. An [Octree]
+    /// whose root has never been splitted has depth 0.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// assert_eq!(Octree::new(points[..2].iter().collect()).depth(), 0);
+    /// assert_eq!(Octree::new(points.iter().collect()).depth(), 1);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.nodes().map(|node| node.depth()).max().unwrap_or(0)
+    }
+
+    /// Count the nodes, including the root, as visited by [Octree::nodes].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    /// assert_eq!(octree.node_count(), 9);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.nodes().count()
+    }
+
+    /// Collect [OctreeStats] about the structure of this [Octree] in a single walk over the tree.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Octree, OctreeStats};
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(
+    ///     octree.stats(),
+    ///     OctreeStats {
+    ///         depth: 1,
+    ///         node_count: 9,
+    ///         point_count: 10,
+    ///         max_points_per_node: 5,
+    ///     }
+    /// );
+    /// ```
+    pub fn stats(&self) -> OctreeStats {
+        let mut ret = OctreeStats::default();
+        for node in self.nodes() {
+            let points = node.points().count();
+            ret.depth = ret.depth.max(node.depth());
+            ret.node_count += 1;
+            ret.point_count += points;
+            ret.max_points_per_node = ret.max_points_per_node.max(points);
+        }
+        ret
+    }
+
+    /// Export the [BoundingBox] of every node, or of the leaves only, as a wireframe in the
+    /// [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file) format, to see in any 3D viewer how the space
+    /// is subdivided. Each box adds 8 `v` records for its corners, the one with a max coordinate on an axis `i` coming
+    /// `2^i` after the one with the min, and 12 `l` records for its edges. Nodes come in the order of [Octree::nodes].
+    /// An empty [Octree] exports nothing, not even its root.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 2.0, 3.0)];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let obj = octree.to_obj_wireframe(false);
+    /// assert!(obj.starts_with("v 0.0 0.0 0.0\nv 1.0000001 0.0 0.0\n"));
+    /// assert!(obj.ends_with("l 7 8\n"));
+    /// assert_eq!(obj.lines().count(), 20);
+    /// ```
+    pub fn to_obj_wireframe(&self, leaves_only: bool) -> String {
+        let mut ret = String::new();
+        if self.is_empty() {
+            return ret;
+        }
+        let mut count = 0;
+        for node in self.nodes().filter(|node| !leaves_only || node.is_leaf()) {
+            let bounds = node.bounds();
+            for corner in 0..8 {
+                let mut vertex = bounds.min;
+                for (i, coordinate) in vertex.iter_mut().enumerate() {
+                    if corner & (1 << i) != 0 {
+                        *coordinate = bounds.max[i];
+                    }
+                }
+                // Writing into a String never fails.
+                writeln!(ret, "v {:?} {:?} {:?}", vertex[0], vertex[1], vertex[2]).unwrap();
+            }
+            // Every edge joins two corners which only differ on one axis, OBJ counts vertices from 1.
+            for corner in 0..8 {
+                for i in 0..3 {
+                    if corner & (1 << i) == 0 {
+                        writeln!(
+                            ret,
+                            "l {} {}",
+                            count + corner + 1,
+                            count + (corner | 1 << i) + 1
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+            count += 8;
+        }
+        ret
+    }
+
+    /// Insert all points of another [Octree], e.g. a spatially adjacent chunk loaded separately. The root grows to
+    /// cover them as by [Octree::insert_growing], so only points with infinite or NaN coordinates are left out. Points
+    /// equal to one already in this [Octree] are not inserted again.
+    ///
+    /// The settings of this [Octree] are kept, e.g. its node capacity and [GrowthPolicy]; those of `other` are dropped
+    /// along with its structure. Growth can not be disabled; to keep the extent of this [Octree] and drop the points of
+    /// `other` outside of it, [insert](Octree::insert) them from [Octree::iter] instead.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(1.0, 1.0, 1.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let point4 = Point3D::new(10.0, 10.0, 10.0);
+    /// let mut octree = Octree::new(vec![&point1, &point2]);
+    /// let other = Octree::new(vec![&point2, &point3, &point4]);
+    ///
+    /// octree.merge(other);
+    /// assert!(octree.contains(&point3));
+    /// assert!(octree.covers(&point3));
+    /// ```
+    pub fn merge(&mut self, other: Octree<'point, L, T>) {
+        other.root.for_each(&mut |point| {
+            // Points already recorded are turned down by the insertion itself.
+            self.insert_growing(point);
+        });
+    }
+
+    /// Construct an [Octree] of grid cells, built by [Quantized::group] from points snapped to a grid, so noisy
+    /// near-duplicates collapse into one cell. The caller owns the cells, as the [Octree] only keeps references; each
+    /// [Quantized] cell maps to the original points snapped to it.
+    ///
+    /// This changes membership semantics: the [Octree] holds cells, not points, so [Octree::contains] and
+    /// [Octree::query] work with the snapped locations, and points of the same cell are a single entry.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree, Quantized};
+    ///
+    /// let point1 = Point3D::new(0.1, 0.0, 0.0);
+    /// let point2 = Point3D::new(-0.2, 0.1, 0.0);
+    /// let point3 = Point3D::new(9.9, 10.2, 10.0);
+    /// let points = vec![point1.clone(), point2.clone(), point3];
+    ///
+    /// let cells = Quantized::group(points.iter().collect(), [1.0, 1.0, 1.0]);
+    /// let octree = Octree::new_quantized(&cells);
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    /// let found: Vec<_> = octree.query(&bounding_box).into_iter().collect();
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].points(), &[&point1, &point2]);
+    /// ```
+    pub fn new_quantized<'cell>(
+        cells: &'cell [Quantized<'point, L, T>],
+    ) -> Octree<'cell, Quantized<'point, L, T>, T>
+    where
+        'point: 'cell,
+    {
+        Octree::new(cells.iter().collect())
+    }
+
+    /// Compute the mean, over all points, of the distance from each point to its nearest other point, or `None` if
+    /// the [Octree] holds fewer than two points. Each point is searched as in [Octree::nearest_distance], so the cost
+    /// grows about as `n log n` rather than `n²`.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(1.0, 0.0, 0.0);
+    /// let point3 = Point3D::new(4.0, 0.0, 0.0);
+    /// let points = vec![point1, point2, point3];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // 1 for the first two points, 3 for the last.
+    /// assert_eq!(octree.mean_nearest_neighbor_distance(), Some(5.0 / 3.0));
+    /// ```
+    pub fn mean_nearest_neighbor_distance(&self) -> Option<T> {
+        let mut count = 0usize;
+        let mut total = 0.0f64;
+        self.root.for_each(&mut |point| {
+            let nearest = self
+                .nearest_candidates(&point.get_location(), 1, |other| {
+                    !core::ptr::eq(other, point)
+                })
+                .pop();
+            if let Some(nearest) = nearest {
+                count += 1;
+                total += nearest.distance_sq.sqrt().to_f64();
+            }
+        });
+        if count < 2 {
+            return None;
+        }
+        Some(T::from_f64(total / count as f64))
+    }
+
+    /// Find the centroid of all points, i.e. their mean location, or `None` if the [Octree] is empty. The sum is taken
+    /// in [f64] so large trees do not lose precision. See [NodeCursor::centroid] for the centroid of a sub tree.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points = vec![
+    ///     Point3D::new(0.0, 0.0, 0.0),
+    ///     Point3D::new(2.0, 0.0, 6.0),
+    ///     Point3D::new(4.0, 3.0, 0.0),
+    ///     Point3D::new(2.0, 1.0, 2.0),
+    /// ];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.centroid(), Some([2.0, 1.0, 2.0]));
+    /// ```
+    pub fn centroid(&self) -> Option<[T; 3]> {
+        self.root.weighted_centroid(&mut |_| T::ONE)
+    }
+
+    /// Find the centroid of all points, weighted by the mass given for each point, or `None` if the [Octree] is empty
+    /// or the total mass is 0.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(4.0, 8.0, 0.0)];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // The point at the origin weighs three times as much as the other.
+    /// let centroid = octree.weighted_centroid(|point| if point.x == 0.0 { 3.0 } else { 1.0 });
+    /// assert_eq!(centroid, Some([1.0, 2.0, 0.0]));
+    /// ```
+    pub fn weighted_centroid<F>(&self, mut mass: F) -> Option<[T; 3]>
+    where
+        F: FnMut(&L) -> T,
+    {
+        self.root.weighted_centroid(&mut mass)
+    }
+
+    /// Query the points within `radius` of `centre`, including the ones on the sphere. Nodes whose [BoundingBox]
+    /// does not reach the sphere are skipped. A negative or NaN radius matches nothing, and a radius of 0 matches only
+    /// the points exactly at the centre.
+    /// # Example
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let point4 = Point3D::new(3.0, 0.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3, point4.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(
+    ///     octree.query_radius([0.0, 0.0, 0.0], 5.0),
+    ///     HashSet::from([&point1, &point4])
+    /// );
+    /// ```
+    pub fn query_radius(&self, centre: [T; 3], radius: T) -> HashSet<&L> {
+        let mut ret = HashSet::new();
+        if radius.is_nan() || radius < T::ZERO {
+            return ret;
+        }
+
+        let centre = self.root.project(&self.config, centre);
+        let radius_sq = radius * radius;
+        self.prune_walk(
+            |bounding_box| bounding_box.intersects_sphere(&centre, radius),
+            |point| {
+                if distance_sq(
+                    &self.root.project(&self.config, point.get_location()),
+                    &centre,
+                ) <= radius_sq
+                {
+                    ret.insert(point);
+                }
+            },
+        );
+        ret
+    }
+
+    /// Query the points in a region of any shape, e.g. a tilted slab or the points closer to one location than to
+    /// another. Nodes are pruned by the `coarse` [BoundingBox] like in [Octree::query], and every point it covers is
+    /// kept if `predicate` holds for its location. `coarse` must cover the whole region, points of the region outside
+    /// of it are missing from the result.
+    /// # Example
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octree};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(1.0, 1.0, 4.0);
+    /// let point4 = Point3D::new(5.0, 1.0, 1.0);
+    /// let points = vec![point1, point2, point3.clone(), point4];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // The slab between the planes x + y + z = 5 and x + y + z = 7.
+    /// let coarse = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(8.0, 8.0, 8.0)].iter().collect());
+    /// let slab = octree.query_where(&coarse, |[x, y, z]| (5.0..=7.0).contains(&(x + y + z)));
+    /// assert_eq!(slab, HashSet::from([&point3, &points[3]]));
+    /// ```
+    pub fn query_where<F>(&self, coarse: &BoundingBox<T>, predicate: F) -> HashSet<&L>
+    where
+        F: Fn(&[T; 3]) -> bool,
+    {
+        let mut ret = HashSet::new();
+        self.root.query_each(
+            &self.config,
+            &self.root.project_bounding_box(&self.config, coarse),
+            &mut |point| {
+                if predicate(&self.root.project(&self.config, point.get_location())) {
+                    ret.insert(point);
+                }
+            },
+        );
+        ret
+    }
+
+    /// Query the points inside a view frustum, e.g. to cull what a camera can not see. Each plane is given as
+    /// `[a, b, c, d]`, and a point is inside if `a * x + b * y + c * z + d >= 0` for all six planes, i.e. it is not
+    /// behind any of them. Nodes whose [BoundingBox] lies fully behind one of the planes are skipped.
+    /// # Example
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(1.0, 1.0, 4.0);
+    /// let point4 = Point3D::new(5.0, 1.0, 4.0);
+    /// let points = vec![point1, point2, point3.clone(), point4];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // A pyramid along z with its apex at the origin, cut at z = 2 and z = 8.
+    /// let planes = [
+    ///     [1.0, 0.0, 1.0, 0.0],
+    ///     [-1.0, 0.0, 1.0, 0.0],
+    ///     [0.0, 1.0, 1.0, 0.0],
+    ///     [0.0, -1.0, 1.0, 0.0],
+    ///     [0.0, 0.0, 1.0, -2.0],
+    ///     [0.0, 0.0, -1.0, 8.0],
+    /// ];
+    /// assert_eq!(octree.query_frustum(planes), HashSet::from([&point3]));
+    /// ```
+    pub fn query_frustum(&self, planes: [[T; 4]; 6]) -> HashSet<&L> {
+        let mut ret = HashSet::new();
+        self.prune_walk(
+            |bounding_box| !planes.iter().any(|plane| bounding_box.behind_plane(plane)),
+            |point| {
+                let [x, y, z] = self.root.project(&self.config, point.get_location());
+                if planes
+                    .iter()
+                    .all(|[a, b, c, d]| *a * x + *b * y + *c * z + *d >= T::ZERO)
+                {
+                    ret.insert(point);
+                }
+            },
+        );
+        ret
+    }
+
+    /// Collect the candidate points for picking along a ray, e.g. the one of a mouse click, from `origin` towards
+    /// `direction` up to `max_distance`. Nodes are tested against the ray with the slab method, and only the ones it
+    /// passes through are searched. All points of these nodes whose projection onto the ray lies within `max_distance`
+    /// of the origin are returned, nearest along the ray first, so the caller filters them by the distance to the ray
+    /// it tolerates. A zero or non-finite `direction`, or a negative or NaN `max_distance`, matches nothing.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Locatable, Octree};
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, 0.0, 0.0)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let hits = octree.query_ray([7.5, 0.0, 0.0], [-2.0, 0.0, 0.0], 3.0);
+    /// let hits: Vec<f32> = hits.into_iter().map(|point| point.x).collect();
+    /// assert_eq!(hits, vec![7.0, 6.0, 5.0]);
+    /// assert!(octree.query_ray([7.5, 0.0, 0.0], [0.0, 0.0, 0.0], 3.0).is_empty());
+    /// ```
+    pub fn query_ray(&self, origin: [T; 3], direction: [T; 3], max_distance: T) -> Vec<&L> {
+        let origin = self.root.project(&self.config, origin);
+        let mut direction = direction;
+        if let Some(axis) = self.config.project_axis {
+            direction[axis] = T::ZERO;
+        }
+        let length = direction.iter().map(|c| *c * *c).sum::<T>().sqrt();
+        if !(length > T::ZERO && length.is_finite())
+            || max_distance.is_nan()
+            || max_distance < T::ZERO
+        {
+            return Vec::new();
+        }
+        let direction = direction.map(|c| c / length);
+
+        let mut hits: Vec<(T, &L)> = Vec::new();
+        self.prune_walk(
+            |bounding_box| bounding_box.hit_by_ray(&origin, &direction, max_distance),
+            |point| {
+                let location = self.root.project(&self.config, point.get_location());
+                let distance: T = (0..3)
+                    .map(|i| (location[i] - origin[i]) * direction[i])
+                    .sum();
+                if T::ZERO <= distance && distance <= max_distance {
+                    hits.push((distance, point));
+                }
+            },
+        );
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits.into_iter().map(|(_, point)| point).collect()
+    }
+
+    /// Check if any point other than `point` lies within `radius` of its location, including on the sphere. Like
+    /// [Octree::query_radius], nodes whose [BoundingBox] does not reach the sphere are skipped, and the search stops at
+    /// the first point found. Points equal to `point` are not counted, whether or not it is stored. A negative or NaN
+    /// radius matches nothing.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(3.0, 0.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert!(octree.has_neighbor_within(&point1, 5.0));
+    /// assert!(!octree.has_neighbor_within(&point1, 4.0));
+    /// ```
+    pub fn has_neighbor_within(&self, point: &L, radius: T) -> bool {
+        if radius.is_nan() || radius < T::ZERO {
+            return false;
+        }
+        let centre = self.root.project(&self.config, point.get_location());
+        self.root
+            .has_neighbor_within(&self.config, point, &centre, radius)
+    }
+
+    /// Find the point closest to a location, or `None` if the [Octree] is empty. Like [Octree::k_nearest], nodes
+    /// which can not hold a closer point than the one found are skipped.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.nearest([1.0, 2.0, 1.0]), Some(&point1));
+    /// assert_eq!(octree.nearest([3.0, 3.0, 5.0]), Some(&point3));
+    /// ```
+    pub fn nearest(&self, location: [T; 3]) -> Option<&L> {
+        self.nearest_candidates(&location, 1, |_| true)
+            .pop()
+            .map(|nearest| nearest.item)
+    }
+
+    /// Find a point close to a location, at most `1 + epsilon` times as far from it as the closest point, or `None` if
+    /// the [Octree] is empty. Like [Octree::nearest], but a node is skipped as soon as it can not hold a point more
+    /// than `1 + epsilon` times closer than the one found, so fewer nodes are searched the larger `epsilon` is. An
+    /// `epsilon` of 0 finds the closest point like [Octree::nearest]; negative or NaN values are treated as 0.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Locatable, Octree};
+    ///
+    /// let points: Vec<Point3D> = (0..100).map(|i| Point3D::new(i as f32, (i * 7 % 10) as f32, 0.0)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    /// let location = [30.2, 4.0, 1.0];
+    ///
+    /// assert_eq!(octree.nearest_approx(location, 0.0), octree.nearest(location));
+    /// let exact = octree.nearest_distance(&location).unwrap();
+    /// let approx = octree.nearest_approx(location, 0.5).unwrap().get_location();
+    /// let distance = (0..3).map(|i| (approx[i] - location[i]).powi(2)).sum::<f32>().sqrt();
+    /// assert!(distance <= 1.5 * exact);
+    /// ```
+    pub fn nearest_approx(&self, location: [T; 3], epsilon: T) -> Option<&L> {
+        let factor = T::ONE + epsilon.max(T::ZERO);
+        let factor_sq = factor * factor;
+        self.nearest_by(location, distance_sq, |bounding_box, location| {
+            bounding_box.min_distance_sq(location) * factor_sq
+        })
+    }
+
+    /// Find the point closest to a location by a custom metric, called with the location and the location of a point,
+    /// or `None` if the [Octree] is empty. Nodes are skipped by `lower_bound`, called with the [BoundingBox] of a node
+    /// and the location, which must never exceed the metric to any point within the [BoundingBox], or a closer point
+    /// may be missed. A lower bound of 0 is always safe, but then every node is searched. See
+    /// [Octree::nearest_weighted] for a weighted Euclidean metric.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1.clone(), point2, point3.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// // The Manhattan distance, bounded by the one to the closest point of a box.
+    /// let manhattan = |a: &[f32; 3], b: &[f32; 3]| (0..3).map(|i| (a[i] - b[i]).abs()).sum::<f32>();
+    /// let nearest = octree.nearest_by(
+    ///     [2.0, 3.0, 3.0],
+    ///     manhattan,
+    ///     |bounding_box, location| manhattan(&bounding_box.closest_point(location), location),
+    /// );
+    /// assert_eq!(nearest, Some(&point3));
+    /// assert_eq!(octree.nearest_by([1.0, 1.0, 1.0], manhattan, |_, _| 0.0), Some(&point1));
+    /// ```
+    pub fn nearest_by<M, B>(&self, location: [T; 3], metric: M, lower_bound: B) -> Option<&L>
+    where
+        M: Fn(&[T; 3], &[T; 3]) -> T,
+        B: Fn(&BoundingBox<T>, &[T; 3]) -> T,
+    {
+        self.nearest_candidates_by(
+            &location,
+            1,
+            |_| true,
+            metric,
+            lower_bound,
+            &mut BinaryHeap::new(),
+        )
+        .pop()
+        .map(|nearest| nearest.item)
+    }
+
+    /// Find the point closest to a location by the Euclidean distance with a weight on every axis, e.g. when a unit
+    /// along z costs more than one along x or y, or `None` if the [Octree] is empty. The weights scale the squared
+    /// differences, so must not be negative. Nodes are skipped as in [Octree::nearest].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(3.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(0.0, 0.0, 2.0);
+    /// let points = vec![point1.clone(), point2.clone()];
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.nearest_weighted([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]), Some(&point2));
+    /// assert_eq!(octree.nearest_weighted([0.0, 0.0, 0.0], [1.0, 1.0, 4.0]), Some(&point1));
+    /// ```
+    pub fn nearest_weighted(&self, location: [T; 3], weights: [T; 3]) -> Option<&L> {
+        let metric = |a: &[T; 3], b: &[T; 3]| {
+            (0..3)
+                .map(|i| weights[i] * (a[i] - b[i]) * (a[i] - b[i]))
+                .sum()
+        };
+        self.nearest_by(location, metric, |bounding_box, location| {
+            metric(&bounding_box.closest_point(location), location)
+        })
+    }
+
+    /// Count the points recorded in this [Octree]. Every node keeps count of the points in its sub tree as they change,
+    /// so this takes constant time.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    /// assert_eq!(octree.len(), 2);
+    ///
+    /// octree.insert(&point3);
+    /// assert_eq!(octree.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    /// Check if this [Octree] records no point.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let points = vec![point1.clone(), point2.clone()];
+    /// let mut octree = Octree::new(points.iter().collect());
+    /// assert!(!octree.is_empty());
+    ///
+    /// octree.delete(&point1);
+    /// assert!(!octree.is_empty());
+    ///
+    /// octree.delete(&point2);
+    /// assert!(octree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+    }
+
+    /// Remove all points, keeping the [BoundingBox] of the root and the allocation of its point set, so the same
+    /// [Octree] can be reused, e.g. frame after frame. The callback registered by [Octree::on_change] is told about
+    /// every removed point.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// octree.clear();
+    /// assert!(octree.is_empty());
+    /// assert!(octree.insert(&point3));
+    /// ```
+    pub fn clear(&mut self) {
+        let mut removed = Vec::new();
+        if self.on_change.is_some() || self.changes.is_some() {
+            self.root.for_each(&mut |point| removed.push(point));
+        }
+        if !self.root.is_empty() {
+            // The whole clear is one change.
+            self.touch();
+        }
+
+        self.root.points.clear();
+        self.root.children = None;
+        self.root.splitted = false;
+        self.root.subtree_count = 0;
+
+        for point in removed {
+            self.report(ChangeEvent::Removed(point));
+        }
+    }
+
+    /// Iterate over every point recorded in this [Octree] exactly once, node by node in the order of
+    /// [Octree::nodes]. Only a stack of nodes still to visit is kept, the points are not collected.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.iter().count(), 10);
+    /// assert!(points.iter().all(|point| octree.iter().any(|stored| stored == point)));
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &'point L> + '_ {
+        self.nodes().flat_map(|node| node.points())
+    }
+
+    /// Insert many points, returning how many are inserted. The same points end up stored, in the same nodes, as by
+    /// calling [Octree::insert] for each of them in order, points not covered are dropped. But the batch is split
+    /// between the children of a node at once, so every node is walked once rather than once per point. The callback
+    /// registered by [Octree::on_change] is told about the inserted points node by node, not in their given order.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(4.0, 4.0, 4.0);
+    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    /// let points = vec![point1, point2];
+    /// let mut octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.extend([&point3, &point4]), 1);
+    /// assert_eq!(octree.len(), 3);
+    /// ```
+    pub fn extend<I>(&mut self, points: I) -> usize
+    where
+        I: IntoIterator<Item = &'point L>,
+    {
+        let points: Vec<_> = points.into_iter().enumerate().collect();
+        if self.config.grow_on_insert {
+            // Covering the corners of the extent of the new points covers all of them at once.
+            let finite = points
+                .iter()
+                .map(|(_, point)| point.get_location())
+                .filter(|location| location.iter().all(|coordinate| coordinate.is_finite()));
+            let mut extent: Option<([T; 3], [T; 3])> = None;
+            for location in finite {
+                let (min, max) = extent.get_or_insert((location, location));
+                for i in 0..3 {
+                    min[i] = min[i].min(location[i]);
+                    max[i] = max[i].max(location[i]);
+                }
+            }
+            if let Some((min, max)) = extent {
+                self.grow_to(&min);
+                self.grow_to(&max);
+            }
+        }
+        let mut inserted = Vec::new();
+        self.root.extend(&self.config, points, &mut inserted, 0);
+        for point in &inserted {
+            self.changed(ChangeEvent::Inserted(*point));
+        }
+        inserted.len()
+    }
+}
+
+/// Compare the points recorded by two [Octree]s, returning the points `(added, removed)` from `old` to `new`. Only
+/// membership is compared, so two trees holding the same points but of different shape have no difference.
+/// # Example
+/// ```
+/// use std::collections::HashSet;
+///
+/// use octree::point::Point3D;
+/// use octree::{diff, Octree};
+///
+/// let point1 = Point3D::new(0.0, 0.0, 0.0);
+/// let point2 = Point3D::new(10.0, 10.0, 10.0);
+/// let point3 = Point3D::new(5.0, 5.0, 5.0);
+/// let points = vec![point1.clone(), point2];
+///
+/// let old = Octree::new(points.iter().collect());
+/// let mut new = Octree::new(points.iter().collect());
+/// new.delete(&point1);
+/// new.insert(&point3);
+///
+/// assert_eq!(
+///     diff(&old, &new),
+///     (HashSet::from([&point3]), HashSet::from([&point1]))
+/// );
+/// ```
+pub fn diff<'a, L, T>(
+    old: &'a Octree<L, T>,
+    new: &'a Octree<L, T>,
+) -> (HashSet<&'a L>, HashSet<&'a L>)
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    let mut old_points = HashSet::new();
+    old.root.for_each(&mut |point| {
+        old_points.insert(point);
+    });
+    let mut new_points = HashSet::new();
+    new.root.for_each(&mut |point| {
+        new_points.insert(point);
+    });
+
+    let added = new_points.difference(&old_points).copied().collect();
+    let removed = old_points.difference(&new_points).copied().collect();
+    (added, removed)
+}
+
+/// Construct an empty [Octree] with default settings, whose root covers nothing: its [BoundingBox] is the inverted
+/// default one, so [Octree::insert] rejects every point. Start with [Octree::insert_growing], which grows the root
+/// around the points inserted, or construct an empty [Octree] over a known extent with [Octree::with_bounds] instead.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::Octree;
+///
+/// let point1 = Point3D::new(1.0, 2.0, 3.0);
+/// let point2 = Point3D::new(10.0, 10.0, 10.0);
+/// let mut octree = Octree::default();
+///
+/// assert!(!octree.insert(&point1));
+/// assert!(octree.insert_growing(&point1));
+/// assert!(octree.insert_growing(&point2));
+/// assert_eq!(octree.len(), 2);
+/// ```
+impl<'point, L, T> Default for Octree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn default() -> Self {
+        Self {
+            root: TreeNode::default(),
+            on_change: None,
+            changes: None,
+            generation: 0,
+            config: OctreeConfig::default(),
+            explicit_bounds: false,
+        }
+    }
+}
+
+/// Copy the structure of an [Octree] as a snapshot, which can then be changed independently. Only the references to
+/// the points are copied, so `L` need not be [Clone]. The [generation](Octree::generation) and the changes tracked
+/// since [Octree::begin_tracking] are kept, while the callback registered by [Octree::on_change] is not, as it can not
+/// be copied.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::Octree;
+///
+/// let point1 = Point3D::new(0.0, 0.0, 0.0);
+/// let point2 = Point3D::new(10.0, 10.0, 10.0);
+/// let point3 = Point3D::new(5.0, 5.0, 5.0);
+/// let points = vec![point1, point2];
+/// let octree = Octree::new(points.iter().collect());
+///
+/// let mut snapshot = octree.clone();
+/// snapshot.insert(&point3);
+/// assert_eq!(snapshot.len(), octree.len() + 1);
+/// ```
+impl<'point, L, T> Clone for Octree<'point, L, T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            on_change: None,
+            changes: self.changes.clone(),
+            generation: self.generation,
+            config: self.config,
+            explicit_bounds: self.explicit_bounds,
+        }
+    }
+}
+
+/// Construct an [Octree] from references to points, the same one as [Octree::new] constructs from them. The extent of
+/// the root is derived from all points before any is inserted, so no point is dropped for being out of bounds.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::Octree;
+///
+/// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+/// let octree: Octree<_> = points.iter().collect();
+///
+/// assert_eq!(octree, Octree::new(points.iter().collect()));
+/// ```
+impl<'point, L, T> FromIterator<&'point L> for Octree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = &'point L>,
+    {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// Consume an [Octree], yielding every point recorded in it exactly once, like [Octree::iter] but without borrowing
+/// the tree. As an [Octree] only borrows its points, these are the stored `&'point L` references, not owned values,
+/// which stay valid after the tree is gone. The callback registered by [Octree::on_change] is not told about them.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::Octree;
+///
+/// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+/// let octree = Octree::new(points.iter().collect());
+///
+/// let drained: Vec<&Point3D> = octree.into_iter().collect();
+/// assert_eq!(drained.len(), 10);
+/// assert!(points.iter().all(|point| drained.contains(&point)));
+/// ```
+impl<'point, L, T> IntoIterator for Octree<'point, L, T> {
+    type Item = &'point L;
+    type IntoIter = IntoIter<'point, L, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            remaining: self.root.subtree_count,
+            nodes: Vec::from([self.root]),
+            points: Vec::new(),
+        }
+    }
+}
+
+impl<'point, L, T> Debug for Octree<'point, L, T>
+where
+    L: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Octree")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+// Implement PartialEq and Eq to make testing easier.
+impl<'point, L, T> PartialEq for Octree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config && self.root == other.root
+    }
+}
+
+impl<'point, L, T> Eq for Octree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+}
+
+// Implement PartialEq and Eq to make testing easier.
+impl<'point, L, T> PartialEq for TreeNode<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.children == other.children
+            && self.bounding_box == other.bounding_box
+            && self.points == other.points
+            && self.splitted == other.splitted
+    }
+}
+
+impl<'point, L, T> Eq for TreeNode<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+}
+
+impl<'point, L, T> TreeNode<'point, L, T> {
+    /// The index into the children of the child in an octant, keeping only the bits of the axes this node is splitted
+    /// along, e.g. octant 5, upper along x and z, is child 3 of a node splitted along x and z, and child 1 of a node
+    /// splitted along x and y. The octants in the upper half along other axes are not used.
+    fn index_of_octant(&self, octant: usize) -> usize {
+        let mut index = 0;
+        let mut bit = 0;
+        for axis in 0..3 {
+            if self.split_axes & (1 << axis) != 0 {
+                index |= (octant >> axis & 1) << bit;
+                bit += 1;
+            }
+        }
+        index
+    }
+
+    /// The octant of the child at an index into the children, the inverse of
+    /// [index_of_octant](TreeNode::index_of_octant).
+    fn octant_of_index(&self, index: usize) -> usize {
+        let mut octant = 0;
+        let mut bit = 0;
+        for axis in 0..3 {
+            if self.split_axes & (1 << axis) != 0 {
+                octant |= (index >> bit & 1) << axis;
+                bit += 1;
+            }
+        }
+        octant
+    }
+
+    /// The axes along which the child at an index covers its max surface: the ones this node covers its max surface
+    /// along, except those along which the child is in the lower half, as its max surface is then the centre plane.
+    fn child_closed_axes(&self, index: usize) -> u8 {
+        self.closed_axes & (!self.split_axes | self.octant_of_index(index) as u8)
+    }
+}
+
+impl<'point, L, T> TreeNode<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn new(config: &OctreeConfig, points: Vec<&'point L>) -> Self {
+        let mut tree_node: TreeNode<L, T> = TreeNode {
+            // So the created bounding box contains every point in points.
+            bounding_box: BoundingBox::covering(points.clone()),
+            ..Default::default()
+        };
+
+        for point in points {
+            tree_node.insert(config, point, 0);
+        }
+
+        tree_node
+    }
+
+    /// Insert a point into this node at a given depth, splitting it if it gets too many points. Nothing changes if the
+    /// point is not covered, or is already recorded.
+    fn insert(&mut self, config: &OctreeConfig, point: &'point L, depth: usize) -> InsertOutcome {
+        // Do nothing is the point won't be covered by current node.
+        if !self.covers(config, point) {
+            return InsertOutcome::OutOfBounds;
+        }
+        let outcome = if !self.splitted {
+            if !self.points.insert(point) {
+                return InsertOutcome::AlreadyPresent;
+            }
+            // Too many points now, split and push all of them down.
+            if self.needs_split(config, depth) {
+                self.split(config, depth);
+            }
+            InsertOutcome::Inserted
+        } else if config.loose_factor == 0.0 {
+            // Exactly one child covers the point, the one in its octant.
+            let index = self.child_index(config, point);
+            self.children.as_mut().unwrap()[index].insert(config, point, depth + 1)
+        } else {
+            // Loose children overlap, ask them in order: does this point belongs to you? The first one covering it
+            // always takes it, so that is where an equal point is recorded, if any.
+            let outcome = self
+                .children
+                .as_mut()
+                .unwrap()
+                .iter_mut()
+                .map(|child| child.insert(config, point, depth + 1))
+                .find(|outcome| *outcome != InsertOutcome::OutOfBounds);
+            match outcome {
+                Some(outcome) => outcome,
+                // Only a loose node covers points no child covers, in its margin beyond the ones of its children.
+                None if self.points.insert(point) => InsertOutcome::Inserted,
+                None => InsertOutcome::AlreadyPresent,
+            }
+        };
+        if outcome == InsertOutcome::Inserted {
+            self.recount();
+        }
+        outcome
+    }
+
+    /// Update the number of points in this sub tree from the points of this node and the numbers of its children.
+    fn recount(&mut self) {
+        self.subtree_count = self.points.len()
+            + self.children.as_ref().map_or(0, |children| {
+                children.iter().map(|child| child.subtree_count).sum()
+            });
+    }
+
+    /// Check if this leaf, at a given depth, holds more points than its capacity, which do not all share one location,
+    /// so splitting it separates some of them, and is not too deep to be splitted.
+    fn needs_split(&self, config: &OctreeConfig, depth: usize) -> bool {
+        self.splits(config, &self.points, depth)
+    }
+
+    /// Check if this node, at a given depth, is to be splitted if it was a leaf holding the given points.
+    fn splits(&self, config: &OctreeConfig, points: &PointSet<'point, L>, depth: usize) -> bool {
+        if depth >= config.max_depth || points.len() <= config.capacity {
+            return false;
+        }
+        let mut locations = points
+            .iter()
+            .map(|point| self.project(config, point.get_location()));
+        let first = locations.next();
+        locations.any(|location| Some(location) != first)
+    }
+
+    /// Split this node and push its points down into the children covering them, so only leaves hold points, and the
+    /// structure depends on the points stored but not on the order they came in.
+    fn split(&mut self, config: &OctreeConfig, depth: usize) {
+        self.splitted = true;
+        self.split_axes = self.choose_split_axes(config);
+
+        // Only the children of the octants in use are allocated.
+        let children = self
+            .split_bounding_box()
+            .into_iter()
+            .enumerate()
+            .map(|(index, bounding_box)| TreeNode {
+                closed_axes: self.child_closed_axes(index),
+                ..self.empty_like(bounding_box)
+            })
+            .collect();
+
+        self.children = Some(children);
+        let points: Vec<_> = self.points.drain().map(|point| (0, point)).collect();
+        self.extend_children(config, points, &mut Vec::new(), depth);
+    }
+
+    fn covers(&self, config: &OctreeConfig, point: &L) -> bool {
+        self.covers_location(config, point.get_location())
+    }
+
+    /// Check if a point at a location could be recorded in this node, like [covers](TreeNode::covers).
+    fn covers_location(&self, config: &OctreeConfig, location: [T; 3]) -> bool {
+        let location = self.project(config, location);
+        let covers = |bounding_box: &BoundingBox<T>| {
+            bounding_box.covers(&location)
+                || self.closed_axes != 0
+                    && bounding_box.covers_inclusive(&location)
+                    && (0..3).all(|i| {
+                        location[i] < bounding_box.max[i] || self.closed_axes & (1 << i) != 0
+                    })
+        };
+        if config.loose_factor == 0.0 {
+            covers(&self.bounding_box)
+        } else {
+            covers(&self.loose_bounding_box(config))
+        }
+    }
+
+    /// Check if a query [BoundingBox] covers a point of this node. Like [BoundingBox::covers], but along an axis this
+    /// node covers its max surface along, the query covers its own max surface where the two coincide. So the points
+    /// of a node are all covered by a query containing its [BoundingBox], as [query](TreeNode::query) assumes, and a
+    /// query over the bounds of an inclusive root finds every point.
+    fn query_covers(
+        &self,
+        config: &OctreeConfig,
+        bounding_box: &BoundingBox<T>,
+        point: &L,
+    ) -> bool {
+        let location = self.project(config, point.get_location());
+        if bounding_box.covers(&location) {
+            return true;
+        }
+        if self.closed_axes == 0 || !bounding_box.covers_inclusive(&location) {
+            return false;
+        }
+        let bounds = if config.loose_factor == 0.0 {
+            self.bounding_box.clone()
+        } else {
+            self.loose_bounding_box(config)
+        };
+        (0..3).all(|i| {
+            location[i] < bounding_box.max[i]
+                || self.closed_axes & (1 << i) != 0 && location[i] == bounds.max[i]
+        })
+    }
+
+    /// The axes to split this node along: every axis but the projected one, whose extent is at least the split ratio
+    /// times the largest extent. A flat axis, only as wide as the smallest possible step, e.g. z of planar points, can
+    /// not separate anything and is never splitted.
+    fn choose_split_axes(&self, config: &OctreeConfig) -> u8 {
+        let mut extents = [T::ZERO; 3];
+        for (i, extent) in extents.iter_mut().enumerate() {
+            if config.project_axis != Some(i) {
+                *extent = self.bounding_box.max[i] - self.bounding_box.min[i];
+            }
+        }
+        let largest = extents.iter().fold(T::ZERO, |a, b| a.max(*b));
+
+        let mut ret = 0;
+        for (i, extent) in extents.iter().enumerate() {
+            let flat = self.bounding_box.max[i] <= self.bounding_box.min[i].next_up();
+            if config.project_axis != Some(i)
+                && !flat
+                && *extent >= T::from_f32(config.split_ratio) * largest
+            {
+                ret |= 1 << i;
+            }
+        }
+        ret
+    }
+
+    /// The index of the child whose [BoundingBox] covers a point covered by this splitted node, computed from the
+    /// centre of this node rather than by asking every child. Along the axes this node is splitted along, the point is
+    /// in the upper half if it is not below the centre. This is the only child covering the point unless the node is
+    /// loose, as loose children overlap.
+    fn child_index(&self, config: &OctreeConfig, point: &L) -> usize {
+        self.child_index_of_location(config, point.get_location())
+    }
+
+    /// The index of the child a point at a location belongs to, like [child_index](TreeNode::child_index).
+    fn child_index_of_location(&self, config: &OctreeConfig, location: [T; 3]) -> usize {
+        let location = self.project(config, location);
+        self.index_of_octant(Octant::for_point(&self.bounding_box, &location).index())
+    }
+
+    /// The [BoundingBox]es of the children of this node, in the order of their octants. Along an axis this node is not
+    /// splitted along, the children keep the whole range of this node.
+    fn split_bounding_box(&self) -> Vec<BoundingBox<T>> {
+        let octants = self.bounding_box.split();
+        (0..1 << self.split_axes.count_ones())
+            .map(|index| {
+                let mut bounding_box = octants[self.octant_of_index(index)].clone();
+                for axis in 0..3 {
+                    if self.split_axes & (1 << axis) == 0 {
+                        bounding_box.min[axis] = self.bounding_box.min[axis];
+                        bounding_box.max[axis] = self.bounding_box.max[axis];
+                    }
+                }
+                bounding_box
+            })
+            .collect()
+    }
+
+    /// Move a location onto the min surface of this node along the projected axis, if there is one.
+    fn project(&self, config: &OctreeConfig, mut location: [T; 3]) -> [T; 3] {
+        if let Some(axis) = config.project_axis {
+            location[axis] = self.bounding_box.min[axis];
+        }
+        location
+    }
+
+    /// Give a [BoundingBox] the range of this node along the projected axis, if there is one, so it ignores that axis
+    /// when compared with the nodes and projected points.
+    fn project_bounding_box(
+        &self,
+        config: &OctreeConfig,
+        bounding_box: &BoundingBox<T>,
+    ) -> BoundingBox<T> {
+        let mut ret = bounding_box.clone();
+        if let Some(axis) = config.project_axis {
+            ret.min[axis] = self.bounding_box.min[axis];
+            ret.max[axis] = self.bounding_box.max[axis];
+        }
+        ret
+    }
+
+    /// The space in which points may belong to this node, its [BoundingBox] grown by the loose factor on every side.
+    fn loose_bounding_box(&self, config: &OctreeConfig) -> BoundingBox<T> {
+        let mut ret = self.bounding_box.clone();
+        for i in 0..3 {
+            let margin = (ret.max[i] - ret.min[i]).max(T::ZERO) * T::from_f32(config.loose_factor);
+            ret.min[i] -= margin;
+            ret.max[i] += margin;
+        }
+        ret
+    }
+
+    /// Check if a point is recorded in this sub tree. Only the children covering the point may hold it.
+    fn contains(&self, config: &OctreeConfig, point: &L) -> bool {
+        if self.points.contains(point) {
+            return true;
+        }
+        if !self.covers(config, point) {
+            return false;
+        }
+        match &self.children {
+            None => false,
+            Some(children) if config.loose_factor == 0.0 => {
+                children[self.child_index(config, point)].contains(config, point)
+            }
+            Some(children) => children.iter().any(|child| child.contains(config, point)),
+        }
+    }
+
+    /// Check if any point at exactly a location is recorded in this sub tree, searching like
+    /// [contains](TreeNode::contains).
+    fn contains_location(&self, config: &OctreeConfig, location: [T; 3]) -> bool {
+        if self
+            .points
+            .iter()
+            .any(|point| point.get_location() == location)
+        {
+            return true;
+        }
+        if !self.covers_location(config, location) {
+            return false;
+        }
+        match &self.children {
+            None => false,
+            Some(children) if config.loose_factor == 0.0 => children
+                [self.child_index_of_location(config, location)]
+            .contains_location(config, location),
+            Some(children) => children
+                .iter()
+                .any(|child| child.contains_location(config, location)),
+        }
+    }
+
+    fn delete(&mut self, config: &OctreeConfig, point: &L) -> bool {
+        // HashSet returns false if removes a non-existing element.
+        let mut ret = self.points.remove(point);
+        // Only the children covering the point may hold it.
+        if !ret && self.covers(config, point) {
+            if config.loose_factor == 0.0 {
+                let octant = self.child_index(config, point);
+                if let Some(children) = &mut self.children {
+                    ret = children[octant].delete(config, point);
+                }
+            } else if let Some(children) = &mut self.children {
+                for child in children.iter_mut() {
+                    ret |= child.delete(config, point);
+                }
+            }
+        }
+        if ret {
+            self.recount();
+            self.merge_children(config);
+        }
+        ret
+    }
+
+    /// Remove the points of this sub tree for which `f` returns `false`, recording them, and merge children back where
+    /// the remaining points fit into their parent.
+    fn retain<F>(&mut self, config: &OctreeConfig, f: &mut F, removed: &mut Vec<&'point L>)
+    where
+        F: FnMut(&L) -> bool,
+    {
+        let before = removed.len();
+        self.points.retain(|point| {
+            let keep = f(point);
+            if !keep {
+                removed.push(*point);
+            }
+            keep
+        });
+        if let Some(children) = &mut self.children {
+            for child in children.iter_mut() {
+                child.retain(config, f, removed);
+            }
+        }
+        if removed.len() > before {
+            self.recount();
+            self.merge_children(config);
+        }
+    }
+
+    /// Remove the points of this sub tree which are no longer covered by the node holding them, as their location
+    /// changed, recording them, and merge children back where the remaining points fit into their parent.
+    fn remove_drifted(&mut self, config: &OctreeConfig, removed: &mut Vec<&'point L>) {
+        let before = removed.len();
+        removed.extend(
+            self.points
+                .iter()
+                .filter(|point| !self.covers(config, point)),
+        );
+        for point in &removed[before..] {
+            self.points.remove(point);
+        }
+        if let Some(children) = &mut self.children {
+            for child in children.iter_mut() {
+                child.remove_drifted(config, removed);
+            }
+        }
+        if removed.len() > before {
+            self.recount();
+            self.merge_children(config);
+        }
+    }
+
+    /// Remove the points of this sub tree a [query](TreeNode::query) would return, with the same pruning, recording
+    /// them, and merge children back where the remaining points fit into their parent.
+    fn remove_within(
+        &mut self,
+        config: &OctreeConfig,
+        bounding_box: &BoundingBox<T>,
+        removed: &mut Vec<&'point L>,
+    ) {
+        if !self.overlaps(config, bounding_box) {
+            return;
+        }
+        let before = removed.len();
+        removed.extend(
+            self.points
+                .iter()
+                .filter(|point| self.query_covers(config, bounding_box, point)),
+        );
+        for point in &removed[before..] {
+            self.points.remove(point);
+        }
+        if let Some(children) = &mut self.children {
+            for child in children.iter_mut() {
+                child.remove_within(config, bounding_box, removed);
+            }
+        }
+        if removed.len() > before {
+            self.recount();
+            self.merge_children(config);
+        }
+    }
+
+    /// Pull the points of the children back up and drop the children, if they all fit into this node, or would not be
+    /// separated by splitting it. Children merge their own children first on deletion, so several levels collapse at
+    /// once.
+    fn merge_children(&mut self, config: &OctreeConfig) {
+        if !self.splitted
+            || self.subtree_count > config.capacity && !self.coincident(config, &mut None)
+        {
+            return;
+        }
+        if let Some(children) = self.children.take() {
+            for child in children.iter() {
+                child.for_each(&mut |point| {
+                    self.points.insert(point);
+                });
+            }
+        }
+        self.splitted = false;
+        self.recount();
+    }
+
+    /// Check if all points of this sub tree share one location, also with the given one, if any, stopping at the first
+    /// point which does not.
+    fn coincident(&self, config: &OctreeConfig, location: &mut Option<[T; 3]>) -> bool {
+        for point in &self.points {
+            let point_location = self.project(config, point.get_location());
+            match location {
+                Some(location) if *location != point_location => return false,
+                Some(_) => {}
+                None => *location = Some(point_location),
+            }
+        }
+        self.children.as_ref().is_none_or(|children| {
+            children
+                .iter()
+                .all(|child| child.coincident(config, location))
+        })
+    }
+
+    fn query(&self, config: &OctreeConfig, bounding_box: &BoundingBox<T>) -> HashSet<&'point L> {
+        // Place holder for the query answer.
+        let mut ret = HashSet::new();
+
+        // If they do not overlap, then we won't find any points in this sub tree which is covered by the query bounding
+        // box.
+        if !self.overlaps(config, bounding_box) {
+            return ret;
+        }
+        // If the query bounding box contains the whole space of this node, every point of the sub tree is covered.
+        let bounds = if config.loose_factor == 0.0 {
+            self.bounding_box.clone()
+        } else {
+            self.loose_bounding_box(config)
+        };
+        if bounding_box.contains_box(&bounds) {
+            self.for_each(&mut |point| {
+                ret.insert(point);
+            });
+            return ret;
+        }
+        for point in &self.points {
+            if self.query_covers(config, bounding_box, point) {
+                // point is of type &&L.
+                ret.insert(*point);
+            }
+        }
+
+        // Recursively ask sub tree if they have something covered by the query bounding box.
+        if self.splitted {
+            for child in self.children.as_ref().unwrap().iter() {
+                ret.extend(child.query(config, bounding_box));
+            }
+        }
+        ret
+    }
+
+    /// Find the points a [query](TreeNode::query) would return, searching the children of large sub trees in parallel.
+    #[cfg(feature = "rayon")]
+    fn query_parallel(&self, config: &OctreeConfig, bounding_box: &BoundingBox<T>) -> HashSet<&L>
+    where
+        L: Sync,
+    {
+        if !self.splitted || self.subtree_count <= PARALLEL_THRESHOLD {
+            return self.query(config, bounding_box);
+        }
+        if !self.overlaps(config, bounding_box) {
+            return HashSet::new();
+        }
+
+        let mut ret: HashSet<&L> = self
+            .points
+            .iter()
+            .copied()
+            .filter(|point| self.query_covers(config, bounding_box, point))
+            .collect();
+        let found = self
+            .children
+            .as_ref()
+            .unwrap()
+            .par_iter()
+            .map(|child| child.query_parallel(config, bounding_box))
+            .reduce(HashSet::new, |mut a, mut b| {
+                // Extend the larger set, so fewer points are moved.
+                if a.len() < b.len() {
+                    core::mem::swap(&mut a, &mut b);
+                }
+                a.extend(b);
+                a
+            });
+        ret.extend(found);
+        ret
+    }
+
+    /// Count the points a [query](TreeNode::query) would return, with the same pruning.
+    fn count_in_box(&self, config: &OctreeConfig, bounding_box: &BoundingBox<T>) -> usize {
+        if !self.overlaps(config, bounding_box) {
+            return 0;
+        }
+        let bounds = if config.loose_factor == 0.0 {
+            self.bounding_box.clone()
+        } else {
+            self.loose_bounding_box(config)
+        };
+        if bounding_box.contains_box(&bounds) {
+            return self.len();
+        }
+
+        let mut ret = self
+            .points
+            .iter()
+            .filter(|point| self.query_covers(config, bounding_box, point))
+            .count();
+        if let Some(children) = &self.children {
+            ret += children
+                .iter()
+                .map(|child| child.count_in_box(config, bounding_box))
+                .sum::<usize>();
+        }
+        ret
+    }
+
+    fn overlaps(&self, config: &OctreeConfig, bounding_box: &BoundingBox<T>) -> bool {
+        if config.loose_factor == 0.0 {
+            self.bounding_box.overlaps(bounding_box)
+        } else {
+            self.loose_bounding_box(config).overlaps(bounding_box)
+        }
+    }
+
+    fn reserve(&mut self, config: &OctreeConfig, additional: usize) {
+        // A node never holds more than capacity points, anything beyond that goes to children.
+        let remaining = config.capacity.saturating_sub(self.points.len());
+        self.points.reserve(additional.min(remaining));
+    }
+
+    fn cells_crossed_by_plane(&self, plane: &[T; 4], ret: &mut Vec<BoundingBox<T>>) {
+        // A sub tree completely on one side of the plane has no crossed leaf.
+        if self.bounding_box.plane_side(plane) != PlaneSide::Crossing {
+            return;
+        }
+        if self.splitted {
+            for child in self.children.as_ref().unwrap().iter() {
+                child.cells_crossed_by_plane(plane, ret);
+            }
+        } else {
+            ret.push(self.bounding_box.clone());
+        }
+    }
+
+    /// Visit every point recorded in this sub tree.
+    fn for_each<F>(&self, f: &mut F)
+    where
+        F: FnMut(&'point L),
+    {
+        for point in &self.points {
+            f(*point);
+        }
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.for_each(f);
+            }
+        }
+    }
+
+    fn check_invariants(&self, config: &OctreeConfig, path: &mut Vec<usize>) -> Result<(), String> {
+        for point in &self.points {
+            if !self.covers(config, point) {
+                return Err(format!(
+                    "node at octants {:?}: point at {:?} is not covered by {:?}",
+                    path,
+                    point.get_location(),
+                    self.bounding_box
+                ));
+            }
+        }
+        let count = self.points.len()
+            + self.children.as_ref().map_or(0, |children| {
+                children.iter().map(|child| child.subtree_count).sum()
+            });
+        if self.subtree_count != count {
+            return Err(format!(
+                "node at octants {:?}: counts {} points in its sub tree but holds {}",
+                path, self.subtree_count, count
+            ));
+        }
+        if !self.splitted && self.needs_split(config, path.len()) {
+            return Err(format!(
+                "node at octants {:?}: holds {} points but its capacity is {}",
+                path,
+                self.points.len(),
+                config.capacity
+            ));
+        }
+
+        match (&self.children, self.splitted) {
+            (None, false) => Ok(()),
+            (None, true) => Err(format!(
+                "node at octants {:?}: splitted but has no children",
+                path
+            )),
+            (Some(_), false) => Err(format!(
+                "node at octants {:?}: has children but is not splitted",
+                path
+            )),
+            (Some(children), true) => {
+                // Points are pushed down on split, so a splitted node only keeps the ones no child covers.
+                for point in &self.points {
+                    if let Some(index) = children
+                        .iter()
+                        .position(|child| child.covers(config, point))
+                    {
+                        return Err(format!(
+                            "node at octants {:?}: point at {:?} is not pushed down to the child in octant {}",
+                            path,
+                            point.get_location(),
+                            self.octant_of_index(index)
+                        ));
+                    }
+                }
+                let bounding_boxes = self.split_bounding_box();
+                if children.len() != bounding_boxes.len() {
+                    return Err(format!(
+                        "node at octants {:?}: has {} children but is splitted into {}",
+                        path,
+                        children.len(),
+                        bounding_boxes.len()
+                    ));
+                }
+                for (i, (child, bounding_box)) in children.iter().zip(bounding_boxes).enumerate() {
+                    path.push(self.octant_of_index(i));
+                    if child.bounding_box != bounding_box {
+                        return Err(format!(
+                            "node at octants {:?}: {:?} does not match the split of its parent {:?}",
+                            path, child.bounding_box, bounding_box
+                        ));
+                    }
+                    if child.closed_axes != self.child_closed_axes(i) {
+                        return Err(format!(
+                            "node at octants {:?}: covers its max surface along axes {:#05b} instead of {:#05b}",
+                            path,
+                            child.closed_axes,
+                            self.child_closed_axes(i)
+                        ));
+                    }
+                    child.check_invariants(config, path)?;
+                    path.pop();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn query_with_cells(
+        &self,
+        config: &OctreeConfig,
+        bounding_box: &BoundingBox<T>,
+        ret: &mut Vec<(&'point L, BoundingBox<T>)>,
+    ) {
+        // Same pruning as query, so the same points are found.
+        self.prune_walk(
+            config,
+            &|bounds| bounds.overlaps(bounding_box),
+            &mut |point, node| {
+                if node.query_covers(config, bounding_box, point) {
+                    ret.push((point, node.bounding_box.clone()));
+                }
+            },
+        );
+    }
+
+    /// Construct an empty node over a [BoundingBox], covering its max surface along the same axes as this node.
+    fn empty_like(&self, bounding_box: BoundingBox<T>) -> Self {
+        TreeNode {
+            bounding_box,
+            closed_axes: self.closed_axes,
+            ..Default::default()
+        }
+    }
+
+    /// Mean location of the points of this sub tree, weighted by their mass, or `None` if the total mass is 0.
+    fn weighted_centroid<F>(&self, mass: &mut F) -> Option<[T; 3]>
+    where
+        F: FnMut(&L) -> T,
+    {
+        let mut total = 0.0f64;
+        let mut sum = [0.0f64; 3];
+        self.for_each(&mut |point| {
+            let weight = mass(point).to_f64();
+            total += weight;
+            for (sum, coordinate) in sum.iter_mut().zip(point.get_location()) {
+                *sum += weight * coordinate.to_f64();
+            }
+        });
+        if total == 0.0 {
+            return None;
+        }
+        Some(sum.map(|sum| T::from_f64(sum / total)))
+    }
+
+    /// Check if a point other than `point` in this sub tree lies within the radius of the projected centre.
+    fn has_neighbor_within(
+        &self,
+        config: &OctreeConfig,
+        point: &L,
+        centre: &[T; 3],
+        radius: T,
+    ) -> bool {
+        let bounds = if config.loose_factor == 0.0 {
+            self.bounding_box.clone()
+        } else {
+            self.loose_bounding_box(config)
+        };
+        if !bounds.intersects_sphere(centre, radius) {
+            return false;
+        }
+
+        let radius_sq = radius * radius;
+        self.points.iter().any(|other| {
+            *other != point
+                && distance_sq(&self.project(config, other.get_location()), centre) <= radius_sq
+        }) || self.children.as_ref().is_some_and(|children| {
+            children
+                .iter()
+                .any(|child| child.has_neighbor_within(config, point, centre, radius))
+        })
+    }
+
+    /// Visit the points of this sub tree together with the node holding each, skipping the nodes rejected by
+    /// `descend`, which is given the [BoundingBox] of every node grown by its loose factor. This is the one pruned walk
+    /// every region query is built on.
+    fn prune_walk<F, G>(&self, config: &OctreeConfig, descend: &F, visit: &mut G)
+    where
+        F: Fn(&BoundingBox<T>) -> bool,
+        G: FnMut(&'point L, &Self),
+    {
+        let bounds = if config.loose_factor == 0.0 {
+            self.bounding_box.clone()
+        } else {
+            self.loose_bounding_box(config)
+        };
+        if !descend(&bounds) {
+            return;
+        }
+
+        for point in &self.points {
+            visit(*point, self);
+        }
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.prune_walk(config, descend, visit);
+            }
+        }
+    }
+
+    /// Visit every point a [query](TreeNode::query) would return, with the same pruning.
+    fn query_each<F>(&self, config: &OctreeConfig, bounding_box: &BoundingBox<T>, f: &mut F)
+    where
+        F: FnMut(&'point L),
+    {
+        self.prune_walk(
+            config,
+            &|bounds| bounds.overlaps(bounding_box),
+            &mut |point, node| {
+                if node.query_covers(config, bounding_box, point) {
+                    f(point);
+                }
+            },
+        );
+    }
+
+    fn len(&self) -> usize {
+        self.subtree_count
+    }
+
+    fn is_empty(&self) -> bool {
+        self.subtree_count == 0
+    }
+
+    /// Insert many points like [extend](TreeNode::extend), but the children of this node take their points in parallel.
+    /// Only a node which is not loose can do so, as then every point is covered by exactly one child, and no child
+    /// turns down a point for the next one to take. Loose nodes, and batches too small to be worth a task, are
+    /// extended sequentially.
+    #[cfg(feature = "rayon")]
+    fn extend_parallel(
+        &mut self,
+        config: &OctreeConfig,
+        points: Vec<(usize, &'point L)>,
+        inserted: &mut Vec<&'point L>,
+        depth: usize,
+    ) -> Vec<(usize, &'point L)>
+    where
+        L: Sync,
+    {
+        if config.loose_factor != 0.0 || points.len() < PARALLEL_THRESHOLD {
+            return self.extend(config, points, inserted, depth);
+        }
+
+        let (covered, rejected): (Vec<_>, Vec<_>) = points
+            .into_iter()
+            .partition(|(_, point)| self.covers(config, point));
+        if !self.splitted {
+            let held: PointSet<'point, L> = self
+                .points
+                .iter()
+                .copied()
+                .chain(covered.iter().map(|(_, point)| *point))
+                .collect();
+            if !self.splits(config, &held, depth) {
+                self.extend(config, covered, inserted, depth);
+                return rejected;
+            }
+            self.split(config, depth);
+        }
+        let mut batches: [Vec<(usize, &'point L)>; 8] = Default::default();
+        for (i, point) in covered {
+            // Every point is covered by exactly one child, as this node is not loose.
+            batches[self.child_index(config, point)].push((i, point));
+        }
+        let results: Vec<_> = self
+            .children
+            .as_mut()
+            .unwrap()
+            .par_iter_mut()
+            .zip(batches)
+            .map(|(child, batch)| {
+                let mut inserted = Vec::new();
+                child.extend_parallel(config, batch, &mut inserted, depth + 1);
+                inserted
+            })
+            .collect();
+        inserted.extend(results.into_iter().flatten());
+        self.recount();
+        rejected
+    }
+
+    /// Insert many points as if by [insert](TreeNode::insert) one by one in order, recording the inserted ones. Points
+    /// come with their position in the order, and the ones which are not inserted are returned in that order.
+    fn extend(
+        &mut self,
+        config: &OctreeConfig,
+        points: Vec<(usize, &'point L)>,
+        inserted: &mut Vec<&'point L>,
+        depth: usize,
+    ) -> Vec<(usize, &'point L)> {
+        let (covered, rejected): (Vec<_>, Vec<_>) = points
+            .into_iter()
+            .partition(|(_, point)| self.covers(config, point));
+        if self.splitted {
+            self.extend_children(config, covered, inserted, depth);
+        } else {
+            for (_, point) in covered {
+                if self.points.insert(point) {
+                    inserted.push(point);
+                }
+            }
+            if self.needs_split(config, depth) {
+                self.split(config, depth);
+            }
+        }
+        self.recount();
+        rejected
+    }
+
+    /// Hand points covered by this splitted node, at a given depth, to its children. Unless the node is loose, each
+    /// point goes straight to the child in its octant. Otherwise what a child does not take is offered to the next
+    /// one, as by [insert](TreeNode::insert), and what no child takes stays in this node.
+    fn extend_children(
+        &mut self,
+        config: &OctreeConfig,
+        mut points: Vec<(usize, &'point L)>,
+        inserted: &mut Vec<&'point L>,
+        depth: usize,
+    ) {
+        if config.loose_factor == 0.0 {
+            let mut batches: [Vec<(usize, &'point L)>; 8] = Default::default();
+            for (i, point) in points.drain(..) {
+                batches[self.child_index(config, point)].push((i, point));
+            }
+            for (child, batch) in self.children.as_mut().unwrap().iter_mut().zip(batches) {
+                if !batch.is_empty() {
+                    points.extend(child.extend(config, batch, inserted, depth + 1));
+                }
+            }
+        } else {
+            for child in self.children.as_mut().unwrap().iter_mut() {
+                if points.is_empty() {
+                    break;
+                }
+                points = child.extend(config, points, inserted, depth + 1);
+            }
+        }
+        for (_, point) in points {
+            if self.points.insert(point) {
+                inserted.push(point);
+            }
+        }
+    }
+}
+
+impl<'point, L, T> Default for TreeNode<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn default() -> Self {
+        Self {
+            children: None,
+            bounding_box: BoundingBox::default(),
+            points: PointSet::default(),
+            splitted: false,
+            split_axes: 0b111,
+            closed_axes: 0,
+            subtree_count: 0,
+        }
+    }
+}
+
+impl<'point, L, T> Clone for TreeNode<'point, L, T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            children: self.children.clone(),
+            bounding_box: self.bounding_box.clone(),
+            points: self.points.clone(),
+            splitted: self.splitted,
+            split_axes: self.split_axes,
+            closed_axes: self.closed_axes,
+            subtree_count: self.subtree_count,
+        }
+    }
+}
+
+impl OctreeBuilder {
+    /// Construct an [OctreeBuilder] with the default settings, which build the same [Octree] as [Octree::new].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many points a node holds before it is splitted, 8 by default. Larger capacities trade fewer nodes, and
+    /// a shallower tree, for more linear scanning of the points within a node. A capacity of 0 is treated as 1.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::OctreeBuilder;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = OctreeBuilder::new()
+    ///     .capacity(16)
+    ///     .build(points.iter().collect());
+    /// assert_eq!(octree.leaf_count(), 1);
+    /// ```
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.config.capacity = capacity.max(1);
+        self
+    }
+
+    /// Make every node loose: a point belongs to a node if it is covered by the node's [BoundingBox] grown by
+    /// `loose_factor` times its size on every side. So a point which moves a little still belongs to the same node and
+    /// the tree needs to be rebuilt less often. The cost is query precision, neighbouring nodes overlap so a query may
+    /// need to check a few extra nodes. The default is 0, negative factors are treated as 0.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::OctreeBuilder;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(11.0, 5.0, 5.0);
+    /// let points = vec![point1, point2];
+    ///
+    /// let mut octree = OctreeBuilder::new()
+    ///     .loose_factor(0.25)
+    ///     .build(points.iter().collect());
+    /// assert!(octree.insert(&point3));
+    /// ```
+    pub fn loose_factor(mut self, loose_factor: f32) -> Self {
+        self.config.loose_factor = loose_factor.max(0.0);
+        self
+    }
+
+    /// Choose how [Octree::insert_growing] grows the root, see [GrowthPolicy]. The default is [GrowthPolicy::Double].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{GrowthPolicy, OctreeBuilder};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(12.0, 5.0, 5.0);
+    /// let points = vec![point1, point2];
+    ///
+    /// let mut octree = OctreeBuilder::new()
+    ///     .growth_policy(GrowthPolicy::Fit)
+    ///     .build(points.iter().collect());
+    /// assert!(octree.insert_growing(&point3));
+    /// assert!(!octree.covers(&Point3D::new(13.0, 5.0, 5.0)));
+    /// ```
+    pub fn growth_policy(mut self, growth_policy: GrowthPolicy) -> Self {
+        self.config.growth_policy = growth_policy;
+        self
+    }
+
+    /// Make [Octree::insert], [Octree::try_insert], [Octree::extend] and [Octree::update] grow the root to cover new
+    /// points, as [Octree::insert_growing] does, rather than dropping the ones out of bounds. Points with infinite or
+    /// NaN coordinates are still not inserted. The default is `false`.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::OctreeBuilder;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(20.0, 20.0, 20.0);
+    /// let points = vec![point1, point2];
+    ///
+    /// let mut octree = OctreeBuilder::new()
+    ///     .grow_on_insert(true)
+    ///     .build(points.iter().collect());
+    /// assert!(octree.insert(&point3));
+    /// assert!(octree.covers(&point3));
+    /// ```
+    pub fn grow_on_insert(mut self, grow_on_insert: bool) -> Self {
+        self.config.grow_on_insert = grow_on_insert;
+        self
+    }
+
+    /// Make the root cover its max surface too, so a [BoundingBox] given to [OctreeBuilder::build_with_bounds] takes
+    /// the points on its max corner and faces, e.g. a world volume whose far walls are valid locations. Inside the
+    /// tree the children still split their parent without overlap: a point on the centre plane of a node belongs to
+    /// the upper half, and only the children sharing the max surface of the root cover it. A [BoundingBox] queried with
+    /// then covers its max surface where it lies on the one of the root, so every query, e.g. [Octree::query] or
+    /// [Octree::count_in_box], over the [bounds](Octree::bounds) finds the points on it. The default is `false`,
+    /// half-open bounds.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, OctreeBuilder};
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let corner = Point3D::new(10.0, 10.0, 10.0);
+    /// let beyond = Point3D::new(10.5, 10.0, 10.0);
+    ///
+    /// let mut octree = OctreeBuilder::new()
+    ///     .boundary_inclusive(true)
+    ///     .build_with_bounds(bounding_box, Vec::new());
+    /// assert!(octree.insert(&corner));
+    /// assert!(!octree.insert(&beyond));
+    /// assert_eq!(octree.count_in_box(octree.bounds()), 1);
+    /// ```
+    pub fn boundary_inclusive(mut self, boundary_inclusive: bool) -> Self {
+        self.config.boundary_inclusive = boundary_inclusive;
+        self
+    }
+
+    /// Collapse an axis, 0 for x, 1 for y or 2 for z, to get a quadtree for data which is effectively 2D, e.g. terrain
+    /// or maps. Nodes are splitted into 4 quadrants along the remaining two axes, and points are projected onto the min
+    /// surface along the collapsed one when inserting them and by every query, e.g. [Octree::covers], [Octree::query],
+    /// [Octree::query_radius], [Octree::k_nearest] and [Octree::density_grid]. The points themselves keep their full 3D
+    /// locations, e.g. in [Octree::bounds_of_query] and [Octree::centroid]. The default is `None`, a plain octree.
+    ///
+    /// # Panics
+    /// Panics if the axis is larger than 2.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, OctreeBuilder};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 0.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 100.0);
+    /// let points = vec![point1, point2];
+    ///
+    /// let mut octree = OctreeBuilder::new()
+    ///     .project_axis(Some(2))
+    ///     .build(points.iter().collect());
+    /// assert!(octree.insert(&point3));
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(4.0, 4.0, 0.0), Point3D::new(6.0, 6.0, 0.0)].iter().collect());
+    /// assert!(octree.query(&bounding_box).contains(&point3));
+    /// ```
+    pub fn project_axis(mut self, project_axis: Option<usize>) -> Self {
+        assert!(
+            project_axis.is_none_or(|axis| axis < 3),
+            "project axis must be 0, 1 or 2"
+        );
+        self.config.project_axis = project_axis;
+        self
+    }
+
+    /// Only split a node along the axes whose extent is at least `split_ratio` times its largest extent, so a node of
+    /// very flat or thin data is splitted into 4 or 2 children instead of 8, and cells stay closer to cubic. The
+    /// default is 0, which splits along every axis; ratios are clamped to `[0, 1]`. An axis on which all points share
+    /// one coordinate, e.g. z of 2D data stored with z = 0, is never splitted whatever the ratio, as the
+    /// [BoundingBox] of [Octree::new] is then only as wide as the smallest possible step along it.
+    ///
+    /// Only the children of the octants in use are allocated, each keeping the whole range of its parent along the axes
+    /// which are not splitted. [NodeCursor::child] returns `None` for the unused octants.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::OctreeBuilder;
+    ///
+    /// // A flat point cloud, 100 wide and 1 high.
+    /// let points: Vec<Point3D> = (0..100)
+    ///     .map(|i| Point3D::new((i % 10) as f32 * 10.0, (i / 10) as f32 * 10.0, (i % 2) as f32))
+    ///     .collect();
+    /// let octree = OctreeBuilder::new()
+    ///     .split_ratio(0.5)
+    ///     .build(points.iter().collect());
+    ///
+    /// let root = octree.root_cursor();
+    /// assert_eq!((0..8).filter(|octant| root.child(*octant).is_some()).count(), 4);
+    /// ```
+    pub fn split_ratio(mut self, split_ratio: f32) -> Self {
+        self.config.split_ratio = split_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the depth at which nodes are no longer splitted, 32 by default, the root being at depth 0. A leaf at this
+    /// depth stores every point it gets regardless of its capacity, so points crowded into a tiny space, e.g. ones
+    /// which only differ by rounding errors, do not make the tree arbitrarily deep. Points which share one location
+    /// exactly are never splitted apart at any depth, as no split could separate them.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::OctreeBuilder;
+    ///
+    /// let points: Vec<Point3D> = (0..100).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = OctreeBuilder::new()
+    ///     .capacity(1)
+    ///     .max_depth(2)
+    ///     .build(points.iter().collect());
+    ///
+    /// assert_eq!(octree.depth(), 2);
+    /// assert_eq!(octree.len(), 100);
+    /// ```
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+
+    /// Getter for the [capacity](OctreeBuilder::capacity) of a node.
+    pub fn get_capacity(&self) -> usize {
+        self.config.capacity
+    }
+
+    /// Getter for the [loose factor](OctreeBuilder::loose_factor) of every node.
+    pub fn get_loose_factor(&self) -> f32 {
+        self.config.loose_factor
+    }
+
+    /// Getter for the [GrowthPolicy] of [Octree::insert_growing].
+    pub fn get_growth_policy(&self) -> GrowthPolicy {
+        self.config.growth_policy
+    }
+
+    /// Getter for the [collapsed axis](OctreeBuilder::project_axis), if any.
+    pub fn get_project_axis(&self) -> Option<usize> {
+        self.config.project_axis
+    }
+
+    /// Getter for the [split ratio](OctreeBuilder::split_ratio) which decides the axes a node is splitted along.
+    pub fn get_split_ratio(&self) -> f32 {
+        self.config.split_ratio
+    }
+
+    /// Getter for the [depth](OctreeBuilder::max_depth) at which nodes are no longer splitted.
+    pub fn get_max_depth(&self) -> usize {
+        self.config.max_depth
+    }
+
+    /// Getter for whether inserting [grows](OctreeBuilder::grow_on_insert) the root.
+    pub fn get_grow_on_insert(&self) -> bool {
+        self.config.grow_on_insert
+    }
+
+    /// Getter for whether the root [covers its max surface](OctreeBuilder::boundary_inclusive).
+    pub fn get_boundary_inclusive(&self) -> bool {
+        self.config.boundary_inclusive
+    }
+
+    /// Getter for all the settings of this builder at once.
+    pub fn get_config(&self) -> OctreeConfig {
+        self.config
+    }
+
+    /// Construct an [Octree] that covers all given points, with the settings of this builder.
+    pub fn build<'point, L, T>(self, points: Vec<&'point L>) -> Octree<'point, L, T>
+    where
+        L: Locatable<T> + Eq + Hash,
+        T: Scalar,
+    {
+        // So the created bounding box contains every point in points.
+        Octree {
+            explicit_bounds: false,
+            ..self.build_with_bounds(BoundingBox::covering(points.clone()), points)
+        }
+    }
+
+    /// Construct an [Octree] over the given [BoundingBox], with the settings of this builder, and insert the given
+    /// points. The [BoundingBox] is used as is, so points it does not cover, including the ones on its max surface
+    /// unless [boundary_inclusive](OctreeBuilder::boundary_inclusive), are dropped.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, OctreeBuilder};
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let points = vec![Point3D::new(1.0, 2.0, 3.0), Point3D::new(10.0, 10.0, 10.0)];
+    ///
+    /// let octree = OctreeBuilder::new()
+    ///     .capacity(4)
+    ///     .build_with_bounds(bounding_box, points.iter().collect());
+    /// assert_eq!(octree.len(), 1);
+    /// ```
+    pub fn build_with_bounds<'point, L, T>(
+        self,
+        bounding_box: BoundingBox<T>,
+        points: Vec<&'point L>,
+    ) -> Octree<'point, L, T>
+    where
+        L: Locatable<T> + Eq + Hash,
+        T: Scalar,
+    {
+        let mut root = TreeNode {
+            bounding_box,
+            closed_axes: if self.config.boundary_inclusive {
+                0b111
+            } else {
+                0
+            },
+            ..Default::default()
+        };
+        for point in points {
+            root.insert(&self.config, point, 0);
+        }
+
+        Octree {
+            root,
+            config: self.config,
+            explicit_bounds: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for OctreeConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 8,
+            loose_factor: 0.0,
+            growth_policy: GrowthPolicy::default(),
+            project_axis: None,
+            split_ratio: 0.0,
+            max_depth: MAX_DEPTH,
+            grow_on_insert: false,
+            boundary_inclusive: false,
+        }
+    }
+}
+
+impl Octant {
+    /// All octants, in the order of their [index](Octant::index).
+    pub const ALL: [Octant; 8] = [
+        Octant::Dfl,
+        Octant::Dfr,
+        Octant::Dbl,
+        Octant::Dbr,
+        Octant::Ufl,
+        Octant::Ufr,
+        Octant::Ubl,
+        Octant::Ubr,
+    ];
+
+    /// The position of this octant in [BoundingBox::split]. Bit 0 is set if it is on the right along x, bit 1 if it is
+    /// at the back along y, and bit 2 if it is up along z.
+    /// # Example
+    /// ```
+    /// use octree::Octant;
+    ///
+    /// assert_eq!(Octant::Dfl.index(), 0);
+    /// assert_eq!(Octant::Ubl.index(), 6);
+    /// ```
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The octant at a position in [BoundingBox::split], or `None` if the index is not below 8.
+    /// # Example
+    /// ```
+    /// use octree::Octant;
+    ///
+    /// assert_eq!(Octant::from_index(3), Some(Octant::Dbr));
+    /// assert_eq!(Octant::from_index(8), None);
+    /// ```
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Find the octant of a [BoundingBox] a location falls into, i.e. the one whose [BoundingBox] from
+    /// [BoundingBox::child] covers it if the parent does. A coordinate on the centre plane belongs to the upper half,
+    /// as a [BoundingBox] covers its min surface but not its max surface.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octant};
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(2.0, 2.0, 2.0)].iter().collect());
+    /// assert_eq!(Octant::for_point(&bounding_box, &[0.5, 1.5, 0.5]), Octant::Dbl);
+    /// assert_eq!(Octant::for_point(&bounding_box, &[1.0, 1.0, 1.0]), Octant::Ubr);
+    /// ```
+    pub fn for_point<T>(bounding_box: &BoundingBox<T>, point: &[T; 3]) -> Self
+    where
+        T: Scalar,
+    {
+        let centre = bounding_box.get_centre();
+        let index = (0..3)
+            .filter(|i| point[*i] >= centre[*i])
+            .fold(0, |index, i| index | (1 << i));
+        Self::ALL[index]
+    }
+}
+
+impl<'node, 'point, L, T> NodeCursor<'node, 'point, L, T> {
+    /// Getter for the [BoundingBox] covered by this node.
+    pub fn bounds(&self) -> &'node BoundingBox<T> {
+        &self.node.bounding_box
+    }
+
+    /// Check if this node is a leaf, i.e. it has not been splitted and has no children.
+    pub fn is_leaf(&self) -> bool {
+        !self.node.splitted
+    }
+
+    /// Iterate over the points stored in this node itself, points stored by its children are not included. Only leaves
+    /// hold points, but for a loose node the ones in its margin which none of its children covers, see
+    /// [OctreeBuilder::loose_factor].
+    pub fn points(&self) -> impl Iterator<Item = &'point L> + 'node {
+        self.node.points.iter().copied()
+    }
+
+    /// Get the child of this node in a given octant, which is an index into the order of [BoundingBox::split]. Returns
+    /// `None` for a leaf or an octant larger than 7. A node splitted along fewer than 3 axes, see
+    /// [OctreeBuilder::split_ratio], has only 2 or 4 children, and `None` is returned for the unused octants.
+    pub fn child(&self, octant: usize) -> Option<NodeCursor<'node, 'point, L, T>> {
+        let children = self.node.children.as_ref()?;
+        if octant >= 8 || octant & !(self.node.split_axes as usize) != 0 {
+            return None;
+        }
+        Some(NodeCursor {
+            node: &children[self.node.index_of_octant(octant)],
+            depth: self.depth + 1,
+        })
+    }
+
+    /// Iterate over the children of this node in the order of [BoundingBox::split], none for a leaf. Unused octants are
+    /// left out as by [NodeCursor::child], so draw the [bounds](NodeCursor::bounds) of every child to show how this
+    /// node is splitted.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// let root = octree.root_cursor();
+    /// assert_eq!(root.children().count(), 8);
+    /// assert!(root.children().all(|child| child.is_leaf() && child.depth() == 1));
+    /// assert_eq!(root.children().map(|child| child.points().count()).sum::<usize>(), 10);
+    /// ```
+    pub fn children(&self) -> impl Iterator<Item = NodeCursor<'node, 'point, L, T>> + 'node {
+        let depth = self.depth + 1;
+        self.node
+            .children
+            .iter()
+            .flatten()
+            .map(move |child| NodeCursor { node: child, depth })
+    }
+
+    /// Getter for the depth of this node, the root is at depth 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Find the centroid of the points of this node and all its descendants, e.g. to stand in for them at a coarse
+    /// level of detail, or `None` if the sub tree holds no points. See [Octree::centroid].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::Octree;
+    ///
+    /// let points: Vec<Point3D> = (0..10).map(|i| Point3D::new(i as f32, i as f32, i as f32)).collect();
+    /// let octree = Octree::new(points.iter().collect());
+    ///
+    /// assert_eq!(octree.root_cursor().centroid(), Some([4.5, 4.5, 4.5]));
+    /// ```
+    pub fn centroid(&self) -> Option<[T; 3]>
+    where
+        L: Locatable<T> + Eq + Hash,
+        T: Scalar,
+    {
+        self.node.weighted_centroid(&mut |_| T::ONE)
+    }
+}
+
+impl<'node, 'point, L, T> Clone for NodeCursor<'node, 'point, L, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'node, 'point, L, T> Copy for NodeCursor<'node, 'point, L, T> {}
+
+impl<'node, 'point, L, T> Debug for NodeCursor<'node, 'point, L, T>
+where
+    L: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeCursor")
+            .field("node", self.node)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl<'point, L, T> Quantized<'point, L, T> {
+    /// Snap every point to the nearest multiple of `cell_size` on each axis, and group the points snapped to the same
+    /// location into one cell, e.g. to build an [Octree] of them with [Octree::new_quantized]. Cells are sorted by
+    /// their location.
+    ///
+    /// # Panics
+    /// Panics if a cell size is not positive.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Locatable, Quantized};
+    ///
+    /// let point1 = Point3D::new(0.1, 0.0, 0.0);
+    /// let point2 = Point3D::new(-0.2, 0.1, 0.0);
+    /// let point3 = Point3D::new(9.9, 10.2, 10.0);
+    /// let points = vec![point1.clone(), point2.clone(), point3.clone()];
+    ///
+    /// let cells = Quantized::group(points.iter().collect(), [1.0, 1.0, 1.0]);
+    /// assert_eq!(cells.len(), 2);
+    /// assert_eq!(cells[0].get_location(), [0.0, 0.0, 0.0]);
+    /// assert_eq!(cells[0].points(), &[&point1, &point2]);
+    /// assert_eq!(cells[1].points(), &[&point3]);
+    /// ```
+    pub fn group(points: Vec<&'point L>, cell_size: [T; 3]) -> Vec<Self>
+    where
+        L: Locatable<T>,
+        T: Scalar,
+    {
+        assert!(
+            cell_size.iter().all(|size| *size > T::ZERO),
+            "cell size must be positive"
+        );
+        let mut snapped: Vec<([T; 3], &'point L)> = points
+            .into_iter()
+            .map(|point| {
+                let mut location = point.get_location();
+                for (coordinate, size) in location.iter_mut().zip(cell_size) {
+                    // Adding 0 turns -0 into 0, so both snap to the same cell.
+                    *coordinate = (*coordinate / size).round() * size + T::ZERO;
+                }
+                (location, point)
+            })
+            .collect();
+        snapped.sort_by(|a, b| compare_locations(&a.0, &b.0));
+
+        let mut ret: Vec<Self> = Vec::new();
+        for (location, point) in snapped {
+            match ret.last_mut() {
+                Some(cell) if cell.location == location => cell.points.push(point),
+                _ => ret.push(Quantized {
+                    location,
+                    points: Vec::from([point]),
+                }),
+            }
+        }
+        ret
+    }
+
+    /// Getter for the original points snapped to this cell.
+    pub fn points(&self) -> &[&'point L] {
+        &self.points
+    }
+}
+
+impl<'point, L, T> Locatable<T> for Quantized<'point, L, T>
+where
+    T: Copy,
+{
+    fn get_location(&self) -> [T; 3] {
+        self.location
+    }
+}
+
+impl<'point, L, T> PartialEq for Quantized<'point, L, T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.location == other.location
+    }
+}
+
+impl<'point, L, T> Eq for Quantized<'point, L, T> where T: PartialEq {}
+
+impl<'point, L, T> Hash for Quantized<'point, L, T>
+where
+    T: Scalar,
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        for coordinate in self.location {
+            coordinate.to_bits().hash(state);
+        }
+    }
+}
+
+impl<'point, L, T> Debug for Quantized<'point, L, T>
+where
+    L: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quantized")
+            .field("location", &self.location)
+            .field("points", &self.points)
+            .finish()
+    }
+}
+
+impl<'a, L, T> Sweep<'a, L, T>
+where
+    T: Scalar,
+{
+    /// Half of the size of the box swept by the motion, on every axis.
+    fn half_extent(&self) -> [T; 3] {
+        let mut ret = [T::ZERO; 3];
+        for (i, coordinate) in ret.iter_mut().enumerate() {
+            *coordinate = (self.end[i] - self.start[i]).abs() / T::from_f32(2.0);
+        }
+        ret
+    }
+}
+
+impl<'a, L, T> Locatable<T> for Sweep<'a, L, T>
+where
+    T: Scalar,
+{
+    fn get_location(&self) -> [T; 3] {
+        let mut ret = [T::ZERO; 3];
+        for (i, coordinate) in ret.iter_mut().enumerate() {
+            *coordinate = (self.start[i] + self.end[i]) / T::from_f32(2.0);
+        }
+        ret
+    }
+}
+
+impl<'a, L, T> PartialEq for Sweep<'a, L, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<'a, L, T> Eq for Sweep<'a, L, T> {}
+
+impl<'a, L, T> Hash for Sweep<'a, L, T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+/// Squared distance between two points at their closest moment, both moving linearly over the same time.
+fn closest_approach_sq<L, T>(a: &Sweep<L, T>, b: &Sweep<L, T>) -> T
+where
+    T: Scalar,
+{
+    // Move along with b, so b stands still at the origin and a moves from offset to offset + velocity.
+    let mut offset = [T::ZERO; 3];
+    let mut velocity = [T::ZERO; 3];
+    for i in 0..3 {
+        offset[i] = a.start[i] - b.start[i];
+        velocity[i] = (a.end[i] - a.start[i]) - (b.end[i] - b.start[i]);
+    }
+
+    let speed_sq: T = velocity.iter().map(|v| *v * *v).sum();
+    let time = if speed_sq > T::ZERO {
+        let towards: T = offset.iter().zip(velocity).map(|(o, v)| *o * v).sum();
+        (-towards / speed_sq).max(T::ZERO).min(T::ONE)
+    } else {
+        T::ZERO
+    };
+
+    offset
+        .iter()
+        .zip(velocity)
+        .map(|(o, v)| (*o + time * v) * (*o + time * v))
+        .sum()
+}
+
+/// Squared distance between two locations.
+fn distance_sq<T>(a: &[T; 3], b: &[T; 3]) -> T
+where
+    T: Scalar,
+{
+    a.iter().zip(b).map(|(a, b)| (*a - *b) * (*a - *b)).sum()
+}
+
+impl<T, I> PartialEq for Nearest<T, I>
+where
+    T: Scalar,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, I> Eq for Nearest<T, I> where T: Scalar {}
+
+impl<T, I> PartialOrd for Nearest<T, I>
+where
+    T: Scalar,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, I> Ord for Nearest<T, I>
+where
+    T: Scalar,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_sq.total_cmp(&other.distance_sq)
+    }
+}
+
+impl<'point, L, T> Iterator for IntoIter<'point, L, T> {
+    type Item = &'point L;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.points.is_empty() {
+            let node = self.nodes.pop()?;
+            let TreeNode {
+                children, points, ..
+            } = node;
+            self.points.extend(points);
+            self.nodes.extend(children.into_iter().flatten());
+        }
+        self.remaining -= 1;
+        self.points.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'point, L, T> ExactSizeIterator for IntoIter<'point, L, T> {}
+
+impl<'node, 'point, L, T> Iterator for NearestIter<'node, 'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    type Item = (&'point L, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse(nearest)) = self.queue.pop() {
+            let node = match nearest.item {
+                Candidate::Point(point) => return Some((point, nearest.distance_sq.sqrt())),
+                Candidate::Node(node) => node,
+            };
+            for point in &node.points {
+                self.queue.push(Reverse(Nearest {
+                    distance_sq: distance_sq(
+                        &self.root.project(self.config, point.get_location()),
+                        &self.location,
+                    ),
+                    item: Candidate::Point(*point),
+                }));
+            }
+            if let Some(children) = &node.children {
+                for child in children.iter() {
+                    self.queue.push(Reverse(Nearest {
+                        distance_sq: child
+                            .loose_bounding_box(self.config)
+                            .min_distance_sq(&self.location),
+                        item: Candidate::Node(child),
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T> BoundingBox<T>
+where
+    T: Scalar,
+{
+    /// Construct a new [BoundingBox] which can hold all given points. Points with an infinite or NaN coordinate are
+    /// skipped, as no finite [BoundingBox] can cover them, and no [BoundingBox] covers them anyway.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(f32::INFINITY, f32::NAN, 5.0);
+    /// let bounding_box = BoundingBox::new(vec![point1, point2, point3].iter().collect());
+    ///
+    /// assert_eq!(bounding_box.get_max(), &[10.0, 10.0, 10.0]);
+    /// ```
+    pub fn new<L>(points: Vec<&L>) -> Self
+    where
+        L: Locatable<T>,
+    {
+        let mut ret = BoundingBox::default();
+
+        // Linear search to find the min and max point.
+        for point in points {
+            ret.expand_to_include(&point.get_location());
+        }
+
+        ret
+    }
+
+    /// Construct a new [BoundingBox] from its centre and half of its side length on each axis, e.g. the reach of a
+    /// camera or a collider. A negative half extent is taken as its absolute value, so the [BoundingBox] is never
+    /// inverted.
+    /// # Example
+    /// ```
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::from_centre_half_extents([1.0, 2.0, 3.0], [0.5, 1.0, -2.0]);
+    /// assert_eq!(bounding_box.get_min(), &[0.5, 1.0, 1.0]);
+    /// assert_eq!(bounding_box.get_max(), &[1.5, 3.0, 5.0]);
+    /// assert_eq!(bounding_box.get_centre(), [1.0, 2.0, 3.0]);
+    /// ```
+    pub fn from_centre_half_extents(centre: [T; 3], half_extents: [T; 3]) -> Self {
+        let half_extents = half_extents.map(|half_extent| half_extent.abs());
+        Self {
+            min: [0, 1, 2].map(|i| centre[i] - half_extents[i]),
+            max: [0, 1, 2].map(|i| centre[i] + half_extents[i]),
+        }
+    }
+
+    /// Grow this [BoundingBox] just enough that a point lies within its min and max, so a running extent can be kept
+    /// without scanning all points again. The default [BoundingBox] is inverted, so the first point sets both its min
+    /// and max. Like in [BoundingBox::new], the max is set to the point itself, which thus lies on the uncovered max
+    /// surface, and a point with an infinite or NaN coordinate is skipped.
+    /// # Example
+    /// ```
+    /// use octree::BoundingBox;
+    ///
+    /// let mut bounding_box = BoundingBox::default();
+    /// bounding_box.expand_to_include(&[1.0, 2.0, 3.0]);
+    /// assert_eq!(bounding_box.get_min(), &[1.0, 2.0, 3.0]);
+    /// assert_eq!(bounding_box.get_max(), &[1.0, 2.0, 3.0]);
+    ///
+    /// bounding_box.expand_to_include(&[0.0, 5.0, 3.0]);
+    /// assert_eq!(bounding_box.get_min(), &[0.0, 2.0, 3.0]);
+    /// assert_eq!(bounding_box.get_max(), &[1.0, 5.0, 3.0]);
+    /// ```
+    pub fn expand_to_include(&mut self, point: &[T; 3]) {
+        if !point.iter().all(|coordinate| coordinate.is_finite()) {
+            return;
+        }
+        for (i, coordinate) in point.iter().enumerate() {
+            self.min[i] = self.min[i].min(*coordinate);
+            self.max[i] = self.max[i].max(*coordinate);
+        }
+    }
+
+    /// Construct the smallest [BoundingBox] which contains both [BoundingBox]es, i.e. the smaller min and the larger
+    /// max on every axis. The default, inverted, [BoundingBox] contains nothing, so a union with it changes nothing.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box1 = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let bounding_box2 = BoundingBox::new(vec![Point3D::new(5.0, -5.0, 2.0), Point3D::new(15.0, 5.0, 8.0)].iter().collect());
+    /// let union = bounding_box1.union(&bounding_box2);
+    ///
+    /// assert_eq!(union.get_min(), &[0.0, -5.0, 0.0]);
+    /// assert_eq!(union.get_max(), &[15.0, 10.0, 10.0]);
+    /// ```
+    pub fn union(&self, other: &BoundingBox<T>) -> BoundingBox<T> {
+        BoundingBox {
+            min: [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            max: [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        }
+    }
+
+    /// Check if a point can be covered by this [BoundingBox]. The min surface is covered but the max surface is not, so
+    /// the [BoundingBox]es of [split](BoundingBox::split) cover every location exactly once. See
+    /// [covers_inclusive](BoundingBox::covers_inclusive) to include the max surface. A point with a NaN coordinate is
+    /// never covered.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Locatable};
+    ///
+    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
+    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
+    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
+    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
+    /// let bounding_box = BoundingBox::new(vec![point1, point2].iter().collect());
+    ///
+    /// assert!(bounding_box.covers(&point3.get_location()));
+    /// assert!(!bounding_box.covers(&point4.get_location()));
+    /// ```
+    pub fn covers(&self, point: &[T; 3]) -> bool {
+        self.min[0] <= point[0]
+            && point[0] < self.max[0]
+            && self.min[1] <= point[1]
+            && point[1] < self.max[1]
+            && self.min[2] <= point[2]
+            && point[2] < self.max[2]
+    }
+
+    /// Check if a point lies within this [BoundingBox] including its max surface, e.g. a corner point used to derive
+    /// the extent. The trade-off is ambiguity: a point on the face shared by two neighbouring [BoundingBox]es, such as
+    /// the children of [split](BoundingBox::split), is covered by both, so this must not be used to assign points to
+    /// one of them. [Octree] nodes therefore use [covers](BoundingBox::covers).
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    ///
+    /// assert!(bounding_box.covers_inclusive(&[10.0, 10.0, 10.0]));
+    /// assert!(!bounding_box.covers(&[10.0, 10.0, 10.0]));
+    /// assert!(!bounding_box.covers_inclusive(&[10.0, 10.5, 10.0]));
+    /// ```
+    pub fn covers_inclusive(&self, point: &[T; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// Check if two [BoundingBox]es overlap, i.e. share some space, by comparing their intervals on every axis. This
+    /// includes one [BoundingBox] lying fully inside the other, and two crossing without any corner inside the other.
+    /// As the max surface is not covered, [BoundingBox]es which only touch do not overlap.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let outer = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let inner = BoundingBox::new(vec![Point3D::new(4.0, 4.0, 4.0), Point3D::new(6.0, 6.0, 6.0)].iter().collect());
+    /// let touching = BoundingBox::new(vec![Point3D::new(10.0, 0.0, 0.0), Point3D::new(20.0, 10.0, 10.0)].iter().collect());
+    ///
+    /// assert!(outer.overlaps(&inner));
+    /// assert!(inner.overlaps(&outer));
+    /// assert!(!outer.overlaps(&touching));
+    /// ```
+    pub fn overlaps(&self, other: &BoundingBox<T>) -> bool {
+        (0..3).all(|i| self.min[i] < other.max[i] && other.min[i] < self.max[i])
+    }
+
+    /// Check if another [BoundingBox] lies entirely within this one on all three axes. With the same half open
+    /// convention as [covers](BoundingBox::covers), every location covered by `other` is then covered by this
+    /// [BoundingBox] too, so `other` may share the max surface.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let outer = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let inner = BoundingBox::new(vec![Point3D::new(4.0, 4.0, 4.0), Point3D::new(10.0, 6.0, 6.0)].iter().collect());
+    ///
+    /// assert!(outer.contains_box(&inner));
+    /// assert!(!inner.contains_box(&outer));
+    /// ```
+    pub fn contains_box(&self, other: &BoundingBox<T>) -> bool {
+        (0..3).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    /// Check if two [BoundingBox]es are equal up to rounding, i.e. each of their min and max coordinates differ by at
+    /// most `epsilon`, e.g. to compare regions computed through repeated [splits](BoundingBox::split). Unlike `==`,
+    /// which stays exact, a NaN coordinate is never approximately equal to anything.
+    /// # Example
+    /// ```
+    /// use octree::{BoundingBox, Octant};
+    ///
+    /// let bounding_box: BoundingBox = BoundingBox::from_centre_half_extents([0.1, 0.2, 0.3], [0.7, 0.7, 0.7]);
+    /// let child = bounding_box.child(Octant::Ubr).child(Octant::Dfl);
+    /// let expected = BoundingBox::from_centre_half_extents([0.275, 0.375, 0.475], [0.175, 0.175, 0.175]);
+    ///
+    /// assert!(child.approx_eq(&expected, 1e-6));
+    /// assert!(!child.approx_eq(&bounding_box, 1e-6));
+    /// ```
+    pub fn approx_eq(&self, other: &BoundingBox<T>, epsilon: T) -> bool {
+        (0..3).all(|i| {
+            (self.min[i] - other.min[i]).abs() <= epsilon
+                && (self.max[i] - other.max[i]).abs() <= epsilon
+        })
+    }
+
+    /// Find the region shared by two [BoundingBox]es, i.e. the larger min and the smaller max on every axis, or `None`
+    /// if they do not [overlap](BoundingBox::overlaps). As the max surface is not covered, [BoundingBox]es which only
+    /// touch do not intersect either.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box1 = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)].iter().collect());
+    /// let bounding_box2 = BoundingBox::new(vec![Point3D::new(5.0, -5.0, 2.0), Point3D::new(15.0, 5.0, 8.0)].iter().collect());
+    /// let intersection = bounding_box1.intersection(&bounding_box2).unwrap();
+    ///
+    /// assert_eq!(intersection.get_min(), &[5.0, 0.0, 2.0]);
+    /// assert_eq!(intersection.get_max(), &[10.0, 5.0, 8.0]);
+    /// ```
+    pub fn intersection(&self, other: &BoundingBox<T>) -> Option<BoundingBox<T>> {
+        let ret = BoundingBox {
+            min: [0, 1, 2].map(|i| self.min[i].max(other.min[i])),
+            max: [0, 1, 2].map(|i| self.max[i].min(other.max[i])),
+        };
+        (0..3).all(|i| ret.min[i] < ret.max[i]).then_some(ret)
+    }
+
+    /// Volume of this [BoundingBox], i.e. the product of its side lengths. It is 0 if the [BoundingBox] is flat on
+    /// some axis, or inverted like the default one, whose min is larger than its max.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 2.0, 3.0)].iter().collect());
+    /// assert_eq!(bounding_box.volume(), 6.0);
+    /// assert_eq!(BoundingBox::<f32>::default().volume(), 0.0);
+    /// ```
+    pub fn volume(&self) -> T {
+        self.sides()
+            .into_iter()
+            .fold(T::ONE, |ret, side| ret * side)
+    }
+
+    /// Surface area of this [BoundingBox], i.e. the sum of the areas of its 6 faces. Like
+    /// [volume](BoundingBox::volume), it is 0 if the [BoundingBox] is flat on some axis, or inverted.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 2.0, 3.0)].iter().collect());
+    /// assert_eq!(bounding_box.surface_area(), 22.0);
+    /// ```
+    pub fn surface_area(&self) -> T {
+        let [x, y, z] = self.sides();
+        if x == T::ZERO || y == T::ZERO || z == T::ZERO {
+            return T::ZERO;
+        }
+        T::from_f32(2.0) * (x * y + y * z + z * x)
+    }
+
+    /// Squared distance from a location to the closest point of this [BoundingBox], 0 if it is inside. No node of a
+    /// sub tree within this [BoundingBox] can hold a point closer than that, which makes it the bound to prune nearest
+    /// neighbour and radius searches with.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    /// assert_eq!(bounding_box.min_distance_sq(&[0.5, 0.5, 0.5]), 0.0);
+    /// assert_eq!(bounding_box.min_distance_sq(&[3.0, 0.5, -1.0]), 5.0);
+    /// ```
+    pub fn min_distance_sq(&self, location: &[T; 3]) -> T {
+        distance_sq(&self.closest_point(location), location)
+    }
+
+    /// Check if a sphere reaches this [BoundingBox], i.e. some point of it lies within `radius` of `centre`, including
+    /// a sphere which only touches its surface. A negative or NaN radius reaches nothing.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    /// assert!(bounding_box.intersects_sphere(&[2.0, 0.5, 0.5], 1.0));
+    /// assert!(!bounding_box.intersects_sphere(&[2.0, 2.0, 0.5], 1.0));
+    /// ```
+    pub fn intersects_sphere(&self, centre: &[T; 3], radius: T) -> bool {
+        radius >= T::ZERO && self.min_distance_sq(centre) <= radius * radius
+    }
+
+    /// The point of this [BoundingBox], on its surface or inside, closest to a location, i.e. the location clamped
+    /// into `[min, max]` on each axis. A location inside is returned as it is.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    /// assert_eq!(bounding_box.closest_point(&[0.5, 0.5, 0.5]), [0.5, 0.5, 0.5]);
+    /// assert_eq!(bounding_box.closest_point(&[3.0, 0.5, -1.0]), [1.0, 0.5, 0.0]);
+    /// ```
+    pub fn closest_point(&self, location: &[T; 3]) -> [T; 3] {
+        [0, 1, 2].map(|i| location[i].max(self.min[i]).min(self.max[i]))
+    }
+
+    /// Squared distance from a location to the farthest corner of this [BoundingBox]. Every point within it is at
+    /// most that far away, so a sub tree within a [BoundingBox] whose max distance is under a search radius can be
+    /// taken as a whole.
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::BoundingBox;
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)].iter().collect());
+    /// assert_eq!(bounding_box.max_distance_sq(&[0.5, 0.5, 0.5]), 0.75);
+    /// assert_eq!(bounding_box.max_distance_sq(&[3.0, 0.5, -1.0]), 13.25);
+    /// ```
+    pub fn max_distance_sq(&self, location: &[T; 3]) -> T {
+        let mut ret = T::ZERO;
+        for (i, coordinate) in location.iter().enumerate() {
+            let gap = (*coordinate - self.min[i]).max(self.max[i] - *coordinate);
+            ret += gap * gap;
+        }
+        ret
+    }
+
+    /// Getter for [BoundingBox] centre coordination.
+    pub fn get_centre(&self) -> [T; 3] {
+        let mut ret = [T::ZERO; 3];
+        for (i, coordinate) in ret.iter_mut().enumerate() {
+            *coordinate = (self.min[i] + self.max[i]) / T::from_f32(2.0);
+        }
+        ret
+    }
+
+    /// Getter for [BoundingBox] min corner coordination.
+    pub fn get_min(&self) -> &[T; 3] {
+        &self.min
+    }
+
+    /// Getter for [BoundingBox] max corner coordination.
+    pub fn get_max(&self) -> &[T; 3] {
+        &self.max
+    }
+
+    /// The sub [BoundingBox] of one [Octant], the same as the one at its [index](Octant::index) in
+    /// [split](BoundingBox::split).
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{BoundingBox, Octant};
+    ///
+    /// let bounding_box = BoundingBox::new(vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(2.0, 2.0, 2.0)].iter().collect());
+    /// let child = bounding_box.child(Octant::Dfr);
+    /// assert_eq!(child.get_min(), &[1.0, 0.0, 0.0]);
+    /// assert_eq!(child.get_max(), &[2.0, 1.0, 1.0]);
+    /// ```
+    pub fn child(&self, octant: Octant) -> Self {
+        let centre = self.get_centre();
+        // Bit i of the index tells which half along axis i.
+        let upper = |i: usize| octant.index() & (1 << i) != 0;
+        Self {
+            min: [0, 1, 2].map(|i| if upper(i) { centre[i] } else { self.min[i] }),
+            max: [0, 1, 2].map(|i| if upper(i) { self.max[i] } else { centre[i] }),
+        }
+    }
+
+    /// Split the [BoundingBox] into 8 sub [BoundingBox]es, in the order of [Octant::index], see
+    /// [child](BoundingBox::child).
+    pub fn split(&self) -> [Self; 8] {
+        Octant::ALL.map(|octant| self.child(octant))
+    }
+
+    /// Classify this [BoundingBox] against a plane `[a, b, c, d]`. Only the two corners with the smallest and the
+    /// largest signed distance along the plane normal need to be checked.
+    fn plane_side(&self, plane: &[T; 4]) -> PlaneSide {
+        let mut nearest = plane[3];
+        let mut farthest = plane[3];
+        for (i, normal) in plane.iter().take(3).enumerate() {
+            if *normal >= T::ZERO {
+                nearest += *normal * self.min[i];
+                farthest += *normal * self.max[i];
+            } else {
+                nearest += *normal * self.max[i];
+                farthest += *normal * self.min[i];
+            }
+        }
+
+        if nearest > T::ZERO {
+            PlaneSide::Front
+        } else if farthest < T::ZERO {
+            PlaneSide::Back
+        } else {
+            PlaneSide::Crossing
+        }
+    }
+
+    /// Construct a new [BoundingBox] which covers all given points, including the ones on the max surface.
+    fn covering<L>(points: Vec<&L>) -> Self
+    where
+        L: Locatable<T>,
+    {
+        let mut ret = Self::new(points);
+        for coordinate in ret.max.iter_mut() {
+            *coordinate = coordinate.next_up();
+        }
+        ret
+    }
+
+    /// Check if this [BoundingBox] lies fully behind a plane `[a, b, c, d]`, i.e. even its corner furthest along the
+    /// normal `[a, b, c]` has `a * x + b * y + c * z + d < 0`.
+    fn behind_plane(&self, plane: &[T; 4]) -> bool {
+        let mut ret = plane[3];
+        for (i, normal) in plane[..3].iter().enumerate() {
+            let corner = if *normal > T::ZERO {
+                self.max[i]
+            } else {
+                self.min[i]
+            };
+            ret += *normal * corner;
+        }
+        ret < T::ZERO
+    }
+
+    /// Check with the slab method if a ray from `origin` along the unit vector `direction` passes through this
+    /// [BoundingBox], including its max surface, before `max_distance`.
+    fn hit_by_ray(&self, origin: &[T; 3], direction: &[T; 3], max_distance: T) -> bool {
+        let mut near = T::ZERO;
+        let mut far = max_distance;
+        for i in 0..3 {
+            if direction[i] == T::ZERO {
+                // Parallel to the slab, so the ray stays either within or outside of it.
+                if origin[i] < self.min[i] || self.max[i] < origin[i] {
+                    return false;
+                }
+            } else {
+                let a = (self.min[i] - origin[i]) / direction[i];
+                let b = (self.max[i] - origin[i]) / direction[i];
+                near = near.max(a.min(b));
+                far = far.min(a.max(b));
+            }
+        }
+        near <= far
+    }
+
+    /// Side lengths of this [BoundingBox], 0 on an axis where it is inverted.
+    fn sides(&self) -> [T; 3] {
+        [0, 1, 2].map(|i| (self.max[i] - self.min[i]).max(T::ZERO))
+    }
+}
+
+impl<T> Default for BoundingBox<T>
+where
+    T: Scalar,
+{
+    /// Construct a default [BoundingBox], inverted so it covers nothing: its min is the largest value `T` can represent
+    /// and its max the smallest, so growing it to a point with `min` and `max` gives the point itself.
+    fn default() -> Self {
+        Self {
+            // Will be used in min() and max() function later so the initial value of min is T::MAX.
+            min: [T::MAX; 3],
+            max: [T::MIN; 3],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::point::{ApproxPoint3D, Point3D, Point3D64};
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    /// Should construct bounding box with the specified min and max corner.
+    fn test_bounding_box_construction() {
+        let point1 = Point3D::new(10.0, 0.0, 0.0);
+        let point2 = Point3D::new(0.0, -1.0, 0.0);
+        let point3 = Point3D::new(0.0, 0.0, 5.0);
+        let bounding_box = BoundingBox::new([point1, point2, point3].iter().collect());
+
+        assert_eq!(bounding_box.get_min(), &[0.0, -1.0, 0.0]);
+        assert_eq!(bounding_box.get_max(), &[10.0, 0.0, 5.0]);
+    }
+
+    #[test]
+    /// Should bound the distance from a location to any point within the bounding box from both sides.
+    fn test_bounding_box_distance_sq() {
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(-1.0, 0.0, 2.0), Point3D::new(1.0, 4.0, 3.0)]
+                .iter()
+                .collect(),
+        );
+
+        assert_eq!(bounding_box.min_distance_sq(&[0.0, 1.0, 2.5]), 0.0);
+        assert_eq!(
+            bounding_box.max_distance_sq(&[0.0, 1.0, 2.5]),
+            1.0 + 9.0 + 0.25
+        );
+        assert_eq!(bounding_box.min_distance_sq(&[-1.0, 4.0, 3.0]), 0.0);
+        assert_eq!(
+            bounding_box.min_distance_sq(&[3.0, -2.0, 0.0]),
+            4.0 + 4.0 + 4.0
+        );
+        assert_eq!(
+            bounding_box.max_distance_sq(&[3.0, -2.0, 0.0]),
+            16.0 + 36.0 + 9.0
+        );
+
+        let numbers = random_numbers(551, 300);
+        for location in numbers.chunks_exact(3) {
+            let location = [0, 1, 2].map(|i| location[i] * 10.0 - 5.0);
+            let min = bounding_box.min_distance_sq(&location);
+            let max = bounding_box.max_distance_sq(&location);
+            assert!(min <= max);
+            for corner in 0..8 {
+                let corner = [0, 1, 2].map(|i| {
+                    if corner & (1 << i) == 0 {
+                        bounding_box.get_min()[i]
+                    } else {
+                        bounding_box.get_max()[i]
+                    }
+                });
+                let distance = distance_sq(&corner, &location);
+                assert!(min <= distance && distance <= max);
+            }
+        }
+    }
+
+    #[test]
+    /// Should clamp a location onto the bounding box, keeping it if it is inside or on a face.
+    fn test_bounding_box_closest_point() {
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(-1.0, 0.0, 2.0), Point3D::new(1.0, 4.0, 3.0)]
+                .iter()
+                .collect(),
+        );
+
+        // Inside.
+        assert_eq!(
+            bounding_box.closest_point(&[0.0, 1.0, 2.5]),
+            [0.0, 1.0, 2.5]
+        );
+        // On a face, an edge and a corner.
+        assert_eq!(
+            bounding_box.closest_point(&[1.0, 1.0, 2.5]),
+            [1.0, 1.0, 2.5]
+        );
+        assert_eq!(
+            bounding_box.closest_point(&[1.0, 4.0, 2.5]),
+            [1.0, 4.0, 2.5]
+        );
+        assert_eq!(
+            bounding_box.closest_point(&[-1.0, 0.0, 2.0]),
+            [-1.0, 0.0, 2.0]
+        );
+        // Outside, beyond a face, an edge and a corner.
+        assert_eq!(
+            bounding_box.closest_point(&[0.0, -5.0, 2.5]),
+            [0.0, 0.0, 2.5]
+        );
+        assert_eq!(
+            bounding_box.closest_point(&[5.0, 1.0, 9.0]),
+            [1.0, 1.0, 3.0]
+        );
+        assert_eq!(
+            bounding_box.closest_point(&[-5.0, 9.0, 0.0]),
+            [-1.0, 4.0, 2.0]
+        );
+
+        let numbers = random_numbers(555, 300);
+        for location in numbers.chunks_exact(3) {
+            let location = [0, 1, 2].map(|i| location[i] * 10.0 - 5.0);
+            let closest = bounding_box.closest_point(&location);
+            assert!(bounding_box.covers_inclusive(&closest));
+            assert_eq!(
+                distance_sq(&closest, &location),
+                bounding_box.min_distance_sq(&location)
+            );
+        }
+    }
+
+    #[test]
+    /// Should construct a bounding box around its centre, taking negative half extents as their absolute value.
+    fn test_bounding_box_from_centre_half_extents() {
+        let bounding_box = BoundingBox::from_centre_half_extents([0.0, -1.0, 2.0], [1.0, 2.0, 0.5]);
+        assert_eq!(bounding_box.get_min(), &[-1.0, -3.0, 1.5]);
+        assert_eq!(bounding_box.get_max(), &[1.0, 1.0, 2.5]);
+        assert_eq!(bounding_box.get_centre(), [0.0, -1.0, 2.0]);
+
+        assert_eq!(
+            BoundingBox::from_centre_half_extents([0.0, -1.0, 2.0], [-1.0, 2.0, -0.5]),
+            bounding_box
+        );
+
+        // A zero half extent gives a flat bounding box.
+        let flat = BoundingBox::from_centre_half_extents([0.0, 0.0, 0.0], [1.0, 0.0, 1.0]);
+        assert_eq!(flat.get_min(), &[-1.0, 0.0, -1.0]);
+        assert_eq!(flat.volume(), 0.0);
+
+        let bounding_box: BoundingBox<f64> =
+            BoundingBox::from_centre_half_extents([1.0, 1.0, 1.0], [0.25, 0.25, 0.25]);
+        assert_eq!(bounding_box.get_max(), &[1.25, 1.25, 1.25]);
+    }
+
+    #[test]
+    /// Should compare bounding boxes coordinate by coordinate within a tolerance.
+    fn test_bounding_box_approx_eq() {
+        let bounding_box = BoundingBox {
+            min: [0.1, 0.2, 0.3],
+            max: [1.1, 1.7, 2.3],
+        };
+        assert!(bounding_box.approx_eq(&bounding_box, 0.0));
+
+        // Splitting down to a leaf accumulates rounding of the centres.
+        let mut leaf = bounding_box.clone();
+        for _ in 0..10 {
+            leaf = leaf.child(Octant::Ubr);
+        }
+        let size = 1.0 / 1024.0;
+        let expected = BoundingBox {
+            min: [1.1 - size, 1.7 - 1.5 * size, 2.3 - 2.0 * size],
+            max: [1.1, 1.7, 2.3],
+        };
+        assert!(leaf.approx_eq(&expected, 1e-5));
+        assert!(expected.approx_eq(&leaf, 1e-5));
+        assert!(!leaf.approx_eq(&expected, -1.0));
+
+        // Every one of the six coordinates counts.
+        for i in 0..6 {
+            let mut other = bounding_box.clone();
+            if i < 3 {
+                other.min[i] += 0.01;
+            } else {
+                other.max[i - 3] -= 0.01;
+            }
+            assert!(bounding_box.approx_eq(&other, 0.011));
+            assert!(!bounding_box.approx_eq(&other, 0.009));
+        }
+
+        let mut nan = bounding_box.clone();
+        nan.max[1] = f32::NAN;
+        assert!(!nan.approx_eq(&nan, f32::MAX));
+    }
+
+    #[test]
+    /// Should tell if a sphere reaches the bounding box, whether it lies inside, crosses a face or touches a corner.
+    fn test_bounding_box_intersects_sphere() {
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(4.0, 4.0, 4.0)]
+                .iter()
+                .collect(),
+        );
+
+        // Fully inside, and around the whole bounding box.
+        assert!(bounding_box.intersects_sphere(&[2.0, 2.0, 2.0], 1.0));
+        assert!(bounding_box.intersects_sphere(&[2.0, 2.0, 2.0], 100.0));
+        // Partially overlapping a face, from outside and from inside.
+        assert!(bounding_box.intersects_sphere(&[5.0, 2.0, 2.0], 1.5));
+        assert!(bounding_box.intersects_sphere(&[3.5, 2.0, 2.0], 1.0));
+        // Touching a face, an edge and a corner.
+        assert!(bounding_box.intersects_sphere(&[2.0, -1.0, 2.0], 1.0));
+        assert!(bounding_box.intersects_sphere(&[-3.0, -4.0, 2.0], 5.0));
+        assert!(bounding_box.intersects_sphere(&[6.0, 7.0, 10.0], 7.0));
+        assert!(!bounding_box.intersects_sphere(&[6.0, 7.0, 10.0], 6.9));
+        // Fully outside.
+        assert!(!bounding_box.intersects_sphere(&[10.0, 2.0, 2.0], 5.0));
+        assert!(!bounding_box.intersects_sphere(&[-1.0, -1.0, -1.0], 1.7));
+        // A radius of 0 only reaches the bounding box from within, a negative or NaN radius never does.
+        assert!(bounding_box.intersects_sphere(&[4.0, 0.0, 2.0], 0.0));
+        assert!(!bounding_box.intersects_sphere(&[2.0, 2.0, 2.0], -1.0));
+        assert!(!bounding_box.intersects_sphere(&[2.0, 2.0, 2.0], f32::NAN));
+    }
+
+    #[test]
+    /// Should name the octants in the order of split, and find the octant whose box covers a location.
+    fn test_bounding_box_octant() {
+        let bounding_box = BoundingBox {
+            min: [-2.0, 0.0, 4.0],
+            max: [2.0, 8.0, 5.0],
+        };
+        let splitted = bounding_box.split();
+        for (index, octant) in Octant::ALL.into_iter().enumerate() {
+            assert_eq!(octant.index(), index);
+            assert_eq!(Octant::from_index(index), Some(octant));
+            assert_eq!(bounding_box.child(octant), splitted[index]);
+        }
+        assert_eq!(Octant::from_index(8), None);
+        assert_eq!(bounding_box.child(Octant::Ubl).min, [-2.0, 4.0, 4.5]);
+        assert_eq!(bounding_box.child(Octant::Ubl).max, [0.0, 8.0, 5.0]);
+
+        for location in random_numbers(567, 3 * 200).chunks(3) {
+            let location = [
+                location[0] * 4.0 - 2.0,
+                location[1] * 8.0,
+                location[2] + 4.0,
+            ];
+            let octant = Octant::for_point(&bounding_box, &location);
+            assert!(bounding_box.child(octant).covers(&location));
+        }
+        // The centre and the min corner.
+        assert_eq!(
+            Octant::for_point(&bounding_box, &[0.0, 4.0, 4.5]),
+            Octant::Ubr
+        );
+        assert_eq!(
+            Octant::for_point(&bounding_box, &[-2.0, 0.0, 4.0]),
+            Octant::Dfl
+        );
+    }
+
+    #[test]
+    /// Should pick the same child for a point by its octant as by trying every child, also right at the split planes.
+    fn test_tree_node_child_index() {
+        fn check(config: &OctreeConfig, node: &TreeNode<Point3D>, points: &[Point3D]) -> usize {
+            let Some(children) = &node.children else {
+                return 0;
+            };
+            let mut checked = 0;
+            for point in points.iter().filter(|point| node.covers(config, point)) {
+                let position = children
+                    .iter()
+                    .position(|child| child.covers(config, point));
+                assert_eq!(Some(node.child_index(config, point)), position);
+                checked += 1;
+            }
+            checked
+                + children
+                    .iter()
+                    .map(|child| check(config, child, points))
+                    .sum::<usize>()
+        }
+
+        // Grid lines at the centres of the first splits, and right next to them.
+        let mut coordinates = vec![0.0f32, 1.25, 2.5, 3.0, 5.0, 7.5, 10.0];
+        for coordinate in coordinates.clone() {
+            coordinates.push(coordinate.next_up());
+            coordinates.push(coordinate.next_down());
+        }
+        let mut points = Vec::new();
+        for &x in &coordinates {
+            for &y in &coordinates {
+                for &z in &coordinates {
+                    points.push(Point3D::new(x, y, z));
+                }
+            }
+        }
+        for builder in [
+            OctreeBuilder::new().capacity(4),
+            OctreeBuilder::new().capacity(4).split_ratio(0.3),
+            OctreeBuilder::new().capacity(4).project_axis(Some(1)),
+        ] {
+            let octree = builder.build(points.iter().collect());
+            assert!(octree.root.splitted);
+            assert!(check(&octree.config, &octree.root, &points) > 0);
+            assert!(octree.check_invariants().is_ok());
+            assert!(points.iter().all(|point| octree.contains(point)));
+        }
+    }
+
+    #[test]
+    /// Should identify if a point is covered by this area.
+    /// Note that the bounding box covers min surface but does not cover max surface.
+    fn test_bounding_box_covers() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let bounding_box = BoundingBox::new([point1.clone(), point2.clone()].iter().collect());
+        let point3 = Point3D::new(5.0, 5.0, 5.0);
+        let point4 = Point3D::new(10.0, 11.0, 9.0);
+
+        assert!(bounding_box.covers(&point1.get_location()));
+        assert!(!bounding_box.covers(&point2.get_location()));
+        assert!(bounding_box.covers(&point3.get_location()));
+        assert!(!bounding_box.covers(&point4.get_location()));
+    }
+
+    #[test]
+    /// Should identify if two bounding boxes overlaps / intersects.
+    fn test_bounding_box_overlaps() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let bounding_box1 = BoundingBox::new([point1, point2].iter().collect());
+
+        let point3 = Point3D::new(1.0, 1.0, 1.0);
+        let point4 = Point3D::new(11.0, 11.0, 11.0);
+        let bounding_box2 = BoundingBox::new([point3, point4].iter().collect());
+
+        let point5 = Point3D::new(1.0, 1.0, 1.0);
+        let point6 = Point3D::new(9.0, 9.0, 9.0);
+        let bounding_box3 = BoundingBox::new([point5, point6].iter().collect());
+
+        let point7 = Point3D::new(11.0, 0.0, 0.0);
+        let point8 = Point3D::new(20.0, 20.0, 20.0);
+        let bounding_box4 = BoundingBox::new([point7, point8].iter().collect());
+
+        assert!(bounding_box1.overlaps(&bounding_box2));
+        assert!(bounding_box1.overlaps(&bounding_box3));
+        assert!(!bounding_box1.overlaps(&bounding_box4));
+    }
+
+    #[test]
+    /// Should find the overlap of a bounding box lying fully inside another one, from either side.
+    fn test_bounding_box_overlaps_containment() {
+        let outer = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)]
+                .iter()
+                .collect(),
+        );
+        let inner = BoundingBox::new(
+            [Point3D::new(4.0, 4.0, 4.0), Point3D::new(6.0, 6.0, 6.0)]
+                .iter()
+                .collect(),
+        );
+
+        assert!(outer.overlaps(&inner));
+        assert!(inner.overlaps(&outer));
+        assert!(outer.overlaps(&outer));
+    }
+
+    #[test]
+    /// Should tell if a bounding box is fully contained, only partially overlapped, or disjoint.
+    fn test_bounding_box_contains_box() {
+        let outer = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)]
+                .iter()
+                .collect(),
+        );
+        let inner = BoundingBox::new(
+            [Point3D::new(0.0, 4.0, 4.0), Point3D::new(6.0, 10.0, 6.0)]
+                .iter()
+                .collect(),
+        );
+        let partial = BoundingBox::new(
+            [Point3D::new(4.0, 4.0, 4.0), Point3D::new(11.0, 6.0, 6.0)]
+                .iter()
+                .collect(),
+        );
+        let disjoint = BoundingBox::new(
+            [Point3D::new(20.0, 0.0, 0.0), Point3D::new(30.0, 10.0, 10.0)]
+                .iter()
+                .collect(),
+        );
+
+        assert!(outer.contains_box(&inner));
+        assert!(outer.contains_box(&outer));
+        assert!(!inner.contains_box(&outer));
+        assert!(outer.overlaps(&partial));
+        assert!(!outer.contains_box(&partial));
+        assert!(!outer.contains_box(&disjoint));
+        assert!(!disjoint.contains_box(&outer));
+    }
+
+    #[test]
+    /// Should find the shared region of overlapping bounding boxes, and none for touching or disjoint ones.
+    fn test_bounding_box_intersection() {
+        let points = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        let bounding_box = BoundingBox::new(points.iter().collect());
+
+        let points = [Point3D::new(-5.0, 2.0, 4.0), Point3D::new(5.0, 8.0, 20.0)];
+        let overlapping = BoundingBox::new(points.iter().collect());
+        let expected = BoundingBox {
+            min: [0.0, 2.0, 4.0],
+            max: [5.0, 8.0, 10.0],
+        };
+        assert_eq!(
+            bounding_box.intersection(&overlapping),
+            Some(expected.clone())
+        );
+        assert_eq!(overlapping.intersection(&bounding_box), Some(expected));
+
+        let points = [Point3D::new(2.0, 2.0, 2.0), Point3D::new(4.0, 4.0, 4.0)];
+        let inner = BoundingBox::new(points.iter().collect());
+        assert_eq!(bounding_box.intersection(&inner), Some(inner.clone()));
+
+        let points = [Point3D::new(10.0, 0.0, 0.0), Point3D::new(20.0, 10.0, 10.0)];
+        let touching = BoundingBox::new(points.iter().collect());
+        assert_eq!(bounding_box.intersection(&touching), None);
+        assert_eq!(touching.intersection(&bounding_box), None);
+
+        let points = [Point3D::new(0.0, 0.0, 11.0), Point3D::new(10.0, 10.0, 20.0)];
+        let disjoint = BoundingBox::new(points.iter().collect());
+        assert_eq!(bounding_box.intersection(&disjoint), None);
+        assert_eq!(bounding_box.intersection(&BoundingBox::default()), None);
+    }
+
+    #[test]
+    /// Should grow a bounding box to include points, starting from the default one, and match the one built at once.
+    fn test_bounding_box_expand_to_include() {
+        let points = [
+            Point3D::new(3.0, -1.0, 2.0),
+            Point3D::new(0.0, 4.0, 2.0),
+            Point3D::new(f32::INFINITY, 0.0, 0.0),
+            Point3D::new(1.0, 1.0, f32::NAN),
+            Point3D::new(2.0, 0.0, 7.0),
+        ];
+        let mut bounding_box = BoundingBox::default();
+        bounding_box.expand_to_include(&points[0].get_location());
+        assert_eq!(bounding_box.get_min(), &[3.0, -1.0, 2.0]);
+        assert_eq!(bounding_box.get_max(), &[3.0, -1.0, 2.0]);
+
+        for point in &points[1..] {
+            bounding_box.expand_to_include(&point.get_location());
+        }
+        assert_eq!(bounding_box.get_min(), &[0.0, -1.0, 2.0]);
+        assert_eq!(bounding_box.get_max(), &[3.0, 4.0, 7.0]);
+        assert_eq!(bounding_box, BoundingBox::new(points.iter().collect()));
+    }
+
+    #[test]
+    /// Should find the smallest bounding box containing both, with the default one as the identity.
+    fn test_bounding_box_union() {
+        let points = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        let bounding_box1 = BoundingBox::new(points.iter().collect());
+        let points = [Point3D::new(20.0, 2.0, -4.0), Point3D::new(30.0, 8.0, 5.0)];
+        let bounding_box2 = BoundingBox::new(points.iter().collect());
+        let expected = BoundingBox {
+            min: [0.0, 0.0, -4.0],
+            max: [30.0, 10.0, 10.0],
+        };
+
+        assert_eq!(bounding_box1.union(&bounding_box2), expected);
+        assert_eq!(bounding_box2.union(&bounding_box1), expected);
+        assert!(expected.contains_box(&bounding_box1));
+        assert!(expected.contains_box(&bounding_box2));
+        assert_eq!(bounding_box1.union(&BoundingBox::default()), bounding_box1);
+        assert_eq!(BoundingBox::default().union(&bounding_box2), bounding_box2);
+    }
+
+    #[test]
+    /// Should compute the volume and surface area of a bounding box, and 0 for flat or inverted ones.
+    fn test_bounding_box_volume_surface_area() {
+        let points = [Point3D::new(-1.0, 0.0, 2.0), Point3D::new(1.0, 3.0, 6.0)];
+        let bounding_box = BoundingBox::new(points.iter().collect());
+        assert_eq!(bounding_box.volume(), 24.0);
+        assert_eq!(bounding_box.surface_area(), 52.0);
+
+        let points = [Point3D::new(0.0, 0.0, 5.0), Point3D::new(1.0, 3.0, 5.0)];
+        let flat = BoundingBox::new(points.iter().collect());
+        assert_eq!(flat.volume(), 0.0);
+        assert_eq!(flat.surface_area(), 0.0);
+
+        let inverted: BoundingBox = BoundingBox::default();
+        assert_eq!(inverted.volume(), 0.0);
+        assert_eq!(inverted.surface_area(), 0.0);
+
+        let bounding_box: BoundingBox<f64> = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [2.0, -1.0, 2.0],
+        };
+        assert_eq!(bounding_box.volume(), 0.0);
+        assert_eq!(bounding_box.surface_area(), 0.0);
+    }
+
+    #[test]
+    /// Should find the overlap of two bounding boxes crossing like a plus sign, with no corner inside the other, and
+    /// tell apart boxes which only touch or are separated along a single axis.
+    fn test_bounding_box_overlaps_cross() {
+        let wide = BoundingBox::new(
+            [Point3D::new(0.0, 4.0, 4.0), Point3D::new(10.0, 6.0, 6.0)]
+                .iter()
+                .collect(),
+        );
+        let tall = BoundingBox::new(
+            [Point3D::new(4.0, 0.0, 0.0), Point3D::new(6.0, 10.0, 10.0)]
+                .iter()
+                .collect(),
+        );
+        let touching = BoundingBox::new(
+            [Point3D::new(10.0, 4.0, 4.0), Point3D::new(20.0, 6.0, 6.0)]
+                .iter()
+                .collect(),
+        );
+        let above = BoundingBox::new(
+            [Point3D::new(4.0, 0.0, 11.0), Point3D::new(6.0, 10.0, 20.0)]
+                .iter()
+                .collect(),
+        );
+
+        assert!(wide.overlaps(&tall));
+        assert!(tall.overlaps(&wide));
+        assert!(!wide.overlaps(&touching));
+        assert!(!touching.overlaps(&wide));
+        assert!(!tall.overlaps(&above));
+    }
+
+    #[test]
+    /// Should cover the max surface only when inclusive, where neighbouring bounding boxes then share their faces.
+    fn test_bounding_box_covers_inclusive() {
+        let points = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        let octree = Octree::new(points.iter().collect());
+        let root = &octree.root.bounding_box;
+        let max = *root.get_max();
+
+        assert!(!root.covers(&max));
+        assert!(root.covers_inclusive(&max));
+        assert!(root.covers_inclusive(root.get_min()));
+        assert!(root.covers_inclusive(&[5.0, 10.0, 0.0]));
+        assert!(!root.covers_inclusive(&[5.0, 10.0, -0.5]));
+        assert!(!root.covers_inclusive(&[f32::NAN, 5.0, 5.0]));
+
+        let centre = root.get_centre();
+        let children = root.split();
+        assert_eq!(
+            children
+                .iter()
+                .filter(|child| child.covers(&centre))
+                .count(),
+            1
+        );
+        assert_eq!(
+            children
+                .iter()
+                .filter(|child| child.covers_inclusive(&centre))
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    /// Should be symmetric, and agree with sharing a covered location, for random pairs of bounding boxes.
+    fn test_bounding_box_overlaps_symmetric() {
+        // Corners on a coarse grid, so boxes often touch, nest or share faces.
+        let numbers = random_numbers(538, 12 * 500);
+        let boxes: Vec<BoundingBox> = numbers
+            .chunks(6)
+            .map(|c| {
+                let min = [c[0], c[1], c[2]].map(|x| (x * 4.0).floor());
+                let size = [c[3], c[4], c[5]].map(|x| (x * 3.0).floor() + 1.0);
+                BoundingBox {
+                    min,
+                    max: [0, 1, 2].map(|i| min[i] + size[i]),
+                }
+            })
+            .collect();
+
+        for pair in boxes.chunks(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert_eq!(a.overlaps(b), b.overlaps(a), "{a:?} {b:?}");
+
+            // Boxes on the integer grid share space if they share the centre of a unit cell.
+            let mut shared = false;
+            for x in 0..6 {
+                for y in 0..6 {
+                    for z in 0..6 {
+                        let location = [x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5];
+                        shared |= a.covers(&location) && b.covers(&location);
+                    }
+                }
+            }
+            assert_eq!(a.overlaps(b), shared, "{a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    /// Should correctly calculate bounding box centre coordination.
+    fn test_bounding_box_centre() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let bounding_box = BoundingBox::new([point1, point2].iter().collect());
+
+        assert_eq!(bounding_box.get_centre(), [5.0; 3]);
+    }
+
+    #[test]
+    /// Should split the current bounding box into 8 smaller bounding boxes.
+    fn test_bounding_box_split() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let bounding_box = BoundingBox::new([point1, point2].iter().collect());
+
+        let splitted = bounding_box.split();
+
+        // u: up, d: down, f: front, b: back, l: left, r: right.
+        // example, ulb is the left back corner in the upper layer.
+        // order: dfl, dfr, dbl, dbr, ufl, ufr, ubl, ubr.
+
+        // dfl
+        assert_eq!(splitted[0].min, [0.0, 0.0, 0.0]);
+        assert_eq!(splitted[0].max, [5.0, 5.0, 5.0]);
+
+        // dfr
+        assert_eq!(splitted[1].min, [5.0, 0.0, 0.0]);
+        assert_eq!(splitted[1].max, [10.0, 5.0, 5.0]);
+
+        // dbl
+        assert_eq!(splitted[2].min, [0.0, 5.0, 0.0]);
+        assert_eq!(splitted[2].max, [5.0, 10.0, 5.0]);
+
+        // dbr
+        assert_eq!(splitted[3].min, [5.0, 5.0, 0.0]);
+        assert_eq!(splitted[3].max, [10.0, 10.0, 5.0]);
+
+        // ufl
+        assert_eq!(splitted[4].min, [0.0, 0.0, 5.0]);
+        assert_eq!(splitted[4].max, [5.0, 5.0, 10.0]);
+
+        // ufr
+        assert_eq!(splitted[5].min, [5.0, 0.0, 5.0]);
+        assert_eq!(splitted[5].max, [10.0, 5.0, 10.0]);
+
+        // ubl
+        assert_eq!(splitted[6].min, [0.0, 5.0, 5.0]);
+        assert_eq!(splitted[6].max, [5.0, 10.0, 10.0]);
+
+        // ubr
+        assert_eq!(splitted[7].min, [5.0, 5.0, 5.0]);
+        assert_eq!(splitted[7].max, [10.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    /// Should construct a tree node with default settings.
+    fn test_tree_node_default_construction() {
+        let tree_node: TreeNode<Point3D> = TreeNode::default();
+        assert!(tree_node.children.is_none());
+        assert_eq!(tree_node.bounding_box, BoundingBox::default());
+        assert_eq!(tree_node.points, PointSet::default());
+        assert_eq!(tree_node.split_axes, 0b111);
+        assert!(!tree_node.splitted);
+    }
+
+    #[test]
+    /// Should construct a tree node and do not further split.
+    fn test_tree_node_construction_no_split() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let points = [point1.clone(), point2.clone()];
+        let point_references: Vec<&Point3D> = points.iter().collect();
+        let tree_node = TreeNode::new(&OctreeConfig::default(), point_references.clone());
+
+        assert!(tree_node.children.is_none());
+        assert_eq!(tree_node.bounding_box.min, [0.0, 0.0, 0.0]);
+        assert_eq!(tree_node.bounding_box.max, [10.0f32.next_up(); 3]);
+        assert_eq!(tree_node.points, PointSet::from_iter([&point1, &point2]));
+        assert!(!tree_node.splitted);
+    }
+
+    #[test]
+    /// Should construct an octree with default settings.
+    fn test_octree_default_construction() {
+        let octree: Octree<Point3D> = Octree::default();
+
+        assert_eq!(octree.root, TreeNode::default());
+    }
+
+    #[test]
+    /// Should reject points into a default octree until it grows, and accept them into an empty one with bounds.
+    fn test_octree_start_empty() {
+        let points = random_points(574, 50, 10.0);
+
+        let mut octree = Octree::default();
+        assert!(points.iter().all(|point| !octree.insert(point)));
+        assert!(points
+            .iter()
+            .all(|point| !octree.bounds().covers(&point.get_location())));
+        assert!(points.iter().all(|point| octree.insert_growing(point)));
+        assert_eq!(octree.len(), points.len());
+        assert!(octree.check_invariants().is_ok());
+
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [10.0, 10.0, 10.0],
+        };
+        let mut octree = Octree::with_bounds(bounding_box.clone());
+        assert!(octree.is_empty());
+        assert!(points.iter().all(|point| octree.insert(point)));
+        assert_eq!(
+            octree,
+            OctreeBuilder::new().build_with_bounds(bounding_box, points.iter().collect())
+        );
+    }
+
+    #[test]
+    /// Should construct an octree from some given points.
+    fn test_octree_construction() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let points = [point1, point2];
+        let octree = Octree::new(points.iter().collect());
+        let tree_node = TreeNode::new(&OctreeConfig::default(), points.iter().collect());
+
+        assert_eq!(octree.root, tree_node);
+    }
+
+    #[test]
+    /// Should insert a point into octree if the octree covers it, and does nothing if not, or if it is already there.
+    fn test_octree_insert() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let point3 = Point3D::new(5.0, 5.0, 5.0);
+        let point4 = Point3D::new(20.0, 20.0, 20.0);
+        let points = [point1.clone(), point2.clone()];
+        let mut octree1 = Octree::new(points.iter().collect());
+        assert!(octree1.insert(&point3));
+        let all_points = [point1, point2, point3.clone()];
+        let octree2 = Octree::new(all_points.iter().collect());
+
+        assert_eq!(octree1, octree2);
+
+        assert!(!octree1.insert(&point4));
+
+        assert_eq!(octree1, octree2);
+
+        let generation = octree1.generation();
+        assert!(!octree1.insert(&point3));
+        assert_eq!(octree1, octree2);
+        assert_eq!(octree1.generation(), generation);
+    }
+
+    #[test]
+    /// Should report an error for points with an infinite or NaN coordinate, and tell apart why finite ones are or are
+    /// not inserted.
+    fn test_octree_try_insert() {
+        let points = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        let point1 = Point3D::new(5.0, 5.0, 5.0);
+        let point2 = Point3D::new(20.0, 20.0, 20.0);
+        let point3 = Point3D::new(5.0, f32::NAN, 5.0);
+        let point4 = Point3D::new(5.0, 5.0, f32::INFINITY);
+        let point5 = Point3D::new(f32::NEG_INFINITY, 5.0, 5.0);
+        let mut octree = Octree::new(points.iter().collect());
+
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = events.clone();
+        octree.on_change(move |event| recorder.lock().unwrap().push(event.map(Point3D::clone)));
+
+        assert_eq!(octree.try_insert(&point1), Ok(InsertOutcome::Inserted));
+        assert_eq!(
+            octree.try_insert(&point1),
+            Ok(InsertOutcome::AlreadyPresent)
+        );
+        assert_eq!(
+            octree.try_insert(&points[0]),
+            Ok(InsertOutcome::AlreadyPresent)
+        );
+        assert_eq!(octree.try_insert(&point2), Ok(InsertOutcome::OutOfBounds));
+        assert!(matches!(
+            octree.try_insert(&point3),
+            Err(OctreeError::NonFinite(_))
+        ));
+        assert_eq!(
+            octree.try_insert(&point4),
+            Err(OctreeError::NonFinite([5.0, 5.0, f32::INFINITY]))
+        );
+        assert_eq!(
+            octree.try_insert(&point5),
+            Err(OctreeError::NonFinite([f32::NEG_INFINITY, 5.0, 5.0]))
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![ChangeEvent::Inserted(point1.clone())]
+        );
+        assert_eq!(octree.len(), 3);
+        assert!(octree.check_invariants().is_ok());
+    }
+
+    #[test]
+    /// Should store every point given to new, including the ones on the max corner and on the max surfaces.
+    fn test_octree_new_stores_every_point() {
+        let numbers = random_numbers(540, 3 * 200);
+        let mut points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| {
+                Point3D::new(
+                    (c[0] * 10.0).floor(),
+                    (c[1] * 10.0).floor(),
+                    (c[2] * 10.0).floor(),
+                )
+            })
+            .collect();
+        points.push(Point3D::new(0.0, 0.0, 0.0));
+        points.push(Point3D::new(10.0, 10.0, 10.0));
+        points.push(Point3D::new(10.0, 0.0, 10.0));
+        let octree = Octree::new(points.iter().collect());
+
+        let stored: HashSet<&Point3D> = octree.iter().collect();
+        assert_eq!(stored.len(), points.iter().collect::<HashSet<_>>().len());
+        for point in &points {
+            assert!(octree.covers(point));
+            assert!(stored.contains(point));
+        }
+        assert!(octree.check_invariants().is_ok());
+
+        let octree = OctreeBuilder::new()
+            .capacity(1)
+            .build(points.iter().collect());
+        assert_eq!(octree.iter().collect::<HashSet<_>>(), stored);
+    }
+
+    #[test]
+    /// Should leave points with an infinite or NaN coordinate out of the bounding box and the octree.
+    fn test_octree_new_non_finite() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(5.0, 5.0, 5.0),
+            Point3D::new(f32::NAN, 5.0, 5.0),
+            Point3D::new(5.0, f32::INFINITY, 5.0),
+            Point3D::new(5.0, 5.0, f32::NEG_INFINITY),
+        ];
+        let bounding_box = BoundingBox::new(points.iter().collect());
+        assert_eq!(bounding_box.get_min(), &[0.0, 0.0, 0.0]);
+        assert_eq!(bounding_box.get_max(), &[10.0, 10.0, 10.0]);
+        for point in &points[3..] {
+            assert!(!bounding_box.covers(&point.get_location()));
+        }
+
+        let octree = Octree::new(points.iter().collect());
+        assert!(octree.contains(&points[2]));
+        for point in &points[3..] {
+            assert!(!octree.contains(point));
+        }
+        assert_eq!(octree.nearest([5.0, 5.0, 5.0]), Some(&points[2]));
+        assert!(octree.check_invariants().is_ok());
+
+        let bounding_box: BoundingBox = BoundingBox::new(points[3..].iter().collect());
+        assert_eq!(bounding_box, BoundingBox::default());
+    }
+
+    #[test]
+    /// Should keep only one of the approximate points which snap to the same grid location.
+    fn test_octree_approx_points() {
+        let points = [
+            ApproxPoint3D::new(0.0, 0.0, 0.0, 0.01),
+            ApproxPoint3D::new(10.0, 10.0, 10.0, 0.01),
+            ApproxPoint3D::new(5.0, 5.0, 5.0, 0.01),
+            ApproxPoint3D::new(5.001, 4.999, 5.0, 0.01),
+            ApproxPoint3D::new(f32::next_up(5.0), 5.0, 5.0, 0.01),
+        ];
+        let point = ApproxPoint3D::new(4.9999, 5.0, 5.0, 0.01);
+        let mut octree = Octree::new(points.iter().collect());
+
+        assert_eq!(octree.len(), 3);
+        assert!(octree.contains(&points[4]));
+        assert_eq!(octree.nearest([5.0, 5.0, 5.0]), Some(&points[2]));
+        assert!(octree.delete(&point));
+        assert_eq!(octree.len(), 2);
+    }
+
+    #[test]
+    /// Should delete a point from octree if the point is recorded, and does nothing if not.
+    fn test_octree_delete() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(5.0, 5.0, 5.0);
+        let point3 = Point3D::new(10.0, 10.0, 10.0);
+        let point4 = Point3D::new(100.0, 100.0, 100.0);
+        let points = [point1.clone(), point3.clone()];
+        let all_points = [point1, point2.clone(), point3];
+        let mut octree1 = Octree::new(all_points.iter().collect());
+        octree1.delete(&point2);
+        let octree2 = Octree::new(points.iter().collect());
+
+        assert_eq!(octree1, octree2);
+
+        octree1.delete(&point4);
+
+        assert_eq!(octree1, octree2);
+    }
+
+    #[test]
+    /// Should correctly identify if a point can be covered by a octree.
+    fn test_octree_covers() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(5.0, 5.0, 5.0);
+        let points = [point1, point2];
+        let octree = Octree::new(points.iter().collect());
+        let point3 = Point3D::new(10.0, 10.0, 10.0);
+        let point4 = Point3D::new(2.0, 2.0, 2.0);
+
+        assert!(octree.covers(&point4));
+        assert!(!octree.covers(&point3));
+    }
+
+    #[test]
+    /// Should correctly identify if a point is recorded.
+    fn test_octree_contains() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(5.0, 5.0, 5.0);
+        let points = [point1.clone(), point2.clone()];
+        let octree = Octree::new(points.iter().collect());
+        let point3 = Point3D::new(2.0, 2.0, 2.0);
+
+        assert!(octree.contains(&point1));
+        // The point at the max corner defines the extent, so the bounding box is grown to cover it.
+        assert!(octree.contains(&point2));
+        assert!(!octree.contains(&point3));
+
+        // Points pushed down into the children of a splitted, and of a loose, tree.
+        let points = random_points(542, 100, 10.0);
+        for loose_factor in [0.0, 0.5] {
+            let octree = OctreeBuilder::new()
+                .capacity(2)
+                .loose_factor(loose_factor)
+                .build(points.iter().collect());
+            assert!(octree.root.splitted);
+            assert!(points.iter().all(|point| octree.contains(point)));
+            assert!(!octree.contains(&point3));
+        }
+    }
+
+    #[test]
+    /// Should find points by their location alone, whichever point is located there.
+    fn test_octree_contains_location() {
+        let particles: Vec<Particle> = random_numbers(571, 3 * 100)
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Particle {
+                id,
+                location: [c[0] * 10.0, c[1] * 10.0, c[2] * 10.0],
+            })
+            .collect();
+        // A distinct particle at the same location as another one.
+        let twin = Particle {
+            id: particles.len(),
+            location: particles[0].location,
+        };
+        let builders = [
+            OctreeBuilder::new().capacity(2),
+            OctreeBuilder::new().capacity(2).loose_factor(0.5),
+            OctreeBuilder::new().capacity(2).project_axis(Some(2)),
+        ];
+        for builder in builders {
+            let mut octree = builder.build(particles.iter().collect());
+            assert!(octree.root.splitted);
+            assert!(particles
+                .iter()
+                .all(|particle| octree.contains_location(particle.location)));
+            assert!(!octree.contains_location([5.0, 5.0, 5.0]));
+            assert!(!octree.contains_location([f32::NAN; 3]));
+
+            assert!(!octree.contains(&twin));
+            assert!(octree.contains_location(twin.location));
+            assert!(octree.delete(&particles[0]));
+            assert!(!octree.contains_location(twin.location));
+            assert!(octree.insert(&twin));
+            assert!(!octree.contains(&particles[0]));
+            assert!(octree.contains_location(particles[0].location));
+        }
+    }
+
+    #[test]
+    /// Should find all points within the given query area.
+    fn test_octree_query() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let point3 = Point3D::new(4.0, 4.0, 4.0);
+        let point4 = Point3D::new(5.0, 10.0, 5.0);
+        let points = [point1.clone(), point2];
+        let mut octree = Octree::new(points.iter().collect());
+        octree.insert(&point3);
+        octree.insert(&point4);
+
+        let points_for_query = [point1.clone(), point4.clone()];
+        let bounding_box = BoundingBox::new(points_for_query.iter().collect());
+
+        assert_eq!(
+            octree.query(&bounding_box),
+            HashSet::from([&point1, &point3])
+        );
+    }
+
+    #[test]
+    /// Should identify if a bounding boxes overlaps / intersects with an octree.
+    fn test_octree_overlap() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let points = [point1, point2];
+        let octree = Octree::new(points.iter().collect());
+
+        let point3 = Point3D::new(1.0, 1.0, 1.0);
+        let point4 = Point3D::new(11.0, 11.0, 11.0);
+        let bounding_box1 = BoundingBox::new([point3, point4].iter().collect());
+
+        let point5 = Point3D::new(1.0, 1.0, 1.0);
+        let point6 = Point3D::new(9.0, 9.0, 9.0);
+        let bounding_box2 = BoundingBox::new([point5, point6].iter().collect());
+
+        let point7 = Point3D::new(11.0, 0.0, 0.0);
+        let point8 = Point3D::new(20.0, 20.0, 20.0);
+        let bounding_box3 = BoundingBox::new([point7, point8].iter().collect());
+
+        assert!(octree.overlaps(&bounding_box1));
+        assert!(octree.overlaps(&bounding_box2));
+        assert!(!octree.overlaps(&bounding_box3));
+    }
+
+    #[test]
+    /// Should not change which points are stored after reserving space.
+    fn test_octree_reserve() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let point3 = Point3D::new(5.0, 5.0, 5.0);
+        let points = [point1.clone(), point2.clone()];
+        let mut octree1 = Octree::new(points.iter().collect());
+        octree1.reserve(100);
+        octree1.insert(&point3);
+        let all_points = [point1, point2, point3.clone()];
+        let octree2 = Octree::new(all_points.iter().collect());
+
+        assert_eq!(octree1, octree2);
+        assert!(octree1.root.points.capacity() >= octree1.config.capacity);
+    }
+
+    #[test]
+    /// Should find the leaf bounding boxes crossed by a plane, and skip sub trees on one side of it.
+    fn test_octree_cells_crossed_by_plane() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let octree = Octree::new(points.iter().collect());
+        assert!(octree.root.splitted);
+
+        let splitted = octree.root.bounding_box.split();
+
+        // The plane x = 2.5 only crosses the left children.
+        assert_eq!(
+            octree.cells_crossed_by_plane(&[1.0, 0.0, 0.0, -2.5]),
+            vec![
+                splitted[0].clone(),
+                splitted[2].clone(),
+                splitted[4].clone(),
+                splitted[6].clone()
+            ]
+        );
+
+        // The plane through the centre along z touches all children.
+        let centre = octree.root.bounding_box.get_centre();
+        assert_eq!(
+            octree
+                .cells_crossed_by_plane(&[0.0, 0.0, 1.0, -centre[2]])
+                .len(),
+            8
+        );
+
+        // The plane y = -1 is outside of the octree.
+        assert!(octree
+            .cells_crossed_by_plane(&[0.0, 1.0, 0.0, 1.0])
+            .is_empty());
+    }
+
+    #[test]
+    /// Should call the registered callback after every successful insert and delete.
+    fn test_octree_on_change() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let point3 = Point3D::new(5.0, 5.0, 5.0);
+        let point4 = Point3D::new(20.0, 20.0, 20.0);
+        let points = [point1.clone(), point2];
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut octree = Octree::new(points.iter().collect());
+        let recorder = events.clone();
+        octree.on_change(move |event| recorder.lock().unwrap().push(event.map(Point3D::clone)));
+
+        octree.insert(&point3);
+        octree.insert(&point4);
+        octree.delete(&point1);
+        octree.delete(&point4);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ChangeEvent::Inserted(point3.clone()),
+                ChangeEvent::Removed(point1.clone())
+            ]
+        );
+    }
+
+    #[test]
+    /// Should record the points inserted and deleted since the last take, which turn the old points into the new ones.
+    fn test_octree_tracking() {
+        let particles: Vec<Particle> = random_numbers(572, 3 * 40)
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Particle {
+                id,
+                location: [c[0] * 10.0, c[1] * 10.0, c[2] * 10.0],
+            })
+            .collect();
+        // The first particles moved, equal to the originals by id.
+        let moved: Vec<Particle> = particles[..5]
+            .iter()
+            .map(|particle| Particle {
+                id: particle.id,
+                location: particle.location.map(|coordinate| coordinate * 0.5 + 2.5),
+            })
+            .collect();
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [10.0, 10.0, 10.0],
+        };
+        let mut octree = OctreeBuilder::new()
+            .capacity(4)
+            .build_with_bounds(bounding_box, particles[..30].iter().collect());
+        assert!(octree.take_changes().is_empty());
+
+        octree.begin_tracking();
+        let before = octree.clone();
+        for (old, new) in particles.iter().zip(&moved) {
+            assert!(octree.update(old, new));
+        }
+        // Inserted and deleted again, so no change.
+        assert!(octree.insert(&particles[30]));
+        assert!(octree.delete(&particles[30]));
+        octree.extend(&particles[31..]);
+        octree.retain(|particle| particle.id % 7 != 6);
+        assert!(octree.delete(&particles[10]));
+
+        let changes = octree.take_changes();
+        assert!(octree.take_changes().is_empty());
+        // The moved particles, and the extended ones but particles[34], which is not retained.
+        assert_eq!(changes.get_inserted().len(), 5 + 9 - 1);
+        assert!(!changes.get_inserted().contains(&particles[30]));
+        assert!(!changes.get_deleted().contains(&particles[30]));
+        for new in &moved {
+            assert!(changes.get_deleted().contains(new));
+            assert!(changes.get_inserted().contains(new));
+        }
+        // Applying the changes to the points of before gives the points of now, moved ones included.
+        let mut applied: Vec<&Particle> = before
+            .iter()
+            .filter(|particle| !changes.get_deleted().contains(particle))
+            .collect();
+        applied.extend(changes.get_inserted().iter().copied());
+        let mut expected: Vec<&Particle> = octree.iter().collect();
+        applied.sort_by_key(|particle| particle.id);
+        expected.sort_by_key(|particle| particle.id);
+        assert_eq!(applied.len(), expected.len());
+        assert!(applied
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| a.id == b.id && a.location == b.location));
+
+        octree.clear();
+        assert_eq!(octree.take_changes().get_deleted().len(), expected.len());
+    }
+
+    #[test]
+    /// Should find the added and removed points by membership, no matter how the trees are shaped.
+    fn test_diff() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let point = Point3D::new(5.0, 5.0, 5.0);
+        let old = Octree::new(points.iter().collect());
+        let mut new = Octree::new(points.iter().rev().collect());
+        assert!(old.root.splitted);
+
+        assert_eq!(diff(&old, &new), (HashSet::new(), HashSet::new()));
+
+        new.insert(&point);
+        new.delete(&points[2]);
+        assert_eq!(
+            diff(&old, &new),
+            (HashSet::from([&point]), HashSet::from([&points[2]]))
+        );
+        assert_eq!(
+            diff(&new, &old),
+            (HashSet::from([&points[2]]), HashSet::from([&point]))
+        );
+    }
+
+    #[test]
+    /// Should increase the generation on every change, and only on changes.
+    fn test_octree_generation() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let point3 = Point3D::new(5.0, 5.0, 5.0);
+        let point4 = Point3D::new(20.0, 20.0, 20.0);
+        let points = [point1.clone(), point2];
+        let mut octree = Octree::new(points.iter().collect());
+        assert_eq!(octree.generation(), 0);
+
+        octree.insert(&point3);
+        assert_eq!(octree.generation(), 1);
+        octree.insert(&point4);
+        assert_eq!(octree.generation(), 1);
+        octree.delete(&point1);
+        assert_eq!(octree.generation(), 2);
+        octree.delete(&point4);
+        assert_eq!(octree.generation(), 2);
+
+        octree.generation = u64::MAX;
+        octree.delete(&point3);
+        assert_eq!(octree.generation(), 0);
+    }
+
+    #[test]
+    /// Should find the same points as the set query, each once, in plain and loose trees.
+    fn test_octree_query_vec() {
+        let points = random_points(565, 500, 10.0);
+        for loose_factor in [0.0, 0.5] {
+            let octree = OctreeBuilder::new()
+                .loose_factor(loose_factor)
+                .build(points.iter().collect());
+            for corners in random_numbers(5650, 6 * 20).chunks(6) {
+                let bounding_box = BoundingBox::new(
+                    [
+                        Point3D::new(corners[0] * 10.0, corners[1] * 10.0, corners[2] * 10.0),
+                        Point3D::new(corners[3] * 10.0, corners[4] * 10.0, corners[5] * 10.0),
+                    ]
+                    .iter()
+                    .collect(),
+                );
+                let found = octree.query_vec(&bounding_box);
+                let expected = octree.query(&bounding_box);
+                assert_eq!(found.len(), expected.len());
+                assert_eq!(found.into_iter().collect::<HashSet<_>>(), expected);
+            }
+            assert_eq!(octree.query_vec(octree.bounds()).len(), points.len());
+        }
+
+        let octree: Octree<Point3D> = Octree::default();
+        assert!(octree.query_vec(&BoundingBox::default()).is_empty());
+    }
+
+    #[test]
+    /// Should return the queried points sorted by location, without duplicates.
+    fn test_octree_query_ordered() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(9.0, 1.0, 1.0),
+            Point3D::new(1.0, 9.0, 1.0),
+            Point3D::new(1.0, 1.0, 9.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(5.0, 5.0, 5.0),
+            Point3D::new(-0.0, 0.0, 1.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 0.5, 1.0),
+            Point3D::new(7.0, 7.0, 7.0),
+        ];
+        let octree = Octree::new(points.iter().collect());
+        assert!(octree.root.splitted);
+
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(9.5, 9.5, 9.5)]
+                .iter()
+                .collect(),
+        );
+
+        assert_eq!(
+            octree.query_ordered(&bounding_box),
+            vec![
+                &points[7],
+                &points[0],
+                &points[5],
+                &points[4],
+                &points[3],
+                &points[6],
+                &points[10],
+                &points[8],
+                &points[9],
+                &points[2]
+            ]
+        );
+    }
+
+    #[test]
+    /// Should expose the bounding box of the root, which covers every point given on construction and grows with the
+    /// tree.
+    fn test_octree_bounds() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.bounds(), &BoundingBox::default());
+
+        let points = random_points(558, 200, 10.0);
+        let far = Point3D::new(-30.0, 5.0, 5.0);
+        let mut octree = Octree::new(points.iter().collect());
+        assert_eq!(octree.bounds(), octree.root_cursor().bounds());
+        assert_eq!(octree.query(octree.bounds()).len(), points.len());
+
+        let before = octree.bounds().clone();
+        assert!(octree.insert_growing(&far));
+        assert!(octree.bounds().contains_box(&before));
+        assert!(octree.bounds().covers(&far.get_location()));
+        assert_eq!(octree.query(octree.bounds()).len(), points.len() + 1);
+    }
+
+    #[test]
+    /// Should walk the tree through node cursors.
+    fn test_octree_root_cursor() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let octree = Octree::new(points.iter().collect());
+        let splitted = octree.root.bounding_box.split();
+
+        let root = octree.root_cursor();
+        assert!(!root.is_leaf());
+        assert_eq!(root.bounds(), &octree.root.bounding_box);
+        // The root holds more points than its capacity, so it pushed all of them down.
+        assert_eq!(root.points().count(), 0);
+        assert!(root.child(8).is_none());
+
+        for (i, bounding_box) in splitted.iter().enumerate() {
+            let child = root.child(i).unwrap();
+            assert!(child.is_leaf());
+            assert!(child.child(0).is_none());
+            assert_eq!(child.bounds(), bounding_box);
+        }
+        assert_eq!(
+            root.child(0).unwrap().points().collect::<HashSet<_>>(),
+            HashSet::from([&points[0], &points[2], &points[3], &points[4], &points[5]])
+        );
+        assert_eq!(
+            root.child(7).unwrap().points().collect::<HashSet<_>>(),
+            HashSet::from([&points[1], &points[6], &points[7], &points[8], &points[9]])
+        );
+    }
+
+    #[test]
+    /// Should split planar points only along the two axes they spread over.
+    fn test_octree_planar_points() {
+        let numbers = random_numbers(546, 2 * 500);
+        let points: Vec<Point3D> = numbers
+            .chunks(2)
+            .map(|c| Point3D::new(c[0] * 100.0, 0.0, c[1] * 100.0))
+            .collect();
+        let octree = Octree::new(points.iter().collect());
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert_eq!(octree.len(), points.len());
+        for node in octree.nodes().filter(|node| !node.is_leaf()) {
+            assert_eq!(node.node.split_axes, 0b101);
+            assert_eq!(node.children().count(), 4);
+        }
+
+        let bounding_box = BoundingBox::new(
+            [
+                Point3D::new(20.0, -1.0, 30.0),
+                Point3D::new(60.0, 1.0, 50.0),
+            ]
+            .iter()
+            .collect(),
+        );
+        let expected: HashSet<&Point3D> = points
+            .iter()
+            .filter(|point| bounding_box.covers(&point.get_location()))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(octree.query(&bounding_box), expected);
+        assert_eq!(
+            octree.nearest([50.0, 0.0, 50.0]),
+            octree.k_nearest(&[50.0, 0.0, 50.0], 1).first().copied()
+        );
+    }
+
+    #[test]
+    /// Should iterate over the used children of a node, matching child for each octant.
+    fn test_node_cursor_children() {
+        let numbers = random_numbers(544, 3 * 200);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 100.0, c[1] * 100.0, c[2]))
+            .collect();
+
+        for split_ratio in [0.0, 0.5] {
+            let octree = OctreeBuilder::new()
+                .split_ratio(split_ratio)
+                .build(points.iter().collect());
+            for node in octree.nodes() {
+                let children: Vec<_> = node.children().collect();
+                let expected: Vec<_> = (0..8).filter_map(|octant| node.child(octant)).collect();
+                assert_eq!(children.len(), expected.len());
+                for (child, expected) in children.iter().zip(&expected) {
+                    assert!(core::ptr::eq(child.node, expected.node));
+                    assert_eq!(child.depth(), node.depth() + 1);
+                }
+                assert_eq!(node.is_leaf(), children.is_empty());
+            }
+
+            // The flat points are only splitted along x and y with a split ratio.
+            let expected = if split_ratio == 0.0 { 8 } else { 4 };
+            assert_eq!(octree.root_cursor().children().count(), expected);
+        }
+    }
+
+    #[test]
+    /// Should accept a valid tree, and report the first violated invariant of a broken one.
+    fn test_octree_check_invariants() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let outside = Point3D::new(1.0, 1.0, 1.0);
+        let mut octree = Octree::new(points.iter().collect());
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        octree.root.children.as_mut().unwrap()[7]
+            .points
+            .insert(&outside);
+        assert!(octree
+            .check_invariants()
+            .unwrap_err()
+            .starts_with("node at octants [7]: point at [1.0, 1.0, 1.0] is not covered"));
+        octree.root.children.as_mut().unwrap()[7]
+            .points
+            .remove(&outside);
+
+        octree.root.children.as_mut().unwrap()[2].bounding_box = BoundingBox::default();
+        assert!(octree
+            .check_invariants()
+            .unwrap_err()
+            .starts_with("node at octants [2]: BoundingBox"));
+
+        octree.root.children.as_mut().unwrap()[2].bounding_box =
+            octree.root.split_bounding_box()[2].clone();
+        octree.root.points.insert(&points[0]);
+        assert_eq!(
+            octree.check_invariants(),
+            Err("node at octants []: counts 10 points in its sub tree but holds 11".to_string())
+        );
+        octree.root.subtree_count += 1;
+        assert_eq!(
+            octree.check_invariants(),
+            Err("node at octants []: point at [0.0, 0.0, 0.0] is not pushed down to the child in octant 0".to_string())
+        );
+        octree.root.points.clear();
+        octree.root.subtree_count -= 1;
+
+        octree.root.splitted = false;
+        assert_eq!(
+            octree.check_invariants(),
+            Err("node at octants []: has children but is not splitted".to_string())
+        );
+    }
+
+    #[test]
+    /// Should shrink the root to the recorded points only if it is much larger than them.
+    fn test_octree_compact() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(1.0, 1.0, 1.0);
+        let point3 = Point3D::new(2.0, 2.0, 2.0);
+        let point4 = Point3D::new(8.0, 8.0, 8.0);
+        let point5 = Point3D::new(10.0, 10.0, 10.0);
+        let points = [
+            point1.clone(),
+            point2.clone(),
+            point3.clone(),
+            point4.clone(),
+            point5.clone(),
+        ];
+        let mut octree = Octree::new(points.iter().collect());
+
+        assert!(!octree.compact());
+        assert_eq!(octree.generation(), 0);
+
+        octree.delete(&point4);
+        octree.delete(&point5);
+        assert!(octree.compact());
+        assert_eq!(octree.generation(), 3);
+        assert_eq!(octree.root.bounding_box.min, [0.0; 3]);
+        assert_eq!(octree.root.bounding_box.max, [2.0f32.next_up(); 3]);
+        assert!(octree.contains(&point1));
+        assert!(octree.contains(&point2));
+        assert!(octree.contains(&point3));
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        // Already tight.
+        assert!(!octree.compact());
+
+        let mut empty: Octree<Point3D> = Octree::default();
+        assert!(!empty.compact());
+    }
+
+    #[test]
+    /// Should move the points whose location changed behind the back of the tree to the nodes covering them again.
+    fn test_octree_reindex() {
+        /// A point moved through a shared reference, identified by its id only.
+        #[derive(Debug)]
+        struct Drifting {
+            id: usize,
+            location: Cell<[f32; 3]>,
+        }
+
+        impl PartialEq for Drifting {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+
+        impl Eq for Drifting {}
+
+        impl Hash for Drifting {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+
+        impl Locatable for Drifting {
+            fn get_location(&self) -> [f32; 3] {
+                self.location.get()
+            }
+        }
+
+        let points: Vec<Drifting> = random_numbers(573, 3 * 200)
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Drifting {
+                id,
+                location: Cell::new([c[0] * 10.0, c[1] * 10.0, c[2] * 10.0]),
+            })
+            .collect();
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [10.0, 10.0, 10.0],
+        };
+        let query = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [2.0, 2.0, 2.0],
+        };
+        for builder in [
+            OctreeBuilder::new().capacity(4),
+            OctreeBuilder::new().capacity(4).loose_factor(0.25),
+        ] {
+            for (i, point) in points.iter().enumerate() {
+                point.location.set([i as f32 / 20.0; 3].map(|c| c + 0.01));
+            }
+            let mut octree =
+                builder.build_with_bounds(bounding_box.clone(), points.iter().collect());
+            assert!(octree.reindex().is_empty());
+            let generation = octree.generation();
+
+            // A few points move into the corner queried, and one out of the bounds.
+            for point in &points[150..160] {
+                point.location.set([1.0, 1.5, 0.5]);
+            }
+            points[199].location.set([20.0, 5.0, 5.0]);
+            let expected: Vec<usize> = points[..199]
+                .iter()
+                .filter(|point| query.covers(&point.get_location()))
+                .map(|point| point.id)
+                .collect();
+            let found = |octree: &Octree<Drifting>| {
+                let mut ids: Vec<usize> = octree
+                    .query_vec(&query)
+                    .iter()
+                    .map(|point| point.id)
+                    .collect();
+                ids.sort();
+                ids
+            };
+            assert_ne!(found(&octree), expected);
+
+            assert_eq!(octree.reindex(), vec![&points[199]]);
+            assert!(octree.generation() > generation);
+            assert_eq!(octree.len(), 199);
+            assert!(!octree.contains(&points[199]));
+            assert_eq!(found(&octree), expected);
+            assert!(octree.check_invariants().is_ok());
+            assert!(octree.reindex().is_empty());
+        }
+    }
+
+    #[test]
+    /// Should keep the exact point set and the settings while rebuilding over the points, never making the tree deeper.
+    fn test_octree_rebuild() {
+        let mut empty: Octree<Point3D> = Octree::default();
+        empty.rebuild();
+        assert_eq!(empty, Octree::default());
+        assert_eq!(empty.generation(), 0);
+
+        let points = random_points(562, 400, 10.0);
+        let far: Vec<Point3D> = (1..6)
+            .map(|i| Point3D::new(100.0 * i as f32, -50.0 * i as f32, 10.0))
+            .collect();
+        let builder = OctreeBuilder::new().capacity(4).loose_factor(0.25);
+        let mut octree = builder.clone().build(points[..200].iter().collect());
+
+        // Churn: grow towards far away points, then move most points away and back.
+        for point in &far {
+            assert!(octree.insert_growing(point));
+        }
+        assert_eq!(octree.extend(&points[200..]), 200);
+        for point in far.iter().chain(&points[..300]) {
+            assert!(octree.delete(point));
+        }
+        let stored: HashSet<&Point3D> = octree.iter().collect();
+        let depth = octree.depth();
+
+        octree.rebuild();
+        assert_eq!(octree.iter().collect::<HashSet<_>>(), stored);
+        assert!(octree.depth() <= depth);
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert_eq!(octree, builder.build(points[300..].iter().collect()));
+
+        // Rebuilding again changes nothing.
+        let rebuilt = octree.clone();
+        octree.rebuild();
+        assert_eq!(octree, rebuilt);
+    }
+
+    #[test]
+    /// Should keep a root bounding box given explicitly when rebuilding, so points outside the recorded ones fit.
+    fn test_octree_rebuild_explicit_bounds() {
+        let point1 = Point3D::new(1.0, 1.0, 1.0);
+        let point2 = Point3D::new(2.0, 2.0, 2.0);
+        let outside = Point3D::new(90.0, 90.0, 90.0);
+        let bounding_box = BoundingBox::new(
+            [
+                Point3D::new(0.0, 0.0, 0.0),
+                Point3D::new(100.0, 100.0, 100.0),
+            ]
+            .iter()
+            .collect(),
+        );
+
+        let mut octree = OctreeBuilder::new()
+            .capacity(1)
+            .build_with_bounds(bounding_box.clone(), Vec::new());
+        assert_eq!(octree.extend([&point1, &point2]), 2);
+        octree.rebuild();
+        assert_eq!(octree.bounds(), &bounding_box);
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert!(octree.insert(&outside));
+
+        let mut built = OctreeBuilder::new().build_with_bounds(bounding_box.clone(), vec![&point1]);
+        built.rebuild();
+        assert_eq!(built.bounds(), &bounding_box);
+
+        // Bounds derived from the points, or fitted by compacting, are fitted again.
+        let mut derived = Octree::new(vec![&point1, &point2]);
+        assert!(derived.insert_growing(&outside));
+        assert!(derived.delete(&outside));
+        derived.rebuild();
+        assert!(!derived.covers(&outside));
+        assert!(octree.delete(&outside));
+        assert!(octree.compact_with_ratio(1.0));
+        octree.rebuild();
+        assert!(!octree.covers(&outside));
+    }
+
+    #[test]
+    /// Should report the settings an octree was built with, and keep them through changes which rebuild the tree.
+    fn test_octree_config() {
+        let points: Vec<Point3D> = (0..50)
+            .map(|i| Point3D::new(i as f32, (i * 7 % 13) as f32, 0.0))
+            .collect();
+        let builder = OctreeBuilder::new()
+            .capacity(0)
+            .loose_factor(-1.0)
+            .growth_policy(GrowthPolicy::Fit)
+            .project_axis(Some(2))
+            .split_ratio(2.0)
+            .max_depth(5);
+        assert_eq!(builder.get_capacity(), 1);
+        assert_eq!(builder.get_loose_factor(), 0.0);
+        assert_eq!(builder.get_growth_policy(), GrowthPolicy::Fit);
+        assert_eq!(builder.get_project_axis(), Some(2));
+        assert_eq!(builder.get_split_ratio(), 1.0);
+        assert_eq!(builder.get_max_depth(), 5);
+
+        let far = Point3D::new(100.0, 100.0, 0.0);
+        let mut octree = builder.clone().build(points.iter().collect());
+        assert_eq!(octree.config(), builder.get_config());
+        assert_eq!(
+            Octree::with_config(points.iter().collect(), octree.config()),
+            octree
+        );
+
+        assert!(octree.insert_growing(&far));
+        octree.rebuild();
+        assert_eq!(octree.config(), builder.get_config());
+
+        let default: Octree<Point3D> = Octree::default();
+        assert_eq!(default.config(), OctreeConfig::default());
+        assert_eq!(
+            OctreeBuilder::default().get_config(),
+            OctreeConfig::default()
+        );
+
+        let builder = builder.grow_on_insert(true).boundary_inclusive(true);
+        assert!(builder.get_grow_on_insert());
+        assert!(builder.get_boundary_inclusive());
+        assert_eq!(
+            builder.clone().build(points.iter().collect()).config(),
+            builder.get_config()
+        );
+    }
+
+    #[test]
+    /// Should build the same octree as the builder with the same settings, and as Octree::new by default.
+    fn test_octree_with_config() {
+        let points = random_points(563, 200, 1.0);
+        assert_eq!(
+            Octree::with_config(points.iter().collect(), OctreeConfig::default()),
+            Octree::new(points.iter().collect())
+        );
+
+        let config = OctreeConfig {
+            capacity: 2,
+            loose_factor: 0.25,
+            split_ratio: 0.5,
+            max_depth: 3,
+            grow_on_insert: true,
+            boundary_inclusive: true,
+            ..OctreeConfig::default()
+        };
+        let octree = Octree::with_config(points.iter().collect(), config);
+        let builder = OctreeBuilder::new()
+            .capacity(2)
+            .loose_factor(0.25)
+            .split_ratio(0.5)
+            .max_depth(3)
+            .grow_on_insert(true)
+            .boundary_inclusive(true);
+        assert_eq!(octree.config(), config);
+        assert_eq!(builder.get_config(), config);
+        assert_eq!(octree, builder.build(points.iter().collect()));
+        assert!(octree.depth() <= 3);
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        // Settings out of range are treated as by the builder.
+        let config = OctreeConfig {
+            capacity: 0,
+            loose_factor: -1.0,
+            ..OctreeConfig::default()
+        };
+        let octree = Octree::with_config(points.iter().collect(), config);
+        assert_eq!(octree.config().capacity, 1);
+        assert_eq!(octree.config().loose_factor, 0.0);
+    }
+
+    #[test]
+    /// Should grow the root on every kind of insert when growing on insert, but never for non finite points.
+    fn test_octree_grow_on_insert() {
+        let points: Vec<Point3D> = (0..20)
+            .map(|i| Point3D::new(i as f32, (i * 3 % 7) as f32, (i * 5 % 11) as f32))
+            .collect();
+        let far = Point3D::new(100.0, -50.0, 30.0);
+        let farther = [
+            Point3D::new(-200.0, 0.0, 0.0),
+            Point3D::new(0.0, 300.0, 0.0),
+            Point3D::new(f32::NAN, 0.0, 0.0),
+        ];
+        let moved = Point3D::new(0.0, 0.0, -400.0);
+        let infinite = Point3D::new(f32::INFINITY, 0.0, 0.0);
+        let builder = OctreeBuilder::new().capacity(2).grow_on_insert(true);
+        let mut octree = builder.build(points.iter().collect());
+
+        assert_eq!(octree.try_insert(&far), Ok(InsertOutcome::Inserted));
+        assert!(octree.covers(&far));
+        assert_eq!(octree.extend(&farther), 2);
+        assert!(octree.covers(&farther[0]) && octree.covers(&farther[1]));
+        assert!(octree.update(&far, &moved));
+        assert!(octree.contains(&moved));
+        assert!(!octree.insert(&infinite));
+        assert_eq!(octree.len(), points.len() + 3);
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        let mut fixed = Octree::new(points.iter().collect());
+        assert!(!fixed.insert(&far));
+        assert_eq!(fixed.extend(&farther), 0);
+    }
+
+    #[test]
+    /// Should cover the max surface of the root only when inclusive, with every point in exactly one child.
+    fn test_octree_boundary_inclusive() {
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(8.0, 8.0, 8.0)]
+                .iter()
+                .collect(),
+        );
+        // Points on the max faces, edges and corner, and on the centre planes.
+        let points: Vec<Point3D> = (0..125)
+            .map(|i| {
+                Point3D::new(
+                    (i % 5) as f32 * 2.0,
+                    (i / 5 % 5) as f32 * 2.0,
+                    (i / 25) as f32 * 2.0,
+                )
+            })
+            .collect();
+        let beyond = Point3D::new(8.5, 0.0, 0.0);
+
+        let mut octree = OctreeBuilder::new()
+            .capacity(2)
+            .boundary_inclusive(true)
+            .build_with_bounds(bounding_box.clone(), points.iter().collect());
+        assert_eq!(octree.len(), 125);
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert!(points.iter().all(|point| octree.contains(point)));
+        assert!(!octree.insert(&beyond));
+        assert!(octree.delete(&points[124]));
+        assert!(!octree.contains(&points[124]));
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        // Growing keeps the new root inclusive, and moves the points off the old max surface.
+        assert!(octree.insert_growing(&beyond));
+        assert!(octree.covers(&Point3D::new(16.0, 16.0, 16.0)));
+        assert_eq!(octree.len(), 125);
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        let half_open = OctreeBuilder::new()
+            .capacity(2)
+            .build_with_bounds(bounding_box, points.iter().collect());
+        assert_eq!(half_open.len(), 64);
+    }
+
+    #[test]
+    /// Should find the same points with every query variant on an inclusive root, also on its max surface.
+    fn test_octree_boundary_inclusive_queries() {
+        let bounds = BoundingBox::new(
+            [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)]
+                .iter()
+                .collect(),
+        );
+        let mut points = vec![
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(5.0, 5.0, 5.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(10.0, 2.0, 3.0),
+            Point3D::new(4.0, 10.0, 10.0),
+        ];
+        points.extend(random_points(5631, 100, 10.0));
+
+        for loose_factor in [0.0, 0.25] {
+            let octree = OctreeBuilder::new()
+                .capacity(1)
+                .loose_factor(loose_factor)
+                .boundary_inclusive(true)
+                .build_with_bounds(bounds.clone(), points.iter().collect());
+            assert_eq!(octree.len(), points.len());
+
+            let mut boxes = vec![octree.bounds().clone()];
+            for corners in random_numbers(5632, 6 * 50).chunks(6) {
+                // Snap some corners onto the max surface of the root.
+                let coordinate = |c: f32| if c > 0.8 { 10.0 } else { c * 12.0 - 1.0 };
+                boxes.push(BoundingBox::new(
+                    [
+                        Point3D::new(corners[0] * 10.0, corners[1] * 10.0, corners[2] * 10.0),
+                        Point3D::new(
+                            coordinate(corners[3]),
+                            coordinate(corners[4]),
+                            coordinate(corners[5]),
+                        ),
+                    ]
+                    .iter()
+                    .collect(),
+                ));
+            }
+            for bounding_box in &boxes {
+                let expected = octree.query(bounding_box);
+                assert_eq!(octree.count_in_box(bounding_box), expected.len());
+                assert_eq!(
+                    octree
+                        .query_vec(bounding_box)
+                        .into_iter()
+                        .collect::<HashSet<_>>(),
+                    expected
+                );
+                assert_eq!(octree.query_where(bounding_box, |_| true), expected);
+                let mut visited = HashSet::new();
+                octree.for_each_in_box(bounding_box, |point| {
+                    visited.insert(point);
+                });
+                assert_eq!(visited, expected);
+                let with_cells: HashSet<_> = octree
+                    .query_with_cells(bounding_box)
+                    .into_iter()
+                    .map(|(point, _)| point)
+                    .collect();
+                assert_eq!(with_cells, expected);
+                assert_eq!(
+                    octree.bounds_of_query(bounding_box),
+                    (!expected.is_empty())
+                        .then(|| BoundingBox::new(expected.iter().copied().collect()))
+                );
+                #[cfg(feature = "rayon")]
+                assert_eq!(octree.query_parallel(bounding_box), expected);
+                let mut removed = octree.clone();
+                assert_eq!(removed.remove_within(bounding_box), expected.len());
+            }
+            // A loose root also holds points in its margin, beyond the bounds.
+            if loose_factor == 0.0 {
+                assert_eq!(octree.query(octree.bounds()).len(), points.len());
+            }
+        }
+    }
+
+    #[test]
+    /// Should build the same octree as Octree::new with the default settings.
+    fn test_octree_builder_default() {
+        let point1 = Point3D::new(0.0, 0.0, 0.0);
+        let point2 = Point3D::new(10.0, 10.0, 10.0);
+        let points = [point1, point2];
+
+        assert_eq!(
+            OctreeBuilder::new().build(points.iter().collect()),
+            Octree::new(points.iter().collect())
+        );
+    }
+
+    #[test]
+    /// Should accept and find points slightly outside of a node with a loose factor.
+    fn test_octree_builder_loose_factor() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let point = Point3D::new(11.0, 6.0, 6.0);
+        let mut octree = OctreeBuilder::new()
+            .loose_factor(0.25)
+            .build(points.iter().collect());
+        assert_eq!(octree.config().loose_factor, 0.25);
+        assert!(octree.root.splitted);
+
+        // The root is splitted, so it pushed all of its points down.
+        assert!(octree.root.points.is_empty());
+
+        // The point is stored by a loose child although it is outside of the root's bounding box.
+        assert!(octree.insert(&point));
+        assert!(!octree.root.points.contains(&point));
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(10.5, 5.5, 5.5), Point3D::new(12.0, 7.0, 7.0)]
+                .iter()
+                .collect(),
+        );
+        assert_eq!(octree.query(&bounding_box), HashSet::from([&point]));
+
+        let mut octree = Octree::new(points.iter().collect());
+        assert!(!octree.insert(&point));
+        assert_eq!(
+            OctreeBuilder::new().loose_factor(-1.0),
+            OctreeBuilder::new()
+        );
+    }
+
+    /// A point identified by its id only, so it can be moved between frames.
+    #[derive(Debug)]
+    struct Particle {
+        id: usize,
+        location: [f32; 3],
+    }
+
+    impl PartialEq for Particle {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Particle {}
+
+    impl Hash for Particle {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.id.hash(state);
+        }
+    }
+
+    impl Locatable for Particle {
+        fn get_location(&self) -> [f32; 3] {
+            self.location
+        }
+    }
+
+    /// Deterministic pseudo random numbers in [0, 1) for tests.
+    fn random_numbers(seed: u64, count: usize) -> Vec<f32> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 40) as f32 / (1u64 << 24) as f32
+            })
+            .collect()
+    }
+
+    /// Deterministic pseudo random points in [0, scale) along every axis for tests, from [random_numbers].
+    fn random_points(seed: u64, count: usize, scale: f32) -> Vec<Point3D> {
+        random_numbers(seed, 3 * count)
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * scale, c[1] * scale, c[2] * scale))
+            .collect()
+    }
+
+    #[test]
+    /// Should find the same points as filtering every point, whether query bounding boxes contain whole nodes or only
+    /// overlap them.
+    fn test_octree_query_contained_nodes() {
+        let points = random_points(515, 2000, 100.0);
+        let octrees = [
+            Octree::new(points.iter().collect()),
+            OctreeBuilder::new()
+                .loose_factor(0.5)
+                .build(points.iter().collect()),
+            OctreeBuilder::new()
+                .capacity(1)
+                .build(points.iter().collect()),
+        ];
+
+        let corners = random_numbers(5150, 6 * 50);
+        for corners in corners.chunks(6) {
+            let corners = [
+                Point3D::new(
+                    corners[0] * 120.0 - 10.0,
+                    corners[1] * 120.0 - 10.0,
+                    corners[2] * 120.0 - 10.0,
+                ),
+                Point3D::new(
+                    corners[3] * 120.0 - 10.0,
+                    corners[4] * 120.0 - 10.0,
+                    corners[5] * 120.0 - 10.0,
+                ),
+            ];
+            let bounding_box = BoundingBox::new(corners.iter().collect());
+            for octree in &octrees {
+                let expected: HashSet<&Point3D> = octree
+                    .iter()
+                    .filter(|point| bounding_box.covers(&point.get_location()))
+                    .collect();
+                assert_eq!(octree.query(&bounding_box), expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Should count the same number of points as a query returns.
+    fn test_octree_count_in_box() {
+        let numbers = random_numbers(516, 3 * 1000);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 100.0, c[1] * 100.0, c[2] * 10.0))
+            .collect();
+        let octrees = [
+            Octree::new(points.iter().collect()),
+            OctreeBuilder::new()
+                .loose_factor(0.25)
+                .capacity(3)
+                .build(points.iter().collect()),
+            OctreeBuilder::new()
+                .project_axis(Some(2))
+                .build(points.iter().collect()),
+        ];
+
+        let corners = random_numbers(5160, 6 * 50);
+        for corners in corners.chunks(6) {
+            let corners = [
+                Point3D::new(
+                    corners[0] * 120.0 - 10.0,
+                    corners[1] * 120.0 - 10.0,
+                    corners[2] * 12.0 - 1.0,
+                ),
+                Point3D::new(
+                    corners[3] * 120.0 - 10.0,
+                    corners[4] * 120.0 - 10.0,
+                    corners[5] * 12.0 - 1.0,
+                ),
+            ];
+            let bounding_box = BoundingBox::new(corners.iter().collect());
+            for octree in &octrees {
+                assert_eq!(
+                    octree.count_in_box(&bounding_box),
+                    octree.query(&bounding_box).len()
+                );
+            }
+        }
+        assert_eq!(
+            Octree::<Point3D>::default().count_in_box(&BoundingBox::default()),
+            0
+        );
+    }
+
+    #[test]
+    /// Should count the points of each grid cell, in row-major order over x, y and z.
+    fn test_octree_density_grid() {
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [8.0, 8.0, 8.0],
+        };
+        let points = random_points(569, 1000, 8.0);
+        let divisions = [2, 4, 8];
+        // Every boundary of these divisions is an integer, so the cell of a point is found by truncation.
+        let mut expected = vec![0; 64];
+        for point in &points {
+            let [x, y, z] = point.get_location();
+            let (i, j, k) = (x as usize / 4, y as usize / 2, z as usize);
+            expected[(i * 4 + j) * 8 + k] += 1;
+        }
+
+        for builder in [
+            OctreeBuilder::new(),
+            OctreeBuilder::new().capacity(3).loose_factor(0.25),
+            OctreeBuilder::new().capacity(3).project_axis(Some(2)),
+        ] {
+            let octree = builder.build_with_bounds(bounding_box.clone(), points.iter().collect());
+            let grid = octree.density_grid([3, 5, 7]);
+            assert_eq!(grid.len(), 3 * 5 * 7);
+            assert_eq!(grid.iter().sum::<usize>(), points.len());
+            if octree.config().project_axis.is_none() {
+                assert_eq!(octree.density_grid(divisions), expected);
+            } else {
+                // Projected onto the min surface along z, every column counts its points in its first slice.
+                let mut projected = vec![0; 64];
+                for (column, count) in expected.chunks(8).zip(projected.chunks_mut(8)) {
+                    count[0] = column.iter().sum();
+                }
+                assert_eq!(octree.density_grid(divisions), projected);
+            }
+            assert_eq!(octree.density_grid([1, 1, 1]), vec![points.len()]);
+            assert!(octree.density_grid([4, 0, 4]).is_empty());
+        }
+        assert_eq!(
+            Octree::<Point3D>::default().density_grid([2, 2, 2]),
+            vec![0; 8]
+        );
+    }
+
+    #[test]
+    /// Should move points, and leave the tree unchanged if the new location is out of bounds, the new point is already
+    /// recorded, or the old point is not recorded.
+    fn test_octree_update() {
+        let points: Vec<Point3D> = (0..20)
+            .map(|i| Point3D::new(i as f32, (i * 7 % 20) as f32, (i * 3 % 20) as f32))
+            .collect();
+        let moved = Point3D::new(12.5, 3.5, 7.5);
+        let outside = Point3D::new(12.5, 3.5, 27.5);
+        let missing = Point3D::new(1.5, 1.5, 1.5);
+        let taken = points[3].clone();
+        let mut octree = Octree::new(points.iter().collect());
+        let expected = Octree::new(points.iter().collect());
+
+        // Out of bounds, so the old point is kept where it was.
+        assert!(!octree.update(&points[12], &outside));
+        assert_eq!(octree, expected);
+        assert_eq!(octree.generation(), expected.generation());
+        assert!(octree.iter().any(|point| point == &points[12]));
+
+        assert!(!octree.update(&missing, &moved));
+        assert_eq!(octree, expected);
+
+        // Already recorded, so the old point is kept too.
+        assert!(!octree.update(&points[12], &taken));
+        assert_eq!(octree, expected);
+        assert_eq!(octree.generation(), expected.generation());
+
+        assert!(octree.update(&points[12], &moved));
+        assert_eq!(octree.len(), expected.len());
+        assert!(octree.iter().all(|point| point != &points[12]));
+        assert_eq!(octree.nearest([12.0, 3.0, 7.0]), Some(&moved));
+        assert_eq!(octree.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    /// Should collect the same tree as constructing it from a vector.
+    fn test_octree_from_iter() {
+        let points = random_points(520, 100, 10.0);
+
+        let octree: Octree<Point3D> = points.iter().collect();
+        assert_eq!(octree, Octree::new(points.iter().collect()));
+
+        let octree: Octree<Point3D> = points.iter().filter(|point| point.x < 5.0).collect();
+        assert_eq!(
+            octree,
+            Octree::new(points.iter().filter(|point| point.x < 5.0).collect())
+        );
+        assert!(core::iter::empty::<&Point3D>()
+            .collect::<Octree<_>>()
+            .is_empty());
+    }
+
+    #[test]
+    /// Should find pairs passing each other between frames, and the same pairs as comparing all pairs.
+    fn test_octree_swept_pairs_within() {
+        // Tunnel through each other, close at the end, far apart all the time, and not moving.
+        let prev_particles = [
+            Particle {
+                id: 0,
+                location: [0.0, 0.0, 0.0],
+            },
+            Particle {
+                id: 1,
+                location: [10.0, 0.0, 0.0],
+            },
+            Particle {
+                id: 2,
+                location: [0.0, 10.0, 0.0],
+            },
+            Particle {
+                id: 3,
+                location: [8.0, 10.0, 0.0],
+            },
+            Particle {
+                id: 4,
+                location: [0.0, 0.0, 10.0],
+            },
+            Particle {
+                id: 5,
+                location: [100.0, 100.0, 100.0],
+            },
+        ];
+        let particles = [
+            Particle {
+                id: 0,
+                location: [10.0, 0.0, 0.0],
+            },
+            Particle {
+                id: 1,
+                location: [0.0, 0.5, 0.0],
+            },
+            Particle {
+                id: 2,
+                location: [5.0, 10.0, 0.0],
+            },
+            Particle {
+                id: 3,
+                location: [5.5, 10.0, 0.0],
+            },
+            Particle {
+                id: 4,
+                location: [0.0, 0.0, 20.0],
+            },
+            // Not recorded by the previous frame, so it does not move.
+            Particle {
+                id: 6,
+                location: [0.0, 0.0, 19.0],
+            },
+            Particle {
+                id: 5,
+                location: [100.0, 100.0, 100.0],
+            },
+        ];
+        let prev = Octree::new(prev_particles.iter().collect());
+        let octree = Octree::new(particles.iter().collect());
+
+        let mut pairs: Vec<(usize, usize)> = octree
+            .swept_pairs_within(&prev, 1.0)
+            .into_iter()
+            .map(|(a, b)| (a.id.min(b.id), a.id.max(b.id)))
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(0, 1), (2, 3), (4, 6)]);
+        assert!(octree.swept_pairs_within(&prev, -1.0).is_empty());
+        assert!(octree.swept_pairs_within(&prev, f32::NAN).is_empty());
+
+        let numbers = random_numbers(1701, 6 * 300);
+        let prev_particles: Vec<Particle> = (0..300)
+            .map(|id| Particle {
+                id,
+                location: [
+                    numbers[6 * id] * 100.0,
+                    numbers[6 * id + 1] * 100.0,
+                    numbers[6 * id + 2] * 100.0,
+                ],
+            })
+            .collect();
+        let particles: Vec<Particle> = prev_particles
+            .iter()
+            .map(|particle| Particle {
+                id: particle.id,
+                location: [
+                    particle.location[0] + numbers[6 * particle.id + 3] * 20.0 - 10.0,
+                    particle.location[1] + numbers[6 * particle.id + 4] * 20.0 - 10.0,
+                    particle.location[2] + numbers[6 * particle.id + 5] * 20.0 - 10.0,
+                ],
+            })
+            .collect();
+        let prev = Octree::new(prev_particles.iter().collect());
+        let octree = Octree::new(particles.iter().collect());
+
+        let mut recorded = HashSet::new();
+        prev.root.for_each(&mut |particle| {
+            recorded.insert(particle.id);
+        });
+        let mut sweeps = Vec::new();
+        octree.root.for_each(&mut |particle| {
+            let prev_particle = &prev_particles[particle.id];
+            let start = if recorded.contains(&particle.id) {
+                prev_particle.location
+            } else {
+                particle.location
+            };
+            sweeps.push(Sweep {
+                index: particle.id,
+                point: particle,
+                start,
+                end: particle.location,
+            });
+        });
+        let mut expected = Vec::new();
+        for a in &sweeps {
+            for b in &sweeps {
+                if a.index < b.index && closest_approach_sq(a, b) <= 25.0 {
+                    expected.push((a.index, b.index));
+                }
+            }
+        }
+        expected.sort();
+
+        let mut pairs: Vec<(usize, usize)> = octree
+            .swept_pairs_within(&prev, 5.0)
+            .into_iter()
+            .map(|(a, b)| (a.id.min(b.id), a.id.max(b.id)))
+            .collect();
+        pairs.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    /// Should find the same points as query, each with the bounding box of the node storing it.
+    fn test_octree_query_with_cells() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let octree = Octree::new(points.iter().collect());
+        let splitted = octree.root.bounding_box.split();
+
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(3.0, 3.0, 3.0), Point3D::new(9.5, 9.5, 9.5)]
+                .iter()
+                .collect(),
+        );
+        let mut results = octree.query_with_cells(&bounding_box);
+        results.sort_by(|a, b| compare_locations(&a.0.get_location(), &b.0.get_location()));
+
+        assert_eq!(
+            results,
+            vec![
+                (&points[4], splitted[0].clone()),
+                (&points[5], splitted[0].clone()),
+                (&points[6], splitted[7].clone()),
+                (&points[7], splitted[7].clone()),
+                (&points[8], splitted[7].clone()),
+                (&points[9], splitted[7].clone()),
+            ]
+        );
+        assert_eq!(
+            results
+                .iter()
+                .map(|(point, _)| *point)
+                .collect::<HashSet<_>>(),
+            octree.query(&bounding_box)
+        );
+    }
+
+    #[test]
+    /// Should double the root towards points in every direction, keeping the old root as the right child.
+    fn test_octree_insert_growing_double() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+
+        // The root is grown to cover the max corner.
+        let size = 10.0f32.next_up();
+        for octant in 0..8 {
+            // The old root is in the upper half on an axis if the point is below it.
+            let mut location = [0.0; 3];
+            let mut min = [0.0; 3];
+            let mut max = [0.0; 3];
+            for i in 0..3 {
+                if octant & (1 << i) == 0 {
+                    location[i] = 13.0;
+                    max[i] = 2.0 * size;
+                } else {
+                    location[i] = -3.0;
+                    min[i] = -size;
+                    max[i] = size;
+                }
+            }
+            let point = Point3D::new(location[0], location[1], location[2]);
+
+            let mut octree = Octree::new(points.iter().collect());
+            let old_root_points = octree.root.points.clone();
+            assert!(octree.insert_growing(&point));
+
+            assert_eq!(octree.root.bounding_box, BoundingBox { min, max });
+            let old_root = &octree.root.children.as_ref().unwrap()[octant];
+            assert_eq!(old_root.bounding_box.min, [0.0; 3]);
+            assert_eq!(old_root.bounding_box.max, [size; 3]);
+            assert_eq!(old_root.points, old_root_points);
+            assert!(old_root.splitted);
+            // The new point goes to the child opposite to the old root.
+            assert!(octree.root.points.is_empty());
+            assert_eq!(
+                octree.root.children.as_ref().unwrap()[7 - octant].points,
+                PointSet::from_iter([&point])
+            );
+            assert_eq!(octree.check_invariants(), Ok(()));
+            assert_eq!(octree.generation(), 2);
+        }
+
+        // Several doublings.
+        let point = Point3D::new(35.0, 5.0, 5.0);
+        let infinite = Point3D::new(f32::INFINITY, 5.0, 5.0);
+        let nan = Point3D::new(f32::NAN, 5.0, 5.0);
+        let mut octree = Octree::new(points.iter().collect());
+        assert!(octree.insert_growing(&point));
+        assert_eq!(octree.root.bounding_box.min, [0.0; 3]);
+        assert_eq!(octree.root.bounding_box.max, [4.0 * size; 3]);
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        assert!(!octree.insert_growing(&infinite));
+        assert!(!octree.insert_growing(&nan));
+    }
+
+    #[test]
+    /// Should grow the root just enough with the fit policy, or while it is not splitted.
+    fn test_octree_insert_growing_fit() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let point1 = Point3D::new(12.0, -1.0, 5.0);
+        let point2 = Point3D::new(5.0, 5.0, 5.0);
+
+        let mut octree = OctreeBuilder::new()
+            .growth_policy(GrowthPolicy::Fit)
+            .build(points.iter().collect());
+        assert!(octree.insert_growing(&point1));
+        assert_eq!(octree.root.bounding_box.min, [0.0, -1.0, 0.0]);
+        assert_eq!(
+            octree.root.bounding_box.max,
+            [12.0f32.next_up(), 10.0f32.next_up(), 10.0f32.next_up()]
+        );
+        let mut stored = HashSet::new();
+        octree.root.for_each(&mut |point| {
+            stored.insert(point);
+        });
+        assert_eq!(stored.len(), points.len() + 1);
+        assert!(stored.contains(&point1));
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        // The root of an empty tree is not splitted.
+        let mut octree: Octree<Point3D> = Octree::default();
+        assert!(octree.insert_growing(&point1));
+        assert!(octree.insert_growing(&point2));
+        assert_eq!(octree.root.bounding_box.min, [5.0, -1.0, 5.0]);
+        assert_eq!(
+            octree.root.bounding_box.max,
+            [12.0f32.next_up(), 5.0f32.next_up(), 5.0f32.next_up()]
+        );
+        assert_eq!(octree.root.points, PointSet::from_iter([&point1, &point2]));
+    }
+
+    #[test]
+    /// Should find the same nearest points as a brute force search.
+    fn test_octree_k_nearest() {
+        let numbers = random_numbers(414, 3 * 500);
+        let points = random_points(414, 500, 100.0);
+        let octree = Octree::new(points.iter().collect());
+        let mut stored: Vec<&Point3D> = Vec::new();
+        octree.root.for_each(&mut |point| stored.push(point));
+
+        for location in numbers
+            .chunks(3)
+            .take(20)
+            .map(|c| [c[2] * 120.0 - 10.0, c[0] * 100.0, c[1] * 100.0])
+        {
+            stored.sort_by(|a, b| {
+                distance_sq(&a.get_location(), &location)
+                    .total_cmp(&distance_sq(&b.get_location(), &location))
+            });
+            for k in [1, 5, 40] {
+                let nearest = octree.k_nearest(&location, k);
+                assert_eq!(nearest.len(), k);
+                for (found, expected) in nearest.iter().zip(&stored) {
+                    assert_eq!(
+                        distance_sq(&found.get_location(), &location),
+                        distance_sq(&expected.get_location(), &location)
+                    );
+                }
+            }
+        }
+
+        assert!(octree.k_nearest(&[0.0; 3], 0).is_empty());
+        assert_eq!(octree.k_nearest(&[0.0; 3], 1000).len(), stored.len());
+        let octree: Octree<Point3D> = Octree::default();
+        assert!(octree.k_nearest(&[0.0; 3], 3).is_empty());
+    }
+
+    #[test]
+    /// Should return the same results as single queries, in input order.
+    fn test_octree_k_nearest_batch() {
+        let numbers = random_numbers(4140, 3 * 500);
+        let points = random_points(4140, 500, 1.0);
+        let octree = Octree::new(points.iter().collect());
+        let locations: Vec<[f32; 3]> = numbers.chunks(3).map(|c| [c[1], c[2], c[0]]).collect();
+
+        let results = octree.k_nearest_batch(&locations, 3);
+        assert_eq!(results.len(), locations.len());
+        for (location, result) in locations.iter().zip(&results) {
+            assert_eq!(result, &octree.k_nearest(location, 3));
+        }
+        assert!(octree.k_nearest_batch(&[], 3).is_empty());
+        assert!(octree
+            .k_nearest_batch(&locations, 0)
+            .iter()
+            .all(Vec::is_empty));
+
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(octree.k_nearest_batch_parallel(&locations, 3), results);
+            assert!(octree.k_nearest_batch_parallel(&[], 3).is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    /// Should find the same points as the sequential query.
+    fn test_octree_query_parallel() {
+        let points = random_points(518, 20000, 100.0);
+        let octrees = [
+            Octree::new(points.iter().collect()),
+            OctreeBuilder::new()
+                .loose_factor(0.25)
+                .project_axis(Some(1))
+                .build(points.iter().collect()),
+        ];
+
+        let corners = random_numbers(5180, 6 * 20);
+        for corners in corners.chunks(6) {
+            let corners = [
+                Point3D::new(
+                    corners[0] * 120.0 - 10.0,
+                    corners[1] * 120.0 - 10.0,
+                    corners[2] * 120.0 - 10.0,
+                ),
+                Point3D::new(
+                    corners[3] * 120.0 - 10.0,
+                    corners[4] * 120.0 - 10.0,
+                    corners[5] * 120.0 - 10.0,
+                ),
+            ];
+            let bounding_box = BoundingBox::new(corners.iter().collect());
+            for octree in &octrees {
+                assert_eq!(
+                    octree.query_parallel(&bounding_box),
+                    octree.query(&bounding_box)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    /// Should build the same tree as the sequential construction.
+    fn test_octree_new_parallel() {
+        let numbers = random_numbers(517, 3 * 20000);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 100.0, c[1] * 100.0, c[2] * c[2] * 100.0))
+            .collect();
+
+        let octree = Octree::new_parallel(points.iter().collect());
+        assert_eq!(octree, Octree::new(points.iter().collect()));
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        assert_eq!(
+            Octree::new_parallel(points[..10].iter().collect()),
+            Octree::new(points[..10].iter().collect())
+        );
+        assert!(Octree::<Point3D>::new_parallel(Vec::new()).is_empty());
+    }
+
+    #[test]
+    /// Should visit every node once in depth first pre-order, with its depth.
+    fn test_octree_nodes() {
+        let points = random_points(415, 200, 1.0);
+        let octree = Octree::new(points.iter().collect());
+
+        let nodes: Vec<_> = octree.nodes().collect();
+        assert_eq!(nodes[0].bounds(), &octree.root.bounding_box);
+        assert_eq!(nodes[0].depth(), 0);
+        assert!(nodes.iter().any(|node| node.depth() > 1));
+
+        // Rebuild the same order by descending with cursors.
+        fn pre_order<'node, 'point>(
+            cursor: NodeCursor<'node, 'point, Point3D>,
+            ret: &mut Vec<(BoundingBox, usize)>,
+        ) {
+            ret.push((cursor.bounds().clone(), cursor.depth()));
+            for octant in 0..8 {
+                if let Some(child) = cursor.child(octant) {
+                    pre_order(child, ret);
+                }
+            }
+        }
+        let mut expected = Vec::new();
+        pre_order(octree.root_cursor(), &mut expected);
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|node| (node.bounds().clone(), node.depth()))
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        let count: usize = nodes.iter().map(|node| node.points().count()).sum();
+        let mut stored = 0;
+        octree.root.for_each(&mut |_| stored += 1);
+        assert_eq!(count, stored);
+    }
+
+    #[test]
+    /// Should only visit the points of nodes accepted by the predicate.
+    fn test_octree_prune_walk() {
+        let points = random_points(416, 200, 1.0);
+        let octree = Octree::new(points.iter().collect());
+
+        let mut all = HashSet::new();
+        octree.prune_walk(
+            |_| true,
+            |point| {
+                all.insert(point);
+            },
+        );
+        let mut stored = HashSet::new();
+        octree.root.for_each(&mut |point| {
+            stored.insert(point);
+        });
+        assert_eq!(all, stored);
+
+        let mut visited = 0;
+        octree.prune_walk(|_| false, |_| visited += 1);
+        assert_eq!(visited, 0);
+
+        // Only the nodes in the lower x half, besides the root.
+        let mut visited = HashSet::new();
+        octree.prune_walk(
+            |bounding_box| {
+                bounding_box.get_max()[0] <= 0.5 || bounding_box == &octree.root.bounding_box
+            },
+            |point| {
+                visited.insert(point);
+            },
+        );
+        assert!(visited
+            .iter()
+            .all(|point| point.x < 0.5 || octree.root.points.contains(point)));
+        assert!(visited.len() < stored.len());
+    }
+
+    #[test]
+    /// Should contain a single point, or coincident points, it is constructed from.
+    fn test_octree_construction_degenerate() {
+        let point1 = Point3D::new(1.0, 2.0, 3.0);
+        let octree = Octree::new(vec![&point1]);
+        assert!(octree.contains(&point1));
+        assert_eq!(octree.root.bounding_box.min, [1.0, 2.0, 3.0]);
+        assert_eq!(
+            octree.root.bounding_box.max,
+            [1.0f32.next_up(), 2.0f32.next_up(), 3.0f32.next_up()]
+        );
+
+        let particles = [
+            Particle {
+                id: 0,
+                location: [1.0, 2.0, 3.0],
+            },
+            Particle {
+                id: 1,
+                location: [1.0, 2.0, 3.0],
+            },
+        ];
+        let octree = Octree::new(particles.iter().collect());
+        assert!(particles.iter().all(|particle| octree.contains(particle)));
+
+        // The flat axis is expanded like the others.
+        let point2 = Point3D::new(0.0, 0.0, 3.0);
+        let octree = Octree::new(vec![&point2, &point1]);
+        assert!(octree.contains(&point2));
+        assert!(octree.contains(&point1));
+        assert_eq!(
+            octree.root.bounding_box.max,
+            [1.0f32.next_up(), 2.0f32.next_up(), 3.0f32.next_up()]
+        );
+        assert_eq!(octree.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    /// Should subdivide only along the remaining two axes, and ignore the collapsed one in queries.
+    fn test_octree_builder_project_axis() {
+        let numbers = random_numbers(418, 3 * 40);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0], c[1] * 100.0, c[2]))
+            .collect();
+        let octree = OctreeBuilder::new()
+            .project_axis(Some(1))
+            .build(points.iter().collect());
+        assert_eq!(octree.check_invariants(), Ok(()));
+        for node in octree.nodes() {
+            assert_eq!(node.bounds().min[1], octree.root.bounding_box.min[1]);
+            assert_eq!(node.bounds().max[1], octree.root.bounding_box.max[1]);
+            if let Some(children) = &node.node.children {
+                // Splitted into 4 quadrants, none of them in the upper half along y.
+                assert_eq!(children.len(), 4);
+                assert!((0..8).all(|octant| node.child(octant).is_some() == (octant & 2 == 0)));
+            }
+        }
+
+        // Any y is covered, and queries ignore y.
+        let point = Point3D::new(0.5, -1000.0, 0.5);
+        assert!(octree.covers(&point));
+        let bounding_box = BoundingBox {
+            min: [0.3, 0.0, 0.3],
+            max: [0.7, 0.0, 0.7],
+        };
+        let expected: HashSet<&Point3D> = points
+            .iter()
+            .filter(|point| (0.3..0.7).contains(&point.x) && (0.3..0.7).contains(&point.z))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(octree.query(&bounding_box), expected);
+
+        let nearest = octree.k_nearest(&[0.5, 1000.0, 0.5], 1)[0];
+        let closest = points
+            .iter()
+            .min_by(|a, b| {
+                let a = (a.x - 0.5).powi(2) + (a.z - 0.5).powi(2);
+                let b = (b.x - 0.5).powi(2) + (b.z - 0.5).powi(2);
+                a.total_cmp(&b)
+            })
+            .unwrap();
+        assert_eq!(nearest, closest);
+
+        // Growth leaves the collapsed axis alone.
+        let far = Point3D::new(3.0, 5000.0, -1.0);
+        let mut octree = OctreeBuilder::new()
+            .project_axis(Some(1))
+            .build(points.iter().collect());
+        assert!(octree.insert_growing(&point));
+        assert!(octree.insert_growing(&far));
+        assert_eq!(octree.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "project axis must be 0, 1 or 2")]
+    /// Should reject an axis which does not exist.
+    fn test_octree_builder_project_axis_invalid() {
+        let _ = OctreeBuilder::new().project_axis(Some(3));
+    }
+
+    #[test]
+    /// Should return the tight bounds of exactly the points a query returns.
+    fn test_octree_bounds_of_query() {
+        let points = random_points(419, 40, 1.0);
+        let octree = Octree::new(points.iter().collect());
+
+        let bounding_box = BoundingBox {
+            min: [0.3, 0.3, 0.3],
+            max: [0.7, 0.7, 0.7],
+        };
+        let found = octree.query(&bounding_box);
+        assert!(found.len() > 1);
+        assert_eq!(
+            octree.bounds_of_query(&bounding_box),
+            Some(BoundingBox::new(found.into_iter().collect()))
+        );
+
+        let bounding_box = BoundingBox {
+            min: [2.0, 2.0, 2.0],
+            max: [3.0, 3.0, 3.0],
+        };
+        assert_eq!(octree.bounds_of_query(&bounding_box), None);
+    }
+
+    #[test]
+    /// Should count the leaves and find the fullest one.
+    fn test_octree_leaf_statistics() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.leaf_count(), 1);
+        assert_eq!(octree.max_leaf_occupancy(), 0);
+
+        let points = random_points(420, 200, 1.0);
+        let octree = Octree::new(points.iter().collect());
+
+        let mut splitted = 0;
+        let mut occupancy = 0;
+        for node in octree.nodes() {
+            if node.is_leaf() {
+                occupancy = occupancy.max(node.points().count());
+            } else {
+                splitted += 1;
+            }
         }
+        // Every split turns one leaf into 8.
+        assert_eq!(octree.leaf_count(), 1 + 7 * splitted);
+        assert_eq!(octree.max_leaf_occupancy(), occupancy);
+        assert!(occupancy <= 8);
     }
-}
 
-// Implement PartialEq and Eq to make testing easier.
-impl<'point, L> PartialEq for Octree<'point, L>
-where
-    L: Locatable + Eq + Hash,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.root == other.root
-    }
-}
+    #[test]
+    /// Should hold the points of both trees once, growing to cover the other tree.
+    fn test_octree_merge() {
+        let points = random_points(421, 100, 1.0);
+        // The second chunk is shifted along x, and shares some points with the first.
+        let shifted: Vec<Point3D> = points
+            .iter()
+            .map(|point| Point3D::new(point.x + 1.0, point.y, point.z))
+            .collect();
+        let first: Vec<&Point3D> = points.iter().collect();
+        let second: Vec<&Point3D> = shifted.iter().chain(&points[..20]).collect();
 
-impl<'point, L> Eq for Octree<'point, L> where L: Locatable + Eq + Hash {}
+        let mut octree = Octree::new(first.clone());
+        let mut expected = HashSet::new();
+        octree.root.for_each(&mut |point| {
+            expected.insert(point);
+        });
+        let other = Octree::new(second);
+        other.root.for_each(&mut |point| {
+            expected.insert(point);
+        });
 
-// Implement PartialEq and Eq to make testing easier.
-impl<'point, L> PartialEq for TreeNode<'point, L>
-where
-    L: Locatable + Eq + Hash,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.children == other.children
-            && self.bounding_box == other.bounding_box
-            && self.points == other.points
-            && self.capacity == other.capacity
-            && self.splitted == other.splitted
+        octree.merge(other);
+        let mut merged = Vec::new();
+        octree.root.for_each(&mut |point| merged.push(point));
+        assert_eq!(merged.len(), expected.len());
+        assert_eq!(merged.into_iter().collect::<HashSet<_>>(), expected);
+        assert!(shifted.iter().all(|point| octree.covers(point)));
+        assert_eq!(octree.check_invariants(), Ok(()));
     }
-}
 
-impl<'point, L> Eq for TreeNode<'point, L> where L: Locatable + Eq + Hash {}
+    #[test]
+    /// Should copy the tree, so changing the copy leaves the original unchanged.
+    fn test_octree_clone() {
+        let points = random_points(532, 100, 1.0);
+        let mut octree = Octree::new(points[..50].iter().collect());
+        octree.on_change(|_| {});
+        octree.insert(&points[50]);
+        let original = Octree::new(points[..51].iter().collect());
 
-impl<'point, L> TreeNode<'point, L>
-where
-    L: Locatable + Eq + Hash,
-{
-    fn new(points: Vec<&'point L>) -> Self {
-        let mut tree_node: TreeNode<L> = TreeNode {
-            // So the created bounding box contains every point in points.
-            bounding_box: BoundingBox::new(points.clone()),
-            ..Default::default()
-        };
+        let mut snapshot = octree.clone();
+        assert_eq!(snapshot, octree);
+        assert_eq!(snapshot.generation(), octree.generation());
+        assert!(snapshot.on_change.is_none());
 
-        for point in points {
-            tree_node.insert(point);
+        for point in &points[51..] {
+            snapshot.insert_growing(point);
         }
+        for point in &points[..20] {
+            snapshot.delete(point);
+        }
+        assert_ne!(snapshot, octree);
+        assert_eq!(octree, original);
+        let stored: HashSet<&Point3D> = snapshot.iter().collect();
+        assert!(points[..20].iter().all(|point| !stored.contains(point)));
+        assert!(points[51..].iter().all(|point| stored.contains(point)));
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert_eq!(snapshot.check_invariants(), Ok(()));
+    }
 
-        tree_node
+    #[test]
+    /// Should snap points to the nearest grid location, grouping the points of a cell.
+    fn test_octree_new_quantized() {
+        let points = [
+            Point3D::new(0.4, -0.4, 0.0),
+            Point3D::new(0.6, 1.4, 0.0),
+            Point3D::new(-0.1, 0.2, 0.1),
+            Point3D::new(1.2, 1.0, 0.3),
+            Point3D::new(3.9, 2.1, 1.6),
+        ];
+        let cells = Quantized::group(points.iter().collect(), [1.0, 1.0, 0.5]);
+        let octree = Octree::new_quantized(&cells);
+
+        let mut stored = Vec::new();
+        octree
+            .root
+            .for_each(&mut |cell| stored.push((cell.get_location(), cell.points().to_vec())));
+        stored.sort_by(|a, b| compare_locations(&a.0, &b.0));
+        assert_eq!(
+            stored,
+            [
+                ([0.0, 0.0, 0.0], vec![&points[0], &points[2]]),
+                ([1.0, 1.0, 0.0], vec![&points[1]]),
+                ([1.0, 1.0, 0.5], vec![&points[3]]),
+                ([4.0, 2.0, 1.5], vec![&points[4]]),
+            ]
+        );
+        assert!(!octree.covers(&Quantized {
+            location: [4.5, 2.0, 1.5],
+            points: Vec::new(),
+        }));
     }
 
-    fn insert(&mut self, point: &'point L) -> bool {
-        // Do nothing is the point won't be covered by current node.
-        if !self.covers(point) {
-            return false;
-        }
-        // Yes, we have enough remaining space.
-        if self.points.len() < self.capacity {
-            self.points.insert(point);
-            true
-        } else {
-            // Already have 8 points, should split further.
-            if !self.splitted {
-                self.split();
-            }
+    #[test]
+    #[should_panic(expected = "cell size must be positive")]
+    /// Should reject a cell size which is not positive.
+    fn test_octree_new_quantized_invalid() {
+        let point = Point3D::new(0.0, 0.0, 0.0);
+        let _ = Quantized::group(vec![&point], [1.0, 0.0, 1.0]);
+    }
 
-            // Ask all children: does this point belongs to you?
-            for child in self.children.as_mut().unwrap().iter_mut() {
-                let child = child.as_mut();
-                if child.insert(point) {
-                    return true;
-                }
+    #[test]
+    /// Should only split along the long axes of flat data, allocating only the children in use.
+    fn test_octree_builder_split_ratio() {
+        let numbers = random_numbers(423, 3 * 300);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 100.0, c[1] * 100.0, c[2]))
+            .collect();
+        let octree = OctreeBuilder::new()
+            .split_ratio(0.5)
+            .build(points.iter().collect());
+        assert_eq!(octree.check_invariants(), Ok(()));
+
+        let mut splitted = 0;
+        for node in octree.nodes() {
+            if node.is_leaf() {
+                continue;
             }
-            false
+            splitted += 1;
+            // Flat nodes are splitted in 4, the small ones may be splitted along z as well.
+            let count = (0..8)
+                .filter(|octant| node.child(*octant).is_some())
+                .count();
+            let size = node.bounds().get_max()[0] - node.bounds().get_min()[0];
+            assert_eq!(count, if size > 2.0 { 4 } else { 8 });
+            // Only the children in use are allocated.
+            assert_eq!(node.node.children.as_ref().unwrap().len(), count);
+        }
+        assert!(splitted > 1);
+        assert_eq!(octree.leaf_count(), octree.nodes().count() - splitted);
+
+        // Every point can be found, and no unused cell is reported.
+        for point in points.iter() {
+            let bounding_box = BoundingBox {
+                min: [point.x - 0.01, point.y - 0.01, point.z - 0.01],
+                max: [point.x + 0.01, point.y + 0.01, point.z + 0.01],
+            };
+            assert!(octree.query(&bounding_box).contains(point));
         }
+        let cells = octree.cells_crossed_by_plane(&[0.0, 0.0, 1.0, -0.5]);
+        assert!(!cells.is_empty());
+        assert!(cells.iter().all(|cell| cell.volume() > 0.0));
+
+        // The default splits along every axis.
+        let octree = Octree::new(points.iter().collect());
+        assert!(octree
+            .nodes()
+            .filter(|node| !node.is_leaf())
+            .all(|node| (0..8).all(|octant| node.child(octant).is_some())));
     }
 
-    fn split(&mut self) {
-        self.splitted = true;
+    #[test]
+    /// Should find the distance to the nearest point, as a brute force search.
+    fn test_octree_nearest_distance() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.nearest_distance(&[0.0; 3]), None);
 
-        let splitted_bounding_boxes = self.bounding_box.split();
-
-        // Place holder for the 8 new nodes.
-        let mut children = [
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-            Box::<TreeNode<'_, L>>::default(),
-        ];
+        let numbers = random_numbers(424, 3 * 300);
+        let points = random_points(424, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let mut stored: Vec<&Point3D> = Vec::new();
+        octree.root.for_each(&mut |point| stored.push(point));
 
-        // Assign new bounding box to placeholders.
-        for (i, splitted_bounding_box) in splitted_bounding_boxes.iter().enumerate() {
-            children[i].bounding_box = splitted_bounding_box.clone();
+        for location in numbers
+            .chunks(3)
+            .take(30)
+            .map(|c| [c[1] * 12.0 - 1.0, c[2] * 10.0, c[0] * 10.0])
+        {
+            let expected = stored
+                .iter()
+                .map(|point| distance_sq(&point.get_location(), &location))
+                .fold(f32::INFINITY, f32::min)
+                .sqrt();
+            assert_eq!(octree.nearest_distance(&location), Some(expected));
         }
-
-        self.children = Some(children);
     }
 
-    fn covers(&self, point: &L) -> bool {
-        self.bounding_box.covers(&point.get_location())
-    }
+    #[test]
+    /// Should average the distance from every point to its nearest other point, as a brute force search.
+    fn test_octree_mean_nearest_neighbor_distance() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.mean_nearest_neighbor_distance(), None);
+        let point = Point3D::new(1.0, 2.0, 3.0);
+        let octree = Octree::new(vec![&point]);
+        assert_eq!(octree.mean_nearest_neighbor_distance(), None);
+
+        let points = random_points(425, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let mut stored: Vec<&Point3D> = Vec::new();
+        octree.root.for_each(&mut |point| stored.push(point));
+
+        let mut total = 0.0;
+        for (i, point) in stored.iter().enumerate() {
+            total += stored
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| i != *j)
+                .map(|(_, other)| distance_sq(&point.get_location(), &other.get_location()))
+                .fold(f32::INFINITY, f32::min)
+                .sqrt() as f64;
+        }
+        let expected = (total / stored.len() as f64) as f32;
+        let mean = octree.mean_nearest_neighbor_distance().unwrap();
+        assert!((mean - expected).abs() < 1e-5);
 
-    fn contains(&self, point: &L) -> bool {
-        self.points.contains(point)
+        // Coincident but distinct points are each other's nearest.
+        let particles = [
+            Particle {
+                id: 0,
+                location: [1.0, 1.0, 1.0],
+            },
+            Particle {
+                id: 1,
+                location: [1.0, 1.0, 1.0],
+            },
+        ];
+        let octree = Octree::new(particles.iter().collect());
+        assert_eq!(octree.mean_nearest_neighbor_distance(), Some(0.0));
     }
 
-    fn delete(&mut self, point: &L) -> bool {
-        // HashSet returns false if removes a non-existing element.
-        let ret = self.points.remove(point);
-        if ret {
-            if let Some(children) = &mut self.children {
-                for child in children.iter_mut() {
-                    child.delete(point);
+    #[test]
+    /// Should find the mean location of all points, of a sub tree, and weighted by mass.
+    fn test_octree_centroid() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.centroid(), None);
+        assert_eq!(octree.root_cursor().centroid(), None);
+
+        let points = random_points(535, 200, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let mean = |points: &[&Point3D]| {
+            let mut sum = [0.0f64; 3];
+            for point in points {
+                for (sum, coordinate) in sum.iter_mut().zip(point.get_location()) {
+                    *sum += f64::from(coordinate);
+                }
+            }
+            sum.map(|sum| (sum / points.len() as f64) as f32)
+        };
+
+        let stored: Vec<&Point3D> = octree.iter().collect();
+        assert_eq!(octree.centroid(), Some(mean(&stored)));
+        assert_eq!(octree.root_cursor().centroid(), octree.centroid());
+        assert_eq!(octree.weighted_centroid(|_| 2.0), octree.centroid());
+        assert_eq!(octree.weighted_centroid(|_| 0.0), None);
+
+        let child = (0..8)
+            .filter_map(|octant| octree.root_cursor().child(octant))
+            .find(|child| !child.is_leaf())
+            .unwrap();
+        fn collect<'point>(
+            cursor: NodeCursor<'_, 'point, Point3D>,
+            ret: &mut Vec<&'point Point3D>,
+        ) {
+            ret.extend(cursor.points());
+            for octant in 0..8 {
+                if let Some(child) = cursor.child(octant) {
+                    collect(child, ret);
                 }
             }
-            // TODO: 7 children contain nothing and only one child contains some point, then re-merge them into 1 node
-            // to reduce tree depth.
-            // Possible performance improvement?
         }
-        ret
+        let mut sub_tree = Vec::new();
+        collect(child, &mut sub_tree);
+        assert!(sub_tree.len() > 8);
+        assert_eq!(child.centroid(), Some(mean(&sub_tree)));
+
+        let heavy = stored[0];
+        let centroid = octree
+            .weighted_centroid(|point| if point == heavy { 1.0 } else { 0.0 })
+            .unwrap();
+        assert_eq!(centroid, heavy.get_location());
     }
 
-    fn query(&self, bounding_box: &BoundingBox) -> HashSet<&L> {
-        // Place holder for the query answer.
-        let mut ret = HashSet::new();
+    #[test]
+    /// Should visit the points a query returns, and keep changes to their mutable state without disturbing the tree.
+    fn test_octree_for_each_in_box() {
+        /// A point with a velocity, which is not part of its identity.
+        #[derive(Debug)]
+        struct Body {
+            id: usize,
+            location: [f32; 3],
+            velocity: Cell<f32>,
+        }
 
-        // If they do not overlap, then we won't find any points in this sub tree which is covered by the query bounding
-        // box.
-        if !self.bounding_box.overlaps(bounding_box) {
-            return ret;
+        impl PartialEq for Body {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
         }
-        for point in &self.points {
-            if bounding_box.covers(&point.get_location()) {
-                // point is of type &&L.
-                ret.insert(*point);
+
+        impl Eq for Body {}
+
+        impl Hash for Body {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
             }
         }
 
-        // Recursively ask sub tree if they have something covered by the query bounding box.
-        if self.splitted {
-            for child in self.children.as_ref().unwrap().iter() {
-                ret.extend(child.query(bounding_box));
+        impl Locatable for Body {
+            fn get_location(&self) -> [f32; 3] {
+                self.location
             }
         }
-        ret
-    }
 
-    fn overlaps(&self, bounding_box: &BoundingBox) -> bool {
-        self.bounding_box.overlaps(bounding_box)
-    }
-}
+        let numbers = random_numbers(536, 3 * 300);
+        let bodies: Vec<Body> = numbers
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Body {
+                id,
+                location: [c[0] * 10.0, c[1] * 10.0, c[2] * 10.0],
+                velocity: Cell::new(0.0),
+            })
+            .collect();
+        let octree = Octree::new(bodies.iter().collect());
+        let before = octree.clone();
+        let bounding_box = BoundingBox {
+            min: [2.0, 3.0, 1.0],
+            max: [7.0, 6.0, 9.0],
+        };
 
-impl<'point, L> Default for TreeNode<'point, L>
-where
-    L: Locatable + Eq + Hash,
-{
-    fn default() -> Self {
-        Self {
-            children: None,
-            bounding_box: BoundingBox::default(),
-            points: HashSet::new(),
-            capacity: 8,
-            splitted: false,
+        let mut visited = HashSet::new();
+        octree.for_each_in_box(&bounding_box, |body| {
+            body.velocity.set(body.velocity.get() + 1.0);
+            visited.insert(body.id);
+        });
+        let ids = |octree: &Octree<Body>| -> HashSet<usize> {
+            octree
+                .query(&bounding_box)
+                .iter()
+                .map(|body| body.id)
+                .collect()
+        };
+        let expected = ids(&octree);
+        assert!(!expected.is_empty());
+        assert_eq!(visited, expected);
+        for body in &bodies {
+            let velocity = if visited.contains(&body.id) { 1.0 } else { 0.0 };
+            assert_eq!(body.velocity.get(), velocity);
         }
+        assert_eq!(octree, before);
+        assert_eq!(ids(&octree), expected);
+        assert_eq!(octree.check_invariants(), Ok(()));
     }
-}
 
-impl BoundingBox {
-    /// Construct a new [BoundingBox] which can hold all given points.
-    /// # Example
-    /// ```
-    /// use octree::point::Point3D;
-    /// use octree::BoundingBox;
-    ///
-    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
-    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
-    /// let bounding_box = BoundingBox::new(vec![point1, point2].iter().collect());
-    /// ```
-    pub fn new<L>(points: Vec<&L>) -> Self
-    where
-        L: Locatable,
-    {
-        let mut min = [f32::MAX, f32::MAX, f32::MAX];
-        let mut max = [f32::MIN, f32::MIN, f32::MIN];
+    #[test]
+    /// Should yield every point by increasing distance, as a brute force search, and stop early without opening the
+    /// remaining nodes.
+    fn test_octree_nearest_iter() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.nearest_iter([0.0; 3]).next(), None);
 
-        // Linear search to find the min and max point.
-        for point in points {
-            let location = point.get_location();
-            for i in 0..3 {
-                min[i] = min[i].min(location[i]);
-                max[i] = max[i].max(location[i]);
+        let points = random_points(537, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let stored: Vec<&Point3D> = octree.iter().collect();
+
+        for location in [[5.0, 5.0, 5.0], [0.0, 10.0, 0.0], [-3.0, 12.0, 5.0]] {
+            let nearest: Vec<(&Point3D, f32)> = octree.nearest_iter(location).collect();
+            assert_eq!(nearest.len(), stored.len());
+            assert!(nearest.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            for (point, distance) in &nearest {
+                assert_eq!(
+                    *distance,
+                    distance_sq(&point.get_location(), &location).sqrt()
+                );
             }
+            let mut expected: Vec<f32> = stored
+                .iter()
+                .map(|point| distance_sq(&point.get_location(), &location).sqrt())
+                .collect();
+            expected.sort_by(f32::total_cmp);
+            let distances: Vec<f32> = nearest.iter().map(|(_, distance)| *distance).collect();
+            assert_eq!(distances, expected);
+
+            let k_nearest: Vec<&Point3D> =
+                nearest.iter().take(5).map(|(point, _)| *point).collect();
+            assert_eq!(octree.k_nearest(&location, 5), k_nearest);
         }
 
-        BoundingBox { min, max }
+        let mut iter = NearestIter {
+            root: &octree.root,
+            config: &octree.config,
+            location: [5.0, 5.0, 5.0],
+            queue: BinaryHeap::from([Reverse(Nearest {
+                distance_sq: 0.0,
+                item: Candidate::Node(&octree.root),
+            })]),
+        };
+        iter.next();
+        assert!(iter.queue.len() < stored.len());
     }
 
-    /// Check if a point can be covered by this [BoundingBox].
-    /// # Example
-    /// ```
-    /// use octree::point::Point3D;
-    /// use octree::{BoundingBox, Locatable};
-    ///
-    /// let point1 = Point3D::new(0.0, 0.0, 0.0);
-    /// let point2 = Point3D::new(10.0, 10.0, 10.0);
-    /// let point3 = Point3D::new(5.0, 5.0, 5.0);
-    /// let point4 = Point3D::new(20.0, 20.0, 20.0);
-    /// let bounding_box = BoundingBox::new(vec![point1, point2].iter().collect());
-    ///
-    /// assert!(bounding_box.covers(&point3.get_location()));
-    /// assert!(!bounding_box.covers(&point4.get_location()));
-    /// ```
-    pub fn covers(&self, point: &[f32; 3]) -> bool {
-        self.min[0] <= point[0]
-            && point[0] < self.max[0]
-            && self.min[1] <= point[1]
-            && point[1] < self.max[1]
-            && self.min[2] <= point[2]
-            && point[2] < self.max[2]
-    }
+    #[test]
+    /// Should find the points within a radius, as a brute force search.
+    fn test_octree_query_radius() {
+        let points = random_points(502, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let mut stored: Vec<&Point3D> = Vec::new();
+        octree.root.for_each(&mut |point| stored.push(point));
 
-    /// Check if two [BoundingBox]es overlap.
-    pub fn overlaps(&self, other: &BoundingBox) -> bool {
-        let other_point1 = other.min;
-        let other_point2 = [other.min[0], other.min[1], other.max[2]];
-        let other_point3 = [other.min[0], other.max[1], other.min[2]];
-        let other_point4 = [other.min[0], other.max[1], other.max[2]];
-        let other_point5 = [other.max[0], other.min[1], other.min[2]];
-        let other_point6 = [other.max[0], other.min[1], other.max[2]];
-        let other_point7 = [other.max[0], other.max[1], other.min[2]];
-        let other_point8 = other.max;
-        for point in [
-            other_point1,
-            other_point2,
-            other_point3,
-            other_point4,
-            other_point5,
-            other_point6,
-            other_point7,
-            other_point8,
+        for (centre, radius) in [
+            ([5.0, 5.0, 5.0], 2.0),
+            ([0.0, 10.0, 0.0], 4.5),
+            ([-3.0, 5.0, 5.0], 3.5),
         ] {
-            if self.covers(&point) {
-                return true;
+            let expected: HashSet<&Point3D> = stored
+                .iter()
+                .filter(|point| distance_sq(&point.get_location(), &centre) <= radius * radius)
+                .copied()
+                .collect();
+            assert_eq!(octree.query_radius(centre, radius), expected);
+        }
+        assert_eq!(
+            octree.query_radius([5.0, 5.0, 5.0], 100.0).len(),
+            stored.len()
+        );
+
+        let location = stored[0].get_location();
+        assert_eq!(
+            octree.query_radius(location, 0.0),
+            HashSet::from([stored[0]])
+        );
+        assert!(octree.query_radius(location, -1.0).is_empty());
+        assert!(octree.query_radius(location, f32::NAN).is_empty());
+    }
+
+    #[test]
+    /// Should match a box query for an axis aligned frustum, and skip nodes behind a plane.
+    fn test_octree_query_frustum() {
+        let points = random_points(530, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+
+        // The box from (2, 3, 1) to (7, 6, 9) as six planes facing inwards.
+        let planes = [
+            [1.0, 0.0, 0.0, -2.0],
+            [-1.0, 0.0, 0.0, 7.0],
+            [0.0, 1.0, 0.0, -3.0],
+            [0.0, -1.0, 0.0, 6.0],
+            [0.0, 0.0, 1.0, -1.0],
+            [0.0, 0.0, -1.0, 9.0],
+        ];
+        let bounding_box = BoundingBox {
+            min: [2.0, 3.0, 1.0],
+            max: [7.0, 6.0, 9.0],
+        };
+        let expected = octree.query(&bounding_box);
+        assert!(!expected.is_empty());
+        assert_eq!(octree.query_frustum(planes), expected);
+
+        // Facing away from every point.
+        let mut planes = planes;
+        planes[0] = [1.0, 0.0, 0.0, -20.0];
+        assert!(octree.query_frustum(planes).is_empty());
+
+        let mut visited = 0;
+        octree.prune_walk(
+            |bounding_box| !planes.iter().any(|plane| bounding_box.behind_plane(plane)),
+            |_| visited += 1,
+        );
+        assert_eq!(visited, 0);
+    }
+
+    #[test]
+    /// Should find the points of the coarse bounding box which match the predicate, and miss the ones outside of it.
+    fn test_octree_query_where() {
+        let points = random_points(556, 300, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let closer_to_a = |location: &[f32; 3]| {
+            distance_sq(location, &[2.0, 2.0, 2.0]) < distance_sq(location, &[8.0, 8.0, 8.0])
+        };
+
+        let everything = octree.bounds().clone();
+        let expected: HashSet<&Point3D> = points
+            .iter()
+            .filter(|point| closer_to_a(&point.get_location()))
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < points.len());
+        assert_eq!(octree.query_where(&everything, closer_to_a), expected);
+
+        // A coarse bounding box which does not cover the whole region cuts it.
+        let coarse = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [5.0, 5.0, 5.0],
+        };
+        let expected: HashSet<&Point3D> = octree
+            .query(&coarse)
+            .into_iter()
+            .filter(|point| closer_to_a(&point.get_location()))
+            .collect();
+        assert_eq!(octree.query_where(&coarse, closer_to_a), expected);
+        assert_eq!(octree.query_where(&coarse, |_| true), octree.query(&coarse));
+        assert!(octree.query_where(&everything, |_| false).is_empty());
+    }
+
+    #[test]
+    /// Should tell if another point is within a radius, as a brute force search, without matching the point itself.
+    fn test_octree_has_neighbor_within() {
+        let points = random_points(533, 200, 10.0);
+        let octree = Octree::new(points.iter().collect());
+        let stored: Vec<&Point3D> = octree.iter().collect();
+
+        for radius in [0.3, 0.8, 1.5] {
+            for point in &points {
+                let expected = stored.iter().any(|other| {
+                    *other != point
+                        && distance_sq(&other.get_location(), &point.get_location())
+                            <= radius * radius
+                });
+                assert_eq!(octree.has_neighbor_within(point, radius), expected);
             }
         }
-        false
+
+        let single = [Point3D::new(1.0, 1.0, 1.0)];
+        let octree = Octree::new(single.iter().collect());
+        assert!(!octree.has_neighbor_within(&single[0], 100.0));
+        assert!(octree.has_neighbor_within(&Point3D::new(1.0, 1.0, 2.0), 1.0));
+        assert!(!octree.has_neighbor_within(&Point3D::new(1.0, 1.0, 2.0), -1.0));
+        assert!(!octree.has_neighbor_within(&Point3D::new(1.0, 1.0, 2.0), f32::NAN));
     }
 
-    /// Getter for [BoundingBox] centre coordination.
-    pub fn get_centre(&self) -> [f32; 3] {
-        let mut ret = [0.0; 3];
-        for (i, coordinate) in ret.iter_mut().enumerate() {
-            *coordinate = (self.min[i] + self.max[i]) / 2.0;
+    #[test]
+    /// Should find every point on a ray in order along it, without searching the nodes the ray misses.
+    fn test_octree_query_ray() {
+        let points: Vec<Point3D> = (0..1000)
+            .map(|i| Point3D::new((i % 10) as f32, (i / 10 % 10) as f32, (i / 100) as f32))
+            .collect();
+        let octree = Octree::new(points.iter().collect());
+        let mut stored: Vec<&Point3D> = Vec::new();
+        octree.root.for_each(&mut |point| stored.push(point));
+
+        let hits = octree.query_ray([-1.0, 2.0, 3.0], [2.0, 0.0, 0.0], 100.0);
+        assert!(hits.len() < stored.len());
+        let on_ray: Vec<&Point3D> = hits
+            .iter()
+            .filter(|point| point.y == 2.0 && point.z == 3.0)
+            .copied()
+            .collect();
+        let expected: Vec<&Point3D> = stored
+            .iter()
+            .filter(|point| point.y == 2.0 && point.z == 3.0)
+            .copied()
+            .collect();
+        assert_eq!(on_ray.len(), expected.len());
+        assert!(on_ray.windows(2).all(|pair| pair[0].x < pair[1].x));
+        assert!(hits.windows(2).all(|pair| pair[0].x <= pair[1].x));
+
+        let hits = octree.query_ray([-1.0, 2.0, 3.0], [1.0, 0.0, 0.0], 2.5);
+        assert!(hits.iter().all(|point| point.x <= 1.5));
+        assert!(hits.contains(&&points[320]));
+        assert!(hits.contains(&&points[321]));
+
+        // Diagonally through the grid, from beyond its far corner.
+        let hits = octree.query_ray([10.0, 10.0, 10.0], [-1.0, -1.0, -1.0], 100.0);
+        for i in 0..9 {
+            assert!(hits.contains(&&points[111 * i]));
         }
-        ret
+        let index = |i: usize| hits.iter().position(|point| *point == &points[111 * i]);
+        assert!((0..8).all(|i| index(i) > index(i + 1)));
+
+        assert!(octree.query_ray([0.0; 3], [0.0; 3], 100.0).is_empty());
+        assert!(octree.query_ray([0.0; 3], [1.0, 0.0, 0.0], -1.0).is_empty());
+        assert!(octree
+            .query_ray([0.0; 3], [f32::NAN, 0.0, 0.0], 1.0)
+            .is_empty());
+        assert!(octree
+            .query_ray([-1.0, 2.0, 3.0], [-1.0, 0.0, 0.0], 100.0)
+            .is_empty());
     }
 
-    /// Getter for [BoundingBox] min corner coordination.
-    pub fn get_min(&self) -> &[f32; 3] {
-        &self.min
+    #[test]
+    /// Should find the nearest point, even if it is in a sibling of the octant holding the location.
+    fn test_octree_nearest() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.nearest([0.0; 3]), None);
+
+        // The first 8 points fill the root, the last two go to children.
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 0.0, 0.0),
+            Point3D::new(0.0, 1.0, 0.0),
+            Point3D::new(0.0, 0.0, 1.0),
+            Point3D::new(1.0, 1.0, 0.0),
+            Point3D::new(1.0, 0.0, 1.0),
+            Point3D::new(0.0, 1.0, 1.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(4.9, 9.0, 9.0),
+            Point3D::new(6.5, 9.0, 9.0),
+        ];
+        let octree = Octree::new(points.iter().collect());
+        let children = octree.root.children.as_ref().unwrap();
+        assert!(children[6].points.contains(&points[9]));
+        assert!(children[7].points.contains(&points[10]));
+
+        let location = [5.2, 9.0, 9.0];
+        assert!(children[7].bounding_box.covers(&location));
+        assert_eq!(octree.nearest(location), Some(&points[9]));
+        assert_eq!(octree.nearest([6.0, 9.0, 9.0]), Some(&points[10]));
+        assert_eq!(octree.nearest([0.1, 0.2, 0.1]), Some(&points[0]));
     }
 
-    /// Getter for [BoundingBox] max corner coordination.
-    pub fn get_max(&self) -> &[f32; 3] {
-        &self.max
+    #[test]
+    /// Should find a point within 1 + epsilon of the nearest distance, and the nearest point for an epsilon of 0.
+    fn test_octree_nearest_approx() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.nearest_approx([0.0; 3], 0.5), None);
+
+        let points = random_points(566, 2000, 10.0);
+        let octree = OctreeBuilder::new()
+            .capacity(4)
+            .build(points.iter().collect());
+
+        for location in random_numbers(5660, 3 * 100).chunks(3) {
+            let location = [
+                location[0] * 12.0 - 1.0,
+                location[1] * 12.0 - 1.0,
+                location[2] * 12.0 - 1.0,
+            ];
+            let exact = octree.nearest_distance(&location).unwrap();
+            assert_eq!(
+                octree.nearest_approx(location, 0.0),
+                octree.nearest(location)
+            );
+            assert_eq!(
+                octree.nearest_approx(location, -1.0),
+                octree.nearest(location)
+            );
+            for epsilon in [0.1, 0.5, 2.0] {
+                let approx = octree.nearest_approx(location, epsilon).unwrap();
+                let distance = distance_sq(&approx.get_location(), &location).sqrt();
+                assert!(distance <= (1.0 + epsilon) * exact * (1.0 + 1e-6));
+            }
+        }
     }
 
-    /// Split the [BoundingBox] into 8 sub [BoundingBox]es.
-    pub fn split(&self) -> [Self; 8] {
-        let centre = self.get_centre();
-        let min = self.min;
-        let max = self.max;
-        let mut ret = [
-            Self::default(),
-            Self::default(),
-            Self::default(),
-            Self::default(),
-            Self::default(),
-            Self::default(),
-            Self::default(),
-            Self::default(),
-        ];
-        // u: up, d: down, f: front, b: back, l: left, r: right.
-        // example, ulb is the left back corner in the upper layer.
-        // order: dfl, dfr, dbl, dbr, ufl, ufr, ubl, ubr.
+    #[test]
+    /// Should find the nearest point by a custom metric as a brute force search, with a pruning or a trivial bound.
+    fn test_octree_nearest_by() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.nearest_weighted([0.0; 3], [1.0; 3]), None);
 
-        // dfl
-        ret[0].min = min;
-        ret[0].max = centre;
+        let points = random_points(559, 300, 10.0);
+        let octree = OctreeBuilder::new()
+            .loose_factor(0.25)
+            .build(points.iter().collect());
+        let weights = [1.0, 0.5, 9.0];
+        let weighted = |a: &[f32; 3], b: &[f32; 3]| -> f32 {
+            (0..3)
+                .map(|i| weights[i] * (a[i] - b[i]) * (a[i] - b[i]))
+                .sum()
+        };
+        let chebyshev = |a: &[f32; 3], b: &[f32; 3]| -> f32 {
+            (0..3).map(|i| (a[i] - b[i]).abs()).fold(0.0, f32::max)
+        };
 
-        // dfr
-        ret[1].min = [centre[0], min[1], min[2]];
-        ret[1].max = [max[0], centre[1], centre[2]];
+        for location in random_numbers(5590, 3 * 50).chunks(3) {
+            let location = [
+                location[0] * 12.0 - 1.0,
+                location[1] * 12.0 - 1.0,
+                location[2] * 12.0 - 1.0,
+            ];
+            let closest = |metric: &dyn Fn(&[f32; 3], &[f32; 3]) -> f32| {
+                points
+                    .iter()
+                    .map(|point| metric(&location, &point.get_location()))
+                    .fold(f32::MAX, f32::min)
+            };
 
-        // dbl
-        ret[2].min = [min[0], centre[1], min[2]];
-        ret[2].max = [centre[0], max[1], centre[2]];
+            let nearest = octree.nearest_weighted(location, weights).unwrap();
+            assert_eq!(
+                weighted(&location, &nearest.get_location()),
+                closest(&weighted)
+            );
 
-        // dbr
-        ret[3].min = [centre[0], centre[1], min[2]];
-        ret[3].max = [max[0], max[1], centre[2]];
+            let nearest = octree
+                .nearest_by(location, chebyshev, |bounding_box, location| {
+                    chebyshev(&bounding_box.closest_point(location), location)
+                })
+                .unwrap();
+            assert_eq!(
+                chebyshev(&location, &nearest.get_location()),
+                closest(&chebyshev)
+            );
+            let nearest = octree.nearest_by(location, chebyshev, |_, _| 0.0).unwrap();
+            assert_eq!(
+                chebyshev(&location, &nearest.get_location()),
+                closest(&chebyshev)
+            );
+        }
 
-        // ufl
-        ret[4].min = [min[0], min[1], centre[2]];
-        ret[4].max = [centre[0], centre[1], max[2]];
+        // With the Euclidean metric, it is the same search as nearest.
+        let location = [3.0, 4.0, 5.0];
+        assert_eq!(
+            octree.nearest_weighted(location, [1.0; 3]),
+            octree.nearest(location)
+        );
+    }
 
-        // ufr
-        ret[5].min = [centre[0], min[1], centre[2]];
-        ret[5].max = [max[0], centre[1], max[2]];
+    #[test]
+    /// Should keep insert non-growing, and nest the old root deeper for points far away.
+    fn test_octree_insert_growing_nested() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let far = Point3D::new(35.0, 5.0, 5.0);
 
-        // ubl
-        ret[6].min = [min[0], centre[1], centre[2]];
-        ret[6].max = [centre[0], max[1], max[2]];
+        let mut octree = Octree::new(points.iter().collect());
+        let old_root = octree.root.bounding_box.clone();
+        assert!(!octree.insert(&far));
+        assert_eq!(octree.root.bounding_box, old_root);
+        assert_eq!(octree.generation(), 0);
 
-        // ubr
-        ret[7].min = centre;
-        ret[7].max = max;
+        // Doubled twice, to about 40 on every axis, and the old root is the lower half of the lower half.
+        let size = 10.0f32.next_up();
+        assert!(octree.insert_growing(&far));
+        assert_eq!(octree.root.bounding_box.min, [0.0; 3]);
+        assert_eq!(octree.root.bounding_box.max, [4.0 * size; 3]);
+        let child = &octree.root.children.as_ref().unwrap()[0];
+        let grandchild = &child.children.as_ref().unwrap()[0];
+        assert_eq!(grandchild.bounding_box, old_root);
+        assert!(grandchild.splitted);
+        assert!(octree.query_radius(far.get_location(), 0.0).contains(&far));
+        assert_eq!(octree.check_invariants(), Ok(()));
+    }
 
-        ret
+    #[test]
+    /// Should count every distinct point once, after splits.
+    fn test_octree_len() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.len(), 0);
+        assert!(octree.is_empty());
+
+        let points = random_points(505, 200, 1.0);
+        let bounds = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)];
+        let mut octree = Octree::new(bounds.iter().collect());
+        assert_eq!(octree.len(), 2);
+
+        let mut inserted = 2;
+        for point in &points {
+            if octree.insert(point) {
+                inserted += 1;
+            }
+        }
+        assert_eq!(inserted, points.len() + 2);
+        assert!(octree.root.splitted);
+        assert_eq!(octree.len(), inserted);
+        assert!(!octree.is_empty());
+
+        for point in &points[..100] {
+            assert!(octree.delete(point));
+        }
+        assert_eq!(octree.len(), inserted - 100);
+        assert!(!octree.is_empty());
+        for point in points[100..].iter().chain(&bounds) {
+            assert!(octree.delete(point));
+        }
+        assert!(octree.is_empty());
     }
-}
 
-impl Default for BoundingBox {
-    /// Construct a default [BoundingBox], covers the whole space which can be represented by [f32].
-    fn default() -> Self {
-        Self {
-            // Will be used in min() and max() function later so the initial value of min is f32::MAX.
-            min: [f32::MAX, f32::MAX, f32::MAX],
-            max: [f32::MIN, f32::MIN, f32::MIN],
+    #[test]
+    /// Should remove all points but keep the extent, reporting every removed point.
+    fn test_octree_clear() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(10.0, 10.0, 10.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+            Point3D::new(3.0, 3.0, 3.0),
+            Point3D::new(4.0, 4.0, 4.0),
+            Point3D::new(6.0, 6.0, 6.0),
+            Point3D::new(7.0, 7.0, 7.0),
+            Point3D::new(8.0, 8.0, 8.0),
+            Point3D::new(9.0, 9.0, 9.0),
+        ];
+        let mut octree = Octree::new(points.iter().collect());
+        let bounding_box = octree.root.bounding_box.clone();
+        assert!(octree.root.splitted);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = events.clone();
+        octree.on_change(move |event| recorder.lock().unwrap().push(event.map(Point3D::clone)));
+
+        octree.clear();
+        assert_eq!(octree.len(), 0);
+        assert!(octree.query(&bounding_box).is_empty());
+        assert_eq!(octree.root.bounding_box, bounding_box);
+        assert!(!octree.root.splitted);
+        assert!(octree.root.children.is_none());
+        assert_eq!(octree.generation(), 1);
+        let mut removed: Vec<Point3D> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| match event {
+                ChangeEvent::Removed(point) => point.clone(),
+                ChangeEvent::Inserted(_) => panic!("nothing is inserted"),
+            })
+            .collect();
+        removed.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let mut expected = points.to_vec();
+        expected.sort_by(|a, b| a.x.total_cmp(&b.x));
+        assert_eq!(removed, expected);
+
+        // The tree can be filled again.
+        for point in &points {
+            octree.insert(point);
         }
-    }
-}
+        assert_eq!(octree.len(), 10);
+        assert_eq!(octree.check_invariants(), Ok(()));
 
-#[cfg(test)]
-mod tests {
-    use super::point::Point3D;
-    use super::*;
+        // Without a callback, the generation still changes.
+        let mut octree = Octree::new(points.iter().collect());
+        octree.clear();
+        assert_eq!(octree.generation(), 1);
+        octree.clear();
+        assert_eq!(octree.generation(), 1);
+    }
 
     #[test]
-    /// Should construct bounding box with the specified min and max corner.
-    fn test_bounding_box_construction() {
-        let point1 = Point3D::new(10.0, 0.0, 0.0);
-        let point2 = Point3D::new(0.0, -1.0, 0.0);
-        let point3 = Point3D::new(0.0, 0.0, 5.0);
-        let bounding_box = BoundingBox::new(vec![point1, point2, point3].iter().collect());
+    /// Should yield every stored point once, including the ones in children.
+    fn test_octree_iter() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.iter().count(), 0);
 
-        assert_eq!(bounding_box.get_min(), &[0.0, -1.0, 0.0]);
-        assert_eq!(bounding_box.get_max(), &[10.0, 0.0, 5.0]);
+        let points = random_points(507, 200, 1.0);
+        let octree = Octree::new(points.iter().collect());
+        assert!(octree.root.splitted);
+
+        let iterated: Vec<&Point3D> = octree.iter().collect();
+        let mut stored = HashSet::new();
+        octree.root.for_each(&mut |point| {
+            stored.insert(point);
+        });
+        assert_eq!(iterated.len(), octree.len());
+        assert_eq!(iterated.into_iter().collect::<HashSet<_>>(), stored);
     }
 
     #[test]
-    /// Should identify if a point is covered by this area.
-    /// Note that the bounding box covers min surface but does not cover max surface.
-    fn test_bounding_box_covers() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let bounding_box = BoundingBox::new(vec![point1.clone(), point2.clone()].iter().collect());
-        let point3 = Point3D::new(5.0, 5.0, 5.0);
-        let point4 = Point3D::new(10.0, 11.0, 9.0);
+    /// Should yield every stored point exactly once when consuming the octree, and know how many are left.
+    fn test_octree_into_iter() {
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.into_iter().count(), 0);
 
-        assert!(bounding_box.covers(&point1.get_location()));
-        assert!(!bounding_box.covers(&point2.get_location()));
-        assert!(bounding_box.covers(&point3.get_location()));
-        assert!(!bounding_box.covers(&point4.get_location()));
-    }
+        let points = random_points(553, 200, 1.0);
+        let octree = OctreeBuilder::new()
+            .loose_factor(0.5)
+            .build(points.iter().collect());
+        assert!(octree.root.splitted);
 
-    #[test]
-    /// Should identify if two bounding boxes overlaps / intersects.
-    fn test_bounding_box_overlaps() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let bounding_box1 = BoundingBox::new(vec![point1, point2].iter().collect());
+        let iterated: HashSet<&Point3D> = octree.iter().collect();
+        let mut drained = octree.into_iter();
+        assert_eq!(drained.len(), points.len());
+        drained.next();
+        assert_eq!(drained.len(), points.len() - 1);
 
-        let point3 = Point3D::new(1.0, 1.0, 1.0);
-        let point4 = Point3D::new(11.0, 11.0, 11.0);
-        let bounding_box2 = BoundingBox::new(vec![point3, point4].iter().collect());
+        let mut drained: Vec<&Point3D> = Octree::new(points.iter().collect()).into_iter().collect();
+        drained.sort_by(|a, b| a.get_location().partial_cmp(&b.get_location()).unwrap());
+        let mut expected: Vec<&Point3D> = iterated.into_iter().collect();
+        expected.sort_by(|a, b| a.get_location().partial_cmp(&b.get_location()).unwrap());
+        assert_eq!(drained, expected);
+    }
 
-        let point5 = Point3D::new(1.0, 1.0, 1.0);
-        let point6 = Point3D::new(9.0, 9.0, 9.0);
-        let bounding_box3 = BoundingBox::new(vec![point5, point6].iter().collect());
+    #[test]
+    /// Should delete points held by children, and collapse several levels back into a fresh tree.
+    fn test_octree_delete_merges_children() {
+        let mut points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        points.extend(random_points(508, 100, 10.0));
+        let mut octree = Octree::new(points.iter().collect());
+        // Splitted twice: the root and some of its children.
+        assert!(octree
+            .nodes()
+            .any(|node| node.depth() == 1 && !node.is_leaf()));
 
-        let point7 = Point3D::new(11.0, 0.0, 0.0);
-        let point8 = Point3D::new(20.0, 20.0, 20.0);
-        let bounding_box4 = BoundingBox::new(vec![point7, point8].iter().collect());
+        // Keep both corners, so the extent stays the same, and 5 others.
+        let kept = [0, 3, 20, 40, 60, 80];
+        for (i, point) in points.iter().enumerate().skip(2) {
+            if !kept.contains(&i) {
+                assert!(octree.delete(point));
+                assert!(!octree.delete(point));
+                assert_eq!(octree.check_invariants(), Ok(()));
+            }
+        }
 
-        assert!(bounding_box1.overlaps(&bounding_box2));
-        assert!(bounding_box1.overlaps(&bounding_box3));
-        assert!(!bounding_box1.overlaps(&bounding_box4));
+        let remaining: Vec<&Point3D> = kept.iter().chain([&1]).map(|i| &points[*i]).collect();
+        assert_eq!(octree, Octree::new(remaining));
+        assert_eq!(octree.len(), kept.len() + 1);
     }
 
     #[test]
-    /// Should correctly calculate bounding box centre coordination.
-    fn test_bounding_box_centre() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let bounding_box = BoundingBox::new(vec![point1, point2].iter().collect());
+    /// Should remove exactly the rejected points, which are then no longer found, and merge children back as deleting
+    /// them one by one does.
+    fn test_octree_retain() {
+        let mut points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        points.extend(random_points(521, 100, 10.0));
+        let kept: Vec<&Point3D> = points.iter().filter(|point| point.x < 2.0).collect();
 
-        assert_eq!(bounding_box.get_centre(), [5.0; 3]);
+        let mut octree = Octree::new(points.iter().collect());
+        let mut expected = Octree::new(points.iter().collect());
+        let mut calls = 0;
+        octree.retain(|point| {
+            calls += 1;
+            point.x < 2.0
+        });
+        for point in &points {
+            if point.x >= 2.0 {
+                expected.delete(point);
+            }
+        }
+
+        assert_eq!(calls, points.len());
+        assert_eq!(octree, expected);
+        assert_eq!(octree.check_invariants(), Ok(()));
+        let everything = BoundingBox::new(points.iter().collect());
+        let found = octree.query(&everything);
+        assert!(kept.iter().all(|point| found.contains(point)));
+        assert!(found.iter().all(|point| point.x < 2.0));
+        assert_eq!(found.len(), octree.len());
+
+        let generation = octree.generation();
+        octree.retain(|_| true);
+        assert_eq!(octree.generation(), generation);
+        octree.retain(|_| false);
+        assert!(octree.is_empty());
+        assert_eq!(octree.leaf_count(), 1);
     }
 
     #[test]
-    /// Should split the current bounding box into 8 smaller bounding boxes.
-    fn test_bounding_box_split() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let bounding_box = BoundingBox::new(vec![point1, point2].iter().collect());
+    /// Should remove the points inside the bounding box, including the ones on its min surface, and keep the ones just
+    /// outside, including the ones on its max surface.
+    fn test_octree_remove_within() {
+        let mut points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        points.extend(random_points(522, 100, 10.0));
+        let on_min = Point3D::new(2.0, 3.0, 3.0);
+        let on_max = Point3D::new(6.0, 3.0, 3.0);
+        let below_min = Point3D::new(2.0f32.next_down(), 3.0, 3.0);
+        let below_max = Point3D::new(6.0f32.next_down(), 3.0, 3.0);
+        points.extend([
+            on_min.clone(),
+            on_max.clone(),
+            below_min.clone(),
+            below_max.clone(),
+        ]);
+        let bounding_box = BoundingBox::new(
+            [Point3D::new(2.0, 2.0, 2.0), Point3D::new(6.0, 6.0, 6.0)]
+                .iter()
+                .collect(),
+        );
 
-        let splitted = bounding_box.split();
+        let mut octree = Octree::new(points.iter().collect());
+        let expected = octree.query(&bounding_box).len();
+        let len = octree.len();
+        assert!(expected > 2);
 
-        // u: up, d: down, f: front, b: back, l: left, r: right.
-        // example, ulb is the left back corner in the upper layer.
-        // order: dfl, dfr, dbl, dbr, ufl, ufr, ubl, ubr.
+        assert_eq!(octree.remove_within(&bounding_box), expected);
+        assert_eq!(octree.len(), len - expected);
+        assert!(octree.query(&bounding_box).is_empty());
+        assert_eq!(octree.check_invariants(), Ok(()));
+        let remaining: Vec<&Point3D> = octree.iter().collect();
+        assert!(!remaining.contains(&&on_min));
+        assert!(!remaining.contains(&&below_max));
+        assert!(remaining.contains(&&on_max));
+        assert!(remaining.contains(&&below_min));
 
-        // dfl
-        assert_eq!(splitted[0].min, [0.0, 0.0, 0.0]);
-        assert_eq!(splitted[0].max, [5.0, 5.0, 5.0]);
+        let generation = octree.generation();
+        assert_eq!(octree.remove_within(&bounding_box), 0);
+        assert_eq!(octree.generation(), generation);
+        let len = octree.len();
+        let everything = octree.root.bounding_box.clone();
+        assert_eq!(octree.remove_within(&everything), len);
+        assert_eq!(octree.leaf_count(), 1);
+    }
 
-        // dfr
-        assert_eq!(splitted[1].min, [5.0, 0.0, 0.0]);
-        assert_eq!(splitted[1].max, [10.0, 5.0, 5.0]);
+    #[test]
+    /// Should export 8 vertices and 12 edges for every node, or every leaf.
+    fn test_octree_to_obj_wireframe() {
+        let points: Vec<Point3D> = (0..10)
+            .map(|i| Point3D::new(i as f32, i as f32, i as f32))
+            .collect();
 
-        // dbl
-        assert_eq!(splitted[2].min, [0.0, 5.0, 0.0]);
-        assert_eq!(splitted[2].max, [5.0, 10.0, 5.0]);
+        let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.to_obj_wireframe(true), "");
+        assert_eq!(octree.to_obj_wireframe(false), "");
 
-        // dbr
-        assert_eq!(splitted[3].min, [5.0, 5.0, 0.0]);
-        assert_eq!(splitted[3].max, [10.0, 10.0, 5.0]);
+        // A root splitted into 8 leaves.
+        let octree = Octree::new(points.iter().collect());
+        for (leaves_only, nodes) in [(false, 9), (true, 8)] {
+            let obj = octree.to_obj_wireframe(leaves_only);
+            let vertices: Vec<[f32; 3]> = obj
+                .lines()
+                .filter_map(|line| line.strip_prefix("v "))
+                .map(|line| {
+                    let coordinates: Vec<f32> =
+                        line.split(' ').map(|c| c.parse().unwrap()).collect();
+                    [coordinates[0], coordinates[1], coordinates[2]]
+                })
+                .collect();
+            let edges: Vec<(usize, usize)> = obj
+                .lines()
+                .filter_map(|line| line.strip_prefix("l "))
+                .map(|line| {
+                    let (a, b) = line.split_once(' ').unwrap();
+                    (a.parse().unwrap(), b.parse().unwrap())
+                })
+                .collect();
+            assert_eq!(vertices.len(), 8 * nodes);
+            assert_eq!(edges.len(), 12 * nodes);
+            assert_eq!(obj.lines().count(), 20 * nodes);
 
-        // ufl
-        assert_eq!(splitted[4].min, [0.0, 0.0, 5.0]);
-        assert_eq!(splitted[4].max, [5.0, 5.0, 10.0]);
+            // Every edge is parallel to an axis, within one node, and as long as the node along that axis.
+            for (a, b) in edges {
+                assert!(a < b && b <= vertices.len());
+                assert_eq!((a - 1) / 8, (b - 1) / 8);
+                let node = octree
+                    .nodes()
+                    .filter(|node| !leaves_only || node.is_leaf())
+                    .nth((a - 1) / 8)
+                    .unwrap();
+                let differing: Vec<usize> = (0..3)
+                    .filter(|i| vertices[a - 1][*i] != vertices[b - 1][*i])
+                    .collect();
+                assert_eq!(differing.len(), 1);
+                let axis = differing[0];
+                let bounds = node.bounds();
+                assert_eq!(vertices[a - 1][axis], bounds.get_min()[axis]);
+                assert_eq!(vertices[b - 1][axis], bounds.get_max()[axis]);
+            }
+        }
+    }
 
-        // ufr
-        assert_eq!(splitted[5].min, [5.0, 0.0, 5.0]);
-        assert_eq!(splitted[5].max, [10.0, 5.0, 10.0]);
+    #[test]
+    /// Should keep distinct points at one location together in one leaf, and return all of them from every query.
+    fn test_octree_coincident_points() {
+        let numbers = random_numbers(550, 3 * 200);
+        let mut particles: Vec<Particle> = numbers
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Particle {
+                id,
+                location: [c[0] * 10.0, c[1] * 10.0, c[2] * 10.0],
+            })
+            .collect();
+        let location = [2.5, 5.0, 7.5];
+        for id in 200..230 {
+            particles.push(Particle { id, location });
+        }
+        let coincident: HashSet<&Particle> = particles[200..].iter().collect();
+        let again = Particle { id: 200, location };
+        let mut octree = Octree::new(particles.iter().collect());
+        assert_eq!(octree.len(), particles.len());
+        assert_eq!(octree.check_invariants(), Ok(()));
 
-        // ubl
-        assert_eq!(splitted[6].min, [0.0, 5.0, 5.0]);
-        assert_eq!(splitted[6].max, [5.0, 10.0, 10.0]);
+        let leaves: Vec<_> = octree
+            .nodes()
+            .filter(|node| node.points().any(|point| point.location == location))
+            .collect();
+        assert_eq!(leaves.len(), 1);
+        assert!(leaves[0].is_leaf());
+        assert!(coincident
+            .iter()
+            .all(|point| leaves[0].points().any(|stored| stored == *point)));
 
-        // ubr
-        assert_eq!(splitted[7].min, [5.0, 5.0, 5.0]);
-        assert_eq!(splitted[7].max, [10.0, 10.0, 10.0]);
+        let bounding_box = BoundingBox {
+            min: location,
+            max: location.map(|c| c.next_up()),
+        };
+        assert_eq!(octree.query(&bounding_box), coincident);
+        assert_eq!(octree.count_in_box(&bounding_box), coincident.len());
+        assert_eq!(octree.query_radius(location, 0.0), coincident);
+        assert_eq!(
+            octree
+                .k_nearest(&location, coincident.len())
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            coincident
+        );
+        assert!(octree
+            .nearest_iter(location)
+            .take(coincident.len())
+            .all(|(point, distance)| { distance == 0.0 && coincident.contains(point) }));
+        assert!(octree.has_neighbor_within(&particles[200], 0.0));
+
+        // Equal points are only stored once.
+        let len = octree.len();
+        assert!(!octree.insert(&again));
+        assert_eq!(octree.len(), len);
+
+        // Deleting all but one leaves the rest where they are.
+        for particle in &particles[201..] {
+            assert!(octree.delete(particle));
+        }
+        assert_eq!(
+            octree.query(&bounding_box),
+            HashSet::from([&particles[200]])
+        );
+        assert_eq!(octree.check_invariants(), Ok(()));
     }
 
     #[test]
-    /// Should construct a tree node with default settings.
-    fn test_tree_node_default_construction() {
-        let tree_node: TreeNode<Point3D> = TreeNode::default();
-        assert!(tree_node.children.is_none());
-        assert_eq!(tree_node.bounding_box, BoundingBox::default());
-        assert_eq!(tree_node.points, HashSet::new());
-        assert_eq!(tree_node.capacity, 8);
-        assert!(!tree_node.splitted);
+    /// Should stop splitting at the max depth, keeping every point of a crowded cluster in the deepest leaves.
+    fn test_octree_max_depth() {
+        // Distinct points at one location.
+        let particles: Vec<Particle> = (0..1000)
+            .map(|id| Particle {
+                id,
+                location: [1.0, 2.0, 3.0],
+            })
+            .chain([Particle {
+                id: 1000,
+                location: [10.0, 10.0, 10.0],
+            }])
+            .collect();
+        let octree = Octree::new(particles.iter().collect());
+        assert_eq!(octree.len(), particles.len());
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert_eq!(octree.max_leaf_occupancy(), 1000);
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [2.0, 3.0, 4.0],
+        };
+        assert_eq!(octree.query(&bounding_box).len(), 1000);
+
+        // Distinct locations, each only the smallest possible step from the last one.
+        let mut location = [1.0f32; 3];
+        let points: Vec<Point3D> = (0..100)
+            .map(|_| {
+                location = location.map(|c| c.next_up());
+                Point3D::new(location[0], location[1], location[2])
+            })
+            .chain([
+                Point3D::new(0.0, 0.0, 0.0),
+                Point3D::new(1000.0, 1000.0, 1000.0),
+            ])
+            .collect();
+        for max_depth in [0, 3, 100] {
+            let octree = OctreeBuilder::new()
+                .capacity(1)
+                .max_depth(max_depth)
+                .build(points.iter().collect());
+            assert_eq!(octree.len(), points.len());
+            assert_eq!(octree.check_invariants(), Ok(()));
+            assert!(octree.depth() <= max_depth);
+            for point in &points {
+                assert_eq!(octree.nearest(point.get_location()), Some(point));
+            }
+
+            let mut extended = OctreeBuilder::new()
+                .capacity(1)
+                .max_depth(max_depth)
+                .build(points[100..].iter().collect());
+            extended.extend(&points[..100]);
+            assert_eq!(extended, octree);
+        }
+        assert_eq!(OctreeConfig::default().max_depth, MAX_DEPTH);
     }
 
     #[test]
-    /// Should construct a tree node and do not further split.
-    fn test_tree_node_construction_no_split() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let points = vec![point1.clone(), point2];
-        let point_references: Vec<&Point3D> = points.iter().collect();
-        let tree_node = TreeNode::new(point_references.clone());
+    /// Should keep the cached point counts of all nodes right through random sequences of changes.
+    fn test_octree_subtree_count() {
+        let numbers = random_numbers(548, 3 * 400);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| {
+                Point3D::new(
+                    (c[0] * 12.0).floor(),
+                    (c[1] * 12.0).floor(),
+                    (c[2] * 12.0).floor(),
+                )
+            })
+            .collect();
+        let bounds = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+        let operations = random_numbers(5480, 600);
 
-        assert!(tree_node.children.is_none());
-        assert_eq!(tree_node.bounding_box.min, [0.0, 0.0, 0.0]);
-        assert_eq!(tree_node.bounding_box.max, [10.0, 10.0, 10.0]);
-        assert_eq!(tree_node.points, HashSet::from([&point1]));
-        assert_eq!(tree_node.capacity, 8);
-        assert!(!tree_node.splitted);
+        for builder in [
+            OctreeBuilder::new(),
+            OctreeBuilder::new().capacity(2),
+            OctreeBuilder::new().loose_factor(0.25),
+        ] {
+            let mut octree = builder.build(bounds.iter().collect());
+            for (step, operation) in operations.iter().enumerate() {
+                let point = &points[step % points.len()];
+                let other = &points[(step * 7 + 3) % points.len()];
+                match (operation * 10.0) as usize {
+                    0..=3 => {
+                        octree.insert(point);
+                    }
+                    4 => {
+                        octree.insert_growing(point);
+                    }
+                    5 => {
+                        octree.delete(other);
+                    }
+                    6 => {
+                        octree.update(other, point);
+                    }
+                    7 => {
+                        let end = (step % points.len()).max(20);
+                        octree.extend(&points[end - 20..end]);
+                    }
+                    8 => {
+                        let bounding_box = BoundingBox {
+                            min: point.get_location(),
+                            max: point.get_location().map(|c| c + 3.0),
+                        };
+                        octree.remove_within(&bounding_box);
+                    }
+                    _ if step % 50 == 9 => octree.clear(),
+                    _ if step % 2 == 0 => octree.retain(|kept| kept.x != point.x),
+                    _ => {
+                        octree.compact();
+                    }
+                }
+                assert_eq!(octree.check_invariants(), Ok(()));
+                assert_eq!(octree.len(), octree.iter().count());
+                assert_eq!(octree.is_empty(), octree.iter().next().is_none());
+            }
+        }
     }
 
     #[test]
-    /// Should construct an octree with default settings.
-    fn test_octree_default_construction() {
+    /// Should report the depth, node count, point count and fullest node of trees of known shape.
+    fn test_octree_stats() {
+        let points: Vec<Point3D> = (0..10)
+            .map(|i| Point3D::new(i as f32, i as f32, i as f32))
+            .collect();
+
         let octree: Octree<Point3D> = Octree::default();
+        assert_eq!(octree.depth(), 0);
+        assert_eq!(octree.node_count(), 1);
+        assert_eq!(
+            octree.stats(),
+            OctreeStats {
+                node_count: 1,
+                ..Default::default()
+            }
+        );
 
-        assert_eq!(octree.root, TreeNode::default());
+        // The root pushes its 10 points down, 5 into each of two of its 8 children.
+        let octree = Octree::new(points.iter().collect());
+        assert_eq!(octree.depth(), 1);
+        assert_eq!(octree.node_count(), 9);
+        assert_eq!(
+            octree.stats(),
+            OctreeStats {
+                depth: 1,
+                node_count: 9,
+                point_count: 10,
+                max_points_per_node: 5,
+            }
+        );
+
+        // With one point per leaf, 9 nodes split into 8 children each, nesting at most 4 levels deep.
+        let octree = OctreeBuilder::new()
+            .capacity(1)
+            .build(points.iter().collect());
+        assert_eq!(octree.depth(), 4);
+        assert_eq!(octree.node_count(), 73);
+        assert_eq!(
+            octree.stats(),
+            OctreeStats {
+                depth: 4,
+                node_count: 73,
+                point_count: 10,
+                max_points_per_node: 1,
+            }
+        );
     }
 
     #[test]
-    /// Should construct an octree from some given points.
-    fn test_octree_construction() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let points = vec![point1, point2];
-        let octree = Octree::new(points.iter().collect());
-        let tree_node = TreeNode::new(points.iter().collect());
+    /// Should use the chosen capacity in every node, including the ones created by later splits.
+    fn test_octree_capacity() {
+        let points = random_points(509, 300, 1.0);
 
-        assert_eq!(octree.root, tree_node);
+        for capacity in [1, 3, 32] {
+            let mut octree = OctreeBuilder::new()
+                .capacity(capacity)
+                .build(points[..150].iter().collect());
+            for point in &points[150..] {
+                octree.insert(point);
+            }
+            assert_eq!(octree.check_invariants(), Ok(()));
+            assert!(octree.nodes().all(|node| if node.is_leaf() {
+                node.points().count() <= capacity
+            } else {
+                node.points().count() == 0
+            }));
+        }
+
+        let octree = OctreeBuilder::new()
+            .capacity(0)
+            .build(points.iter().collect());
+        assert_eq!(octree.config().capacity, 1);
+        assert_eq!(octree.check_invariants(), Ok(()));
+        let large = OctreeBuilder::new()
+            .capacity(32)
+            .build(points.iter().collect());
+        assert!(large.leaf_count() < Octree::new(points.iter().collect()).leaf_count());
     }
 
     #[test]
-    /// Should insert a point into octree if the octree covers it, and does nothing if not.
-    fn test_octree_insert() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let point3 = Point3D::new(5.0, 5.0, 5.0);
-        let points = vec![point1.clone(), point2.clone()];
-        let mut octree1 = Octree::new(points.iter().collect());
-        octree1.insert(&point3);
-        let all_points = vec![point1, point2, point3.clone()];
-        let octree2 = Octree::new(all_points.iter().collect());
+    /// Should split a tree with explicit bounds along the same planes whatever the insertion order.
+    fn test_octree_with_bounds() {
+        let numbers = random_numbers(541, 3 * 300);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 120.0 - 10.0, c[1] * 100.0, c[2] * 100.0))
+            .collect();
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [100.0, 100.0, 100.0],
+        };
+        // The bounding box of every node, by the octants leading to it from the root.
+        fn layout(
+            node: NodeCursor<Point3D, f32>,
+            octants: &mut Vec<usize>,
+            ret: &mut Vec<(Vec<usize>, BoundingBox)>,
+        ) {
+            ret.push((octants.clone(), node.bounds().clone()));
+            for octant in 0..8 {
+                if let Some(child) = node.child(octant) {
+                    octants.push(octant);
+                    layout(child, octants, ret);
+                    octants.pop();
+                }
+            }
+        }
 
-        assert_eq!(octree1, octree2);
+        for capacity in [1, 8] {
+            let builder = OctreeBuilder::new().capacity(capacity);
+            let mut forward = builder
+                .clone()
+                .build_with_bounds(bounding_box.clone(), Vec::new());
+            let inserted = points.iter().filter(|&point| forward.insert(point)).count();
+            assert!(inserted < points.len());
 
-        let point4 = Point3D::new(20.0, 20.0, 20.0);
-        octree1.insert(&point4);
+            let mut backward = builder.build_with_bounds(bounding_box.clone(), Vec::new());
+            assert_eq!(backward.extend(points.iter().rev()), inserted);
 
-        assert_eq!(octree1, octree2);
+            // Which points a node keeps, and so which nodes are splitted, depends on the order, but the nodes present
+            // in both trees cover the same space.
+            let mut forward_layout = Vec::new();
+            let mut backward_layout = Vec::new();
+            layout(forward.root_cursor(), &mut Vec::new(), &mut forward_layout);
+            layout(
+                backward.root_cursor(),
+                &mut Vec::new(),
+                &mut backward_layout,
+            );
+            let mut shared = 0;
+            for (octants, bounds) in &forward_layout {
+                if let Some((_, other)) = backward_layout.iter().find(|(other, _)| other == octants)
+                {
+                    assert_eq!(bounds, other);
+                    shared += 1;
+                }
+            }
+            assert!(shared > 8);
+            assert_eq!(
+                forward.iter().collect::<HashSet<_>>(),
+                backward.iter().collect::<HashSet<_>>()
+            );
+            assert_eq!(forward.check_invariants(), Ok(()));
+            assert_eq!(backward.check_invariants(), Ok(()));
+            assert_eq!(forward.bounds(), &bounding_box);
+        }
+
+        // With fewer points than the capacity, no node keeps a point the other does not.
+        let mut forward = Octree::with_bounds(bounding_box.clone());
+        let mut backward = Octree::with_bounds(bounding_box.clone());
+        forward.extend(points[..6].iter());
+        backward.extend(points[..6].iter().rev());
+        assert_eq!(forward, backward);
+
+        let octree: Octree<Point3D> = Octree::with_bounds(bounding_box.clone());
+        assert!(octree.is_empty());
+        assert_eq!(
+            octree,
+            OctreeBuilder::new().build_with_bounds(bounding_box, Vec::new())
+        );
     }
 
     #[test]
-    /// Should delete a point from octree if the point is recorded, and does nothing if not.
-    fn test_octree_delete() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(5.0, 5.0, 5.0);
-        let point3 = Point3D::new(10.0, 10.0, 10.0);
-        let points = vec![point1.clone(), point3.clone()];
-        let all_points = vec![point1, point2.clone(), point3];
-        let mut octree1 = Octree::new(all_points.iter().collect());
-        octree1.delete(&point2);
-        let octree2 = Octree::new(points.iter().collect());
+    /// Should build equal trees from the same points in any order, with only leaves holding points.
+    fn test_octree_order_independent() {
+        let numbers = random_numbers(542, 3 * 300);
+        let mut particles: Vec<Particle> = numbers
+            .chunks(3)
+            .enumerate()
+            .map(|(id, c)| Particle {
+                id,
+                location: [c[0] * 10.0, c[1] * 10.0, c[2] * 10.0],
+            })
+            .collect();
+        // More coincident points than fit into a node.
+        for id in 300..320 {
+            particles.push(Particle {
+                id,
+                location: [2.5, 2.5, 2.5],
+            });
+        }
+        let bounding_box = BoundingBox {
+            min: [0.0, 0.0, 0.0],
+            max: [10.0, 10.0, 10.0],
+        };
+        let orders: Vec<Vec<&Particle>> = (0..4)
+            .map(|seed| {
+                let keys = random_numbers(seed, particles.len());
+                let mut order: Vec<(f32, &Particle)> = keys.into_iter().zip(&particles).collect();
+                order.sort_by(|a, b| a.0.total_cmp(&b.0));
+                order.into_iter().map(|(_, particle)| particle).collect()
+            })
+            .collect();
 
-        assert_eq!(octree1, octree2);
+        for builder in [
+            OctreeBuilder::new(),
+            OctreeBuilder::new().capacity(1),
+            OctreeBuilder::new().loose_factor(0.25),
+        ] {
+            let expected = builder
+                .clone()
+                .build_with_bounds(bounding_box.clone(), particles.iter().collect());
+            assert_eq!(expected.len(), particles.len());
+            assert_eq!(expected.check_invariants(), Ok(()));
+            assert!(expected.nodes().all(|node| node.is_leaf()
+                || builder.get_loose_factor() != 0.0
+                || node.points().count() == 0));
 
-        let point4 = Point3D::new(100.0, 100.0, 100.0);
-        octree1.delete(&point4);
+            for order in &orders {
+                let octree = builder
+                    .clone()
+                    .build_with_bounds(bounding_box.clone(), order.clone());
+                assert_eq!(octree, expected);
 
-        assert_eq!(octree1, octree2);
+                let mut octree = builder
+                    .clone()
+                    .build_with_bounds(bounding_box.clone(), Vec::new());
+                octree.extend(order.iter().copied());
+                assert_eq!(octree, expected);
+
+                // Deleting points merges nodes back into what the remaining points build.
+                let mut octree = builder
+                    .clone()
+                    .build_with_bounds(bounding_box.clone(), order.clone());
+                for particle in &order[..250] {
+                    assert!(octree.delete(particle));
+                }
+                let remaining = builder
+                    .clone()
+                    .build_with_bounds(bounding_box.clone(), order[250..].to_vec());
+                assert_eq!(octree, remaining);
+            }
+        }
     }
 
     #[test]
-    /// Should correctly identify if a point can be covered by a octree.
-    fn test_octree_covers() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(5.0, 5.0, 5.0);
-        let points = vec![point1, point2];
-        let octree = Octree::new(points.iter().collect());
-        let point3 = Point3D::new(10.0, 10.0, 10.0);
-        let point4 = Point3D::new(2.0, 2.0, 2.0);
+    /// Should build the same tree as inserting the points one by one.
+    fn test_octree_extend() {
+        let numbers = random_numbers(510, 3 * 300);
+        let points: Vec<Point3D> = numbers
+            .chunks(3)
+            .map(|c| Point3D::new(c[0] * 12.0 - 1.0, c[1] * 12.0 - 1.0, c[2] * 10.0))
+            .collect();
+        let bounds = [Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
 
-        assert!(octree.covers(&point4));
-        assert!(!octree.covers(&point3));
-    }
+        let mut expected = Octree::new(bounds.iter().collect());
+        let mut count = 0;
+        for point in &points {
+            if expected.insert(point) {
+                count += 1;
+            }
+        }
+        assert!(count < points.len());
 
-    #[test]
-    /// Should correctly identify if a point is recorded.
-    fn test_octree_contains() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(5.0, 5.0, 5.0);
-        let points = vec![point1.clone(), point2.clone()];
-        let octree = Octree::new(points.iter().collect());
-        let point3 = Point3D::new(2.0, 2.0, 2.0);
+        let mut octree = Octree::new(bounds.iter().collect());
+        assert_eq!(octree.extend(&points), count);
+        assert_eq!(octree, expected);
+        assert_eq!(octree.generation(), expected.generation());
 
-        assert!(octree.contains(&point1));
-        // Because the bounding box contains the min surface but does not contain the max surface.
-        assert!(!octree.contains(&point2));
-        assert!(!octree.contains(&point3));
+        let mut octree = OctreeBuilder::new()
+            .loose_factor(0.5)
+            .build(bounds.iter().collect());
+        let mut expected = OctreeBuilder::new()
+            .loose_factor(0.5)
+            .build(bounds.iter().collect());
+        let count = points.iter().filter(|point| expected.insert(point)).count();
+        assert_eq!(octree.extend(&points), count);
+        assert_eq!(octree, expected);
+        assert_eq!(octree.extend(&points), 0);
+        assert_eq!(octree, expected);
+        assert_eq!(octree.extend([]), 0);
     }
 
     #[test]
-    /// Should find all points within the given query area.
-    fn test_octree_query() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let point3 = Point3D::new(4.0, 4.0, 4.0);
-        let points = vec![point1.clone(), point2];
-        let mut octree = Octree::new(points.iter().collect());
-        let point4 = Point3D::new(5.0, 10.0, 5.0);
-        octree.insert(&point3);
-        octree.insert(&point4);
+    /// Should tell apart points closer than the precision of [f32] when built over [f64] coordinates.
+    fn test_octree_f64() {
+        // About the radius of the earth in metres, where f32 steps by 0.5.
+        let origin = 6_378_137.0;
+        let points: Vec<Point3D64> = (0..20)
+            .map(|i| Point3D64::new(origin + 0.1 * i as f64, origin, origin + 0.05 * i as f64))
+            .collect();
+        let mut octree: Octree<Point3D64, f64> = Octree::new(points.iter().collect());
 
-        let points_for_query = vec![point1.clone(), point4.clone()];
-        let bounding_box = BoundingBox::new(points_for_query.iter().collect());
+        assert_eq!(octree.check_invariants(), Ok(()));
+        assert_eq!(octree.len(), 20);
+        assert_eq!(
+            octree.nearest([origin + 0.52, origin, origin + 0.26]),
+            Some(&points[5])
+        );
+        assert_eq!(
+            octree.k_nearest(&[origin, origin, origin], 3),
+            vec![&points[0], &points[1], &points[2]]
+        );
+        assert_eq!(
+            octree.query_radius(points[10].get_location(), 0.15).len(),
+            3
+        );
 
+        assert!(octree.delete(&points[5]));
         assert_eq!(
-            octree.query(&bounding_box),
-            HashSet::from([&point1, &point3])
+            octree.nearest([origin + 0.52, origin, origin + 0.26]),
+            Some(&points[6])
         );
     }
 
     #[test]
-    /// Should identify if a bounding boxes overlaps / intersects with an octree.
-    fn test_octree_overlap() {
-        let point1 = Point3D::new(0.0, 0.0, 0.0);
-        let point2 = Point3D::new(10.0, 10.0, 10.0);
-        let points = vec![point1, point2];
+    /// Should find the points of nodes lying fully inside the query bounding box.
+    fn test_octree_query_enclosing() {
+        let points: Vec<Point3D> = (0..20)
+            .map(|i| Point3D::new(i as f32, (i * 7 % 20) as f32, (i * 3 % 20) as f32))
+            .collect();
         let octree = Octree::new(points.iter().collect());
+        let bounding_box = BoundingBox::new(
+            [
+                Point3D::new(-5.0, -5.0, -5.0),
+                Point3D::new(25.0, 25.0, 25.0),
+            ]
+            .iter()
+            .collect(),
+        );
 
-        let point3 = Point3D::new(1.0, 1.0, 1.0);
-        let point4 = Point3D::new(11.0, 11.0, 11.0);
-        let bounding_box1 = BoundingBox::new(vec![point3, point4].iter().collect());
-
-        let point5 = Point3D::new(1.0, 1.0, 1.0);
-        let point6 = Point3D::new(9.0, 9.0, 9.0);
-        let bounding_box2 = BoundingBox::new(vec![point5, point6].iter().collect());
-
-        let point7 = Point3D::new(11.0, 0.0, 0.0);
-        let point8 = Point3D::new(20.0, 20.0, 20.0);
-        let bounding_box3 = BoundingBox::new(vec![point7, point8].iter().collect());
-
-        assert!(octree.overlaps(&bounding_box1));
-        assert!(octree.overlaps(&bounding_box2));
-        assert!(!octree.overlaps(&bounding_box3));
+        assert_eq!(octree.query(&bounding_box).len(), octree.len());
+        let mut expected: Vec<&Point3D> = octree.iter().collect();
+        expected.sort();
+        assert_eq!(octree.query_ordered(&bounding_box), expected);
     }
 }
@@ -1,34 +1,72 @@
 //! The default, and a sample point structure that [Octree](crate::Octree) holds.
-use std::hash::Hash;
+use core::cmp::Ordering;
+use core::hash::Hash;
 
-use crate::Locatable;
+use crate::{compare_locations, Locatable, Scalar};
 
-/// Defines a basic 3D point. [Octree](crate::Octree) uses a [HashSet](std::collections::HashSet) to keep a record of
-/// all points it has seen, so [PartialEq], [Eq]. and [Hash] must be defined as well.
+/// Defines a basic 3D point with coordinates of type `T`, see [Point3D] and [Point3D64]. [Octree](crate::Octree) uses
+/// a [HashSet](std::collections::HashSet) to keep a record of all points it has seen, so [PartialEq], [Eq]. and [Hash]
+/// must be defined as well.
 #[derive(Clone, Debug, Default)]
-pub struct Point3D {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl PartialEq for Point3D {
+/// A [Point] with [f32] coordinates, the default of [Octree](crate::Octree).
+pub type Point3D = Point<f32>;
+
+/// A [Point] with [f64] coordinates, for data which needs the extra precision.
+pub type Point3D64 = Point<f64>;
+
+impl<T> PartialEq for Point<T>
+where
+    T: Scalar,
+{
     fn eq(&self, other: &Self) -> bool {
         self.x == other.x && self.y == other.y && self.z == other.z
     }
 }
 
-impl Eq for Point3D {}
+impl<T> Eq for Point<T> where T: Scalar {}
 
-impl Hash for Point3D {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+impl<T> Hash for Point<T>
+where
+    T: Scalar,
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.x.to_bits().hash(state);
         self.y.to_bits().hash(state);
         self.z.to_bits().hash(state);
     }
 }
 
-impl Point3D {
+/// Orders points by x, then y, then z, using [total_cmp](Scalar::total_cmp) on each coordinate, so a [Point] can key a
+/// [BTreeMap](std::collections::BTreeMap) or [BTreeSet](std::collections::BTreeSet).
+///
+/// Like the rest of the crate, this assumes finite coordinates. NaN, and `0.0` against `-0.0`, compare differently
+/// under [PartialEq], which breaks the total order contract.
+impl<T> Ord for Point<T>
+where
+    T: Scalar,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_locations(&self.get_location(), &other.get_location())
+    }
+}
+
+impl<T> PartialOrd for Point<T>
+where
+    T: Scalar,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Point<T> {
     /// Construct a 3D point from given coordination.
     /// # Example
     /// ```
@@ -38,21 +76,122 @@ impl Point3D {
     /// let point = Point3D::new(0.0, 0.0, 0.0);
     /// assert_eq!(point.get_location(), [0.0, 0.0, 0.0]);
     /// ```
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
-        Point3D { x, y, z }
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point { x, y, z }
     }
 }
 
-impl Locatable for Point3D {
-    fn get_location(&self) -> [f32; 3] {
+impl<T> Locatable<T> for Point<T>
+where
+    T: Copy,
+{
+    fn get_location(&self) -> [T; 3] {
         [self.x, self.y, self.z]
     }
 }
 
+/// Wrap raw coordinates into a [Point], so they can be stored in an [Octree](crate::Octree).
+impl<T> From<[T; 3]> for Point<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Point::new(x, y, z)
+    }
+}
+
+/// Wrap raw coordinates into a [Point], so they can be stored in an [Octree](crate::Octree).
+impl<T> From<(T, T, T)> for Point<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point::new(x, y, z)
+    }
+}
+
+/// A [Point] compared with a tolerance: its coordinates are snapped to the nearest multiple of `step` on each axis,
+/// and two points are equal, and hash the same, if they snap to the same grid location with the same `step`. Points
+/// which only differ by rounding errors thus collapse into one, see [ApproxPoint3D] and [ApproxPoint3D64].
+///
+/// It is located at the snapped location, so equal points always land in the same node of an
+/// [Octree](crate::Octree), whose [HashSet](std::collections::HashSet) then keeps only the first one inserted. The
+/// original coordinates are kept in `point`. Note the tolerance follows the grid: points closer than `step` on either
+/// side of a half way boundary, e.g. `0.49` and `0.51` with a `step` of 1, are still distinct.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApproxPoint<T> {
+    pub point: Point<T>,
+    pub step: T,
+}
+
+/// An [ApproxPoint] with [f32] coordinates.
+pub type ApproxPoint3D = ApproxPoint<f32>;
+
+/// An [ApproxPoint] with [f64] coordinates.
+pub type ApproxPoint3D64 = ApproxPoint<f64>;
+
+impl<T> ApproxPoint<T>
+where
+    T: Scalar,
+{
+    /// Construct a point from given coordination, snapped to a grid of the given `step`, which must be positive.
+    /// # Example
+    /// ```
+    /// use octree::point::ApproxPoint3D;
+    ///
+    /// let point1 = ApproxPoint3D::new(0.3, 1.0, 2.0, 1e-3);
+    /// let point2 = ApproxPoint3D::new(f32::next_up(0.3), 1.0, 2.0, 1e-3);
+    /// assert_eq!(point1, point2);
+    /// assert_ne!(point1.point, point2.point);
+    /// ```
+    pub fn new(x: T, y: T, z: T, step: T) -> Self {
+        assert!(step > T::ZERO, "step must be positive");
+        ApproxPoint {
+            point: Point::new(x, y, z),
+            step,
+        }
+    }
+
+    /// The index of the grid location this point is snapped to on each axis.
+    fn cell(&self) -> [T; 3] {
+        // Adding 0 turns -0 into 0, so both snap to the same cell.
+        self.point
+            .get_location()
+            .map(|coordinate| (coordinate / self.step).round() + T::ZERO)
+    }
+}
+
+impl<T> PartialEq for ApproxPoint<T>
+where
+    T: Scalar,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.step == other.step && self.cell() == other.cell()
+    }
+}
+
+impl<T> Eq for ApproxPoint<T> where T: Scalar {}
+
+impl<T> Hash for ApproxPoint<T>
+where
+    T: Scalar,
+{
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.step.to_bits().hash(state);
+        for coordinate in self.cell() {
+            coordinate.to_bits().hash(state);
+        }
+    }
+}
+
+impl<T> Locatable<T> for ApproxPoint<T>
+where
+    T: Scalar,
+{
+    fn get_location(&self) -> [T; 3] {
+        self.cell().map(|index| index * self.step)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
+    use std::collections::{BTreeSet, HashSet};
 
     #[test]
     /// Should correctly construct a point from given coordinate.
@@ -70,6 +209,23 @@ mod tests {
         assert_eq!(point.get_location(), [0.0, 1.0, 2.0]);
     }
 
+    #[test]
+    /// Should locate raw coordinates as they are, and wrap them into points at the same location.
+    fn test_raw_coordinates() {
+        let array = [0.0, 1.0, 2.0];
+        let tuple = (0.0, 1.0, 2.0);
+        assert_eq!(array.get_location(), [0.0, 1.0, 2.0]);
+        assert_eq!(tuple.get_location(), [0.0, 1.0, 2.0]);
+        assert_eq!([1.0f64, 2.0, 3.0].get_location(), [1.0, 2.0, 3.0]);
+
+        assert_eq!(Point3D::from(array), Point3D::new(0.0, 1.0, 2.0));
+        assert_eq!(Point3D::from(tuple), Point3D::new(0.0, 1.0, 2.0));
+        assert_eq!(
+            Point3D64::from([1.0, 2.0, 3.0]).get_location(),
+            [1.0, 2.0, 3.0]
+        );
+    }
+
     #[test]
     /// Should be able to determine if two points are equal, e.g., they have the same coordination.
     fn test_point3d_equal() {
@@ -95,4 +251,59 @@ mod tests {
         assert!(set.contains(&point2));
         assert!(!set.contains(&point3));
     }
+
+    #[test]
+    /// Should keep coordinates which only differ beyond the precision of [f32].
+    fn test_point3d64_precision() {
+        let point1 = Point3D64::new(1e8, 0.0, 0.0);
+        let point2 = Point3D64::new(1e8 + 1.0, 0.0, 0.0);
+
+        assert_ne!(point1, point2);
+        assert!(point1 < point2);
+        assert_eq!(point2.get_location(), [100_000_001.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    /// Should consider points snapped to the same grid location equal, and hash them the same.
+    fn test_approx_point3d_equal() {
+        let point1 = ApproxPoint3D::new(f32::next_up(0.3), 1.0, -0.0001, 0.001);
+        let point2 = ApproxPoint3D::new(0.3, 1.0004, 0.0, 0.001);
+        let point3 = ApproxPoint3D::new(0.3, 1.0, 0.002, 0.001);
+        let point4 = ApproxPoint3D::new(0.3, 1.0, 0.0, 0.01);
+
+        assert_ne!(
+            Point3D::new(f32::next_up(0.3), 1.0, 0.0),
+            Point3D::new(0.3, 1.0, 0.0)
+        );
+        assert_eq!(point1, point2);
+        assert_ne!(point1, point3);
+        assert_ne!(point2, point4);
+        assert_eq!(point1.get_location(), point2.get_location());
+
+        let set: HashSet<&ApproxPoint3D> = [&point1, &point2, &point3].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&ApproxPoint3D::new(0.3001, 1.0, 0.0, 0.001)));
+    }
+
+    #[test]
+    /// Should be ordered by x, then y, then z, and be able to be stored in a [BTreeSet].
+    fn test_point3d_ord() {
+        let point1 = Point3D::new(-1.0, 5.0, 5.0);
+        let point2 = Point3D::new(0.0, 1.0, 2.0);
+        let point3 = Point3D::new(0.0, 1.0, 3.0);
+        let point4 = Point3D::new(0.0, 2.0, -7.0);
+
+        assert!(point1 < point2);
+        assert!(point2 < point3);
+        assert!(point3 < point4);
+        assert_eq!(point2.cmp(&Point3D::new(0.0, 1.0, 2.0)), Ordering::Equal);
+
+        let set: BTreeSet<&Point3D> = [&point4, &point2, &point1, &point3, &point2]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            [&point1, &point2, &point3, &point4]
+        );
+    }
 }
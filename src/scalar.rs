@@ -0,0 +1,229 @@
+//! The floating point types coordinates can be given in.
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::iter::Sum;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A floating point type for coordinates, implemented for [f32] and [f64]. [f32] is the default for [Locatable],
+/// [Octree] and [BoundingBox], so only data which needs the extra precision, e.g. geodetic coordinates, has to name
+/// [f64].
+///
+/// Settings which are ratios rather than coordinates, such as [OctreeBuilder::loose_factor], stay [f32] and are
+/// converted with [from_f32](Scalar::from_f32).
+///
+/// [Locatable]: crate::Locatable
+/// [Octree]: crate::Octree
+/// [BoundingBox]: crate::BoundingBox
+/// [OctreeBuilder::loose_factor]: crate::OctreeBuilder::loose_factor
+pub trait Scalar:
+    Copy
+    + Debug
+    + Default
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + Sum
+    + Send
+    + Sync
+    + 'static
+{
+    /// Zero.
+    const ZERO: Self;
+    /// One.
+    const ONE: Self;
+    /// The smallest finite value.
+    const MIN: Self;
+    /// The largest finite value.
+    const MAX: Self;
+
+    /// Convert from an [f32], exactly for both [f32] and [f64].
+    fn from_f32(x: f32) -> Self;
+
+    /// Convert from an [f64], rounding to the nearest value.
+    fn from_f64(x: f64) -> Self;
+
+    /// Convert to an [f64], exactly for both [f32] and [f64].
+    fn to_f64(self) -> f64;
+
+    /// The raw bits, widened to [u64], so equal values which are not NaN, other than `0.0` and `-0.0`, hash the same.
+    fn to_bits(self) -> u64;
+
+    /// Total order, like [f32::total_cmp].
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// The least value greater than this one, like [f32::next_up].
+    fn next_up(self) -> Self;
+
+    /// Round to the nearest integer, half way cases away from 0, like [f32::round].
+    fn round(self) -> Self;
+
+    /// Square root, like [f32::sqrt].
+    fn sqrt(self) -> Self;
+
+    /// Absolute value.
+    fn abs(self) -> Self;
+
+    /// The larger of two values, ignoring NaN like [f32::max].
+    fn max(self, other: Self) -> Self;
+
+    /// The smaller of two values, ignoring NaN like [f32::min].
+    fn min(self, other: Self) -> Self;
+
+    /// Check if this value is NaN.
+    fn is_nan(self) -> bool;
+
+    /// Check if this value is neither infinite nor NaN.
+    fn is_finite(self) -> bool;
+}
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const MIN: Self = f32::MIN;
+    const MAX: Self = f32::MAX;
+
+    fn from_f32(x: f32) -> Self {
+        x
+    }
+
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn to_bits(self) -> u64 {
+        u64::from(f32::to_bits(self))
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    fn next_up(self) -> Self {
+        f32::next_up(self)
+    }
+
+    fn round(self) -> Self {
+        #[cfg(feature = "std")]
+        {
+            f32::round(self)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            libm::roundf(self)
+        }
+    }
+
+    fn sqrt(self) -> Self {
+        #[cfg(feature = "std")]
+        {
+            f32::sqrt(self)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            libm::sqrtf(self)
+        }
+    }
+
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    fn min(self, other: Self) -> Self {
+        f32::min(self, other)
+    }
+
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const MIN: Self = f64::MIN;
+    const MAX: Self = f64::MAX;
+
+    fn from_f32(x: f32) -> Self {
+        f64::from(x)
+    }
+
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn to_bits(self) -> u64 {
+        f64::to_bits(self)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    fn next_up(self) -> Self {
+        f64::next_up(self)
+    }
+
+    fn round(self) -> Self {
+        #[cfg(feature = "std")]
+        {
+            f64::round(self)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            libm::round(self)
+        }
+    }
+
+    fn sqrt(self) -> Self {
+        #[cfg(feature = "std")]
+        {
+            f64::sqrt(self)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            libm::sqrt(self)
+        }
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    fn min(self, other: Self) -> Self {
+        f64::min(self, other)
+    }
+
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
@@ -0,0 +1,205 @@
+//! Optional [serde] support. An [Octree] only borrows its points, so it can be serialized directly, but it can not be
+//! deserialized on its own: there is nothing to borrow the points from. Instead, deserialize an [OctreeData], which
+//! owns the points, then borrow an [Octree] from it with [OctreeData::octree].
+
+use crate::{BoundingBox, Locatable, Octree, OctreeConfig, Scalar, TreeNode};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::Hash;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// The owned, serializable form of an [Octree]. It keeps the whole tree structure, so the [Octree] borrowed from it
+/// is the same as the one serialized. The callback registered by [Octree::on_change] is not kept.
+/// # Example
+/// ```
+/// use octree::point::Point3D;
+/// use octree::{Octree, OctreeData};
+///
+/// let points = vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(10.0, 10.0, 10.0)];
+/// let octree = Octree::new(points.iter().collect());
+///
+/// let json = serde_json::to_string(&octree).unwrap();
+/// let data: OctreeData<Point3D> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(data.octree(), Ok(octree));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OctreeData<L, T = f32> {
+    root: NodeData<L, T>,
+    config: OctreeConfig,
+    explicit_bounds: bool,
+}
+
+/// The serializable form of a [TreeNode], holding its points as `P` and its coordinates as `T`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct NodeData<P, T> {
+    children: Option<Vec<NodeData<P, T>>>,
+    bounding_box: BoundingBox<T>,
+    points: Vec<P>,
+    splitted: bool,
+    split_axes: u8,
+    closed_axes: u8,
+}
+
+impl<'point, L, T> Serialize for Octree<'point, L, T>
+where
+    L: Locatable<T> + Eq + Hash + Serialize,
+    T: Scalar + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        OctreeData {
+            root: NodeData::from_node(&self.root),
+            config: self.config,
+            explicit_bounds: self.explicit_bounds,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<L, T> OctreeData<L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    /// Borrow an [Octree] from the points owned by this [OctreeData], with the same structure as the one serialized.
+    /// Return an error if the data does not describe a valid [Octree], see [Octree::check_invariants].
+    /// # Example
+    /// ```
+    /// use octree::point::Point3D;
+    /// use octree::{Octree, OctreeData};
+    ///
+    /// let points = vec![Point3D::new(1.0, 2.0, 3.0)];
+    /// let json = serde_json::to_string(&Octree::new(points.iter().collect())).unwrap();
+    ///
+    /// let data: OctreeData<Point3D> = serde_json::from_str(&json).unwrap();
+    /// let octree = data.octree().unwrap();
+    /// assert_eq!(octree.len(), 1);
+    /// ```
+    pub fn octree(&self) -> Result<Octree<'_, L, T>, String> {
+        let octree = Octree {
+            root: self.root.to_node()?,
+            config: self.config,
+            explicit_bounds: self.explicit_bounds,
+            ..Octree::default()
+        };
+        octree.check_invariants()?;
+        Ok(octree)
+    }
+}
+
+impl<'point, L, T> NodeData<&'point L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn from_node(node: &TreeNode<'point, L, T>) -> Self {
+        Self {
+            children: node.children.as_ref().map(|children| {
+                children
+                    .iter()
+                    .map(|child| Self::from_node(child))
+                    .collect()
+            }),
+            bounding_box: node.bounding_box.clone(),
+            points: node.points.iter().copied().collect(),
+            splitted: node.splitted,
+            split_axes: node.split_axes,
+            closed_axes: node.closed_axes,
+        }
+    }
+}
+
+impl<L, T> NodeData<L, T>
+where
+    L: Locatable<T> + Eq + Hash,
+    T: Scalar,
+{
+    fn to_node(&self) -> Result<TreeNode<'_, L, T>, String> {
+        let children = match &self.children {
+            Some(children) => {
+                // One child for every octant used along the axes the node is splitted along.
+                let expected = 1 << self.split_axes.count_ones();
+                if children.len() != expected {
+                    return Err(format!(
+                        "a node has {} children instead of {expected}",
+                        children.len()
+                    ));
+                }
+                Some(
+                    children
+                        .iter()
+                        .map(|child| child.to_node())
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            None => None,
+        };
+        let mut node = TreeNode {
+            children,
+            bounding_box: self.bounding_box.clone(),
+            points: self.points.iter().collect(),
+            splitted: self.splitted,
+            split_axes: self.split_axes,
+            closed_axes: self.closed_axes,
+            subtree_count: 0,
+        };
+        node.recount();
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point3D;
+    use crate::{GrowthPolicy, OctreeBuilder};
+
+    #[test]
+    /// Should rebuild the same tree structure and settings from the serialized form.
+    fn test_round_trip() {
+        let points: Vec<Point3D> = (0..50)
+            .map(|i| Point3D::new(i as f32, (i * 7 % 13) as f32, (i * 3 % 11) as f32))
+            .collect();
+        let octree = OctreeBuilder::new()
+            .capacity(2)
+            .loose_factor(0.25)
+            .split_ratio(0.5)
+            .growth_policy(GrowthPolicy::Fit)
+            .build(points.iter().collect());
+
+        let json = serde_json::to_string(&octree).unwrap();
+        let data: OctreeData<Point3D> = serde_json::from_str(&json).unwrap();
+        let restored = data.octree().unwrap();
+
+        assert_eq!(restored, octree);
+        assert_eq!(restored.config(), octree.config());
+        assert!(!restored.explicit_bounds);
+        assert_eq!(restored.nodes().count(), octree.nodes().count());
+        assert_eq!(serde_json::to_string(&restored).unwrap().len(), json.len());
+    }
+
+    #[test]
+    /// Should reject data with a wrong number of children.
+    fn test_wrong_children() {
+        let points = [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(1.0, 1.0, 1.0),
+            Point3D::new(2.0, 2.0, 2.0),
+        ];
+        let octree = OctreeBuilder::new()
+            .capacity(1)
+            .build(points.iter().collect());
+
+        let mut value = serde_json::to_value(&octree).unwrap();
+        value["root"]["children"].as_array_mut().unwrap().pop();
+        let data: OctreeData<Point3D> = serde_json::from_value(value).unwrap();
+
+        assert_eq!(
+            data.octree().err(),
+            Some(String::from("a node has 7 children instead of 8"))
+        );
+    }
+}